@@ -0,0 +1,80 @@
+//! Synthetic sources for benchmarking.
+
+/// A small deterministic random number generator, so every run lexes the
+/// same corpus
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A random index below `len`
+    fn below(&mut self, len: usize) -> usize {
+        (self.next() % len as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+const IDENTS: &[&str] = &["x", "value", "count", "total_size", "buffer", "index", "parse_expression", "i"];
+const OPERATORS: &[&str] = &["+", "-", "*", "/", "%", "=", "==", "!=", "<", "<=", ">", ">="];
+const COMMENTS: &[&str] = &["// TODO: handle the empty case", "/* the length, in bytes */", "// see above"];
+
+/// Generates `len` bytes or slightly more of source code with a realistic
+/// mix of identifiers, numbers, operators, strings and comments
+pub fn generate(len: usize) -> String {
+    let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+    let mut source = String::with_capacity(len + 128);
+    let mut indent = 0;
+
+    while source.len() < len {
+        let line = rng.below(10);
+        let closes = line == 2 && indent > 0;
+        indent -= usize::from(closes);
+        source.extend((0..indent).map(|_| "    "));
+
+        match line {
+            0 => source.push_str(rng.pick(COMMENTS)),
+            1 if indent < 4 => {
+                source.push_str(rng.pick(&["if", "while", "fn"]));
+                source.push(' ');
+                source.push_str(rng.pick(IDENTS));
+                source.push_str(" (");
+                source.push_str(rng.pick(IDENTS));
+                source.push_str(") {");
+                indent += 1;
+            },
+            _ if closes => source.push('}'),
+            _ => {
+                source.push_str("let ");
+                source.push_str(rng.pick(IDENTS));
+                source.push_str(" = ");
+                for i in 0..=rng.below(6) {
+                    if i > 0 {
+                        source.push(' ');
+                        source.push_str(rng.pick(OPERATORS));
+                        source.push(' ');
+                    }
+                    match rng.below(4) {
+                        0 => source.push_str(&rng.next().to_string()[..1 + rng.below(6)]),
+                        1 => {
+                            source.push('"');
+                            source.push_str(rng.pick(IDENTS));
+                            source.push_str(" text\"");
+                        },
+                        _ => source.push_str(rng.pick(IDENTS))
+                    }
+                }
+            }
+        }
+        source.push('\n');
+    }
+
+    source
+}
@@ -0,0 +1,58 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use reusable_lexer::{Lexer, LexerBuilder, Token};
+use std::hint::black_box;
+
+mod corpus;
+
+/// The size of the corpus each benchmark lexes
+const CORPUS_LEN: usize = 1024 * 1024;
+
+fn builder() -> LexerBuilder {
+    LexerBuilder::new()
+        .keywords(["let", "fn", "if", "else", "while", "return"])
+        .string_delimiters(['"'])
+        .block_comments([("/*", "*/")])
+}
+
+fn lexing(c: &mut Criterion) {
+    let source = corpus::generate(CORPUS_LEN);
+    let mut group = c.benchmark_group("lexing");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+
+    group.bench_function("default", |b| b.iter(|| Lexer::new(black_box(&source)).count()));
+
+    let configured = builder();
+    group.bench_function("configured", |b| b.iter(|| configured.build(black_box(&source)).count()));
+
+    let trivia = builder().trivia(true);
+    group.bench_function("trivia", |b| b.iter(|| trivia.build(black_box(&source)).count()));
+
+    let indentation = builder().indentation(true);
+    group.bench_function("indentation", |b| b.iter(|| indentation.build(black_box(&source)).count()));
+
+    group.bench_function("tokenize_into", |b| {
+        b.iter_batched_ref(
+            Vec::<Token>::new,
+            |tokens| configured.build(black_box(&source)).tokenize_into(tokens),
+            BatchSize::LargeInput
+        )
+    });
+
+    group.bench_function("streaming", |b| {
+        b.iter(|| {
+            let mut lexer = configured.build_streaming();
+            let mut count = 0;
+            for chunk in source.as_bytes().chunks(8 * 1024) {
+                lexer.feed(std::str::from_utf8(chunk).unwrap_or_default());
+                count += lexer.by_ref().count();
+            }
+            lexer.finish();
+            count + lexer.count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, lexing);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+//! Writes a synthetic source to stdout for benchmarking, e.g.
+//! `cargo run --release --example corpus -- 100 > corpus.txt` for 100 MB.
+use std::io::Write;
+
+#[path = "../benches/corpus/mod.rs"]
+mod corpus;
+
+fn main() {
+    let megabytes = std::env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or(1);
+    let source = corpus::generate(megabytes * 1024 * 1024);
+    std::io::stdout().write_all(source.as_bytes()).expect("failed to write the corpus");
+}
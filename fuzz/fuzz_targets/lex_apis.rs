@@ -0,0 +1,20 @@
+//! Lexes arbitrary UTF-8 in pieces, on several threads and from any offset
+//! with each configuration.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use reusable_lexer_fuzz::{builders, check};
+
+fuzz_target!(|input: (&str, u16)| {
+    let (source, at) = input;
+    let is_boundary = |offset| source.is_char_boundary(offset);
+    let split = (0..=usize::from(at).min(source.len())).rev().find(|&i| source.is_char_boundary(i)).unwrap_or(0);
+    for builder in builders() {
+        check(source.len(), is_boundary, builder.tokenize_parallel(source, 3));
+        let fragments: Vec<_> = builder.build_fragments([&source[..split], &source[split..]]).collect();
+        check(source.len(), is_boundary, fragments.iter().map(|token| token.as_token()));
+
+        let mut lexer = builder.build(source);
+        lexer.seek(split);
+        check(source.len(), is_boundary, lexer);
+    }
+});
@@ -0,0 +1,11 @@
+//! Lexes arbitrary bytes, invalid UTF-8 included, with each configuration.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use reusable_lexer_fuzz::{builders, check};
+
+fuzz_target!(|bytes: &[u8]| {
+    for builder in builders() {
+        // Invalid sequences are error tokens, so any offset can be a boundary
+        check(bytes.len(), |_| true, builder.build_from_bytes_lossy(bytes));
+    }
+});
@@ -0,0 +1,10 @@
+//! Lexes arbitrary UTF-8 with each configuration.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use reusable_lexer_fuzz::{builders, check};
+
+fuzz_target!(|source: &str| {
+    for builder in builders() {
+        check(source.len(), |offset| source.is_char_boundary(offset), builder.build(source));
+    }
+});
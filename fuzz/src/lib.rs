@@ -0,0 +1,45 @@
+//! Configurations and invariants shared by the fuzz targets.
+use reusable_lexer::{LexerBuilder, Token};
+
+/// The settings each input is lexed with, covering every optional feature
+pub fn builders() -> Vec<LexerBuilder> {
+    let configured = LexerBuilder::new()
+        .keywords(["if", "else", "let"])
+        .line_comments(["//", "#"])
+        .block_comments([("/*", "*/")])
+        .string_delimiters(['"', '\''])
+        .interpolated_strings('`', "${", "}")
+        .operator("=>")
+        .operator("::")
+        .operator("&&");
+    vec![
+        LexerBuilder::new(),
+        configured.clone().trivia(true),
+        configured.clone().indentation(true).tab_width(4),
+        configured.clone().embedded_regions("<%", "%>").line_continuations(true).warnings(true),
+        configured.clone().max_token_len(8).max_nesting(4).increment_operators(true),
+        configured.newlines(true).lone_cr_newlines(true).unicode_idents()
+    ]
+}
+
+/// Checks the tokens lexed from a source of `len` bytes
+///
+/// Every span must lie within the source, start on a character boundary
+/// (as `is_boundary` tells) and follow the previous token's. Only layout
+/// tokens and errors at the end of an unterminated token may be empty, and
+/// there can't be more than two tokens a byte, so a lexer stuck in place is
+/// caught.
+pub fn check<'a>(len: usize, is_boundary: impl Fn(usize) -> bool, tokens: impl IntoIterator<Item = Token<'a>>) {
+    let mut end = 0;
+    let mut position = (0, 0);
+    for (count, token) in tokens.into_iter().enumerate() {
+        assert!(count <= 2 * len + 2, "too many tokens for {len} bytes");
+        let span = token.span;
+        assert!(end <= span.start && span.start <= span.end && span.end <= len, "{token:?} out of place after {end}");
+        assert!(is_boundary(span.start) && is_boundary(span.end), "{token:?} splits a character");
+        assert!(span.start < span.end || token.kind.is_layout() || token.kind.is_error(), "{token:?} is empty");
+        assert!(position <= token.position, "{token:?} is before {position:?}");
+        end = span.end;
+        position = token.position;
+    }
+}
@@ -0,0 +1,63 @@
+/* The C interface of reusable_lexer, built with the `ffi` feature:
+ *   cargo rustc --lib --release --features ffi --crate-type cdylib */
+#ifndef REUSABLE_LEXER_H
+#define REUSABLE_LEXER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The kinds of token */
+enum {
+    RL_KIND_OPR = 0,
+    RL_KIND_IDENT = 1,
+    RL_KIND_SYMBOL = 2,
+    RL_KIND_KEYWORD = 3,
+    RL_KIND_PUNCT = 4,
+    RL_KIND_NUM = 5,
+    RL_KIND_STR = 6,
+    RL_KIND_TEXT = 7,
+    RL_KIND_NEWLINE = 8,
+    RL_KIND_INDENT = 9,
+    RL_KIND_DEDENT = 10,
+    RL_KIND_WHITESPACE = 11,
+    RL_KIND_COMMENT = 12,
+    RL_KIND_OPENING_BRACKET = 13,
+    RL_KIND_CLOSING_BRACKET = 14,
+    RL_KIND_ERROR = 15,
+    RL_KIND_FLOAT = 16
+};
+
+/* A token, whose text is the bytes of the source from start to end */
+typedef struct RlToken {
+    uint32_t kind;
+    size_t start;
+    size_t end;
+    /* 1-based, with columns counted in codepoints */
+    size_t line;
+    size_t column;
+    /* The value of a number, 0 for other tokens */
+    int32_t value;
+} RlToken;
+
+/* The tokens of a source, freed with reusable_lexer_free_tokens */
+typedef struct RlTokenArray {
+    RlToken *tokens;
+    size_t len;
+} RlTokenArray;
+
+/* Lexes len bytes of source with the default settings, invalid UTF-8 giving
+ * error tokens */
+RlTokenArray reusable_lexer_tokenize(const uint8_t *source, size_t len);
+
+/* Frees tokens returned by reusable_lexer_tokenize */
+void reusable_lexer_free_tokens(RlTokenArray array);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,187 @@
+//! Derive macro for token enums lexed by `reusable_lexer`.
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Expr, Fields, Lifetime, LitStr, Token, Type};
+
+/// A pattern a variant is lexed from
+enum Pattern {
+    /// A literal string, from `#[token("...")]`
+    Token(LitStr),
+    /// A regular expression, from `#[regex("...")]`
+    Regex(LitStr)
+}
+
+/// The arguments of a `#[token]` or `#[regex]` attribute
+struct PatternArgs {
+    /// The pattern
+    pattern: LitStr,
+    /// The function converting the matched text into the variant's field
+    callback: Option<Expr>
+}
+
+impl Parse for PatternArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let pattern = input.parse()?;
+        let callback = if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self { pattern, callback })
+    }
+}
+
+/// A pattern along with the variant it produces
+struct Rule {
+    /// The pattern matched
+    pattern: Pattern,
+    /// The expression producing the token from the matched `text`
+    constructor: TokenStream2
+}
+
+/// Derives `Lexable` for an enum of tokens
+///
+/// Variants are annotated with `#[token("...")]` to match a literal string
+/// or `#[regex("...")]` to match a regular expression, and exactly one unit
+/// variant with `#[error]` to stand for text no pattern matches. Variants
+/// may carry one field, which is converted from the matched text with
+/// `From<&str>` or with the function given after the pattern, as in
+/// `#[regex("[0-9]+", |text| text.parse().unwrap())]`.
+///
+/// The longest match wins, ties going to the pattern declared first.
+#[proc_macro_derive(Lexable, attributes(token, regex, error))]
+pub fn derive_lexable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(&input.ident, "`Lexable` can only be derived for enums"));
+    };
+
+    let lifetime = input.generics
+        .lifetimes()
+        .next()
+        .map(|param| param.lifetime.clone())
+        .unwrap_or_else(|| Lifetime::new("'source", Span::call_site()));
+    let mut rules = Vec::new();
+    let mut error = None;
+
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let field = match &variant.fields {
+            Fields::Unit => None,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some(&fields.unnamed[0].ty),
+            fields => return Err(Error::new_spanned(fields, "token variants can have at most one unnamed field"))
+        };
+
+        for attr in &variant.attrs {
+            if attr.path().is_ident("error") {
+                if field.is_some() {
+                    return Err(Error::new_spanned(variant, "the `#[error]` variant can't have fields"));
+                }
+                if error.replace(ident).is_some() {
+                    return Err(Error::new_spanned(attr, "only one variant can be `#[error]`"));
+                }
+                continue;
+            }
+
+            let regex = attr.path().is_ident("regex");
+            if !regex && !attr.path().is_ident("token") {
+                continue;
+            }
+            let args: PatternArgs = attr.parse_args()?;
+            if regex {
+                if let Err(err) = regex_syntax::Parser::new().parse(&args.pattern.value()) {
+                    return Err(Error::new_spanned(&args.pattern, format!("invalid regex: {err}")));
+                }
+            }
+
+            let constructor = match (field, &args.callback) {
+                (None, None) => quote!(Self::#ident),
+                (None, Some(callback)) => {
+                    return Err(Error::new_spanned(callback, "unit variants don't take a callback"));
+                },
+                (Some(ty), callback) => field_constructor(ident, ty, callback.as_ref(), &lifetime)
+            };
+            let pattern = if regex {
+                Pattern::Regex(args.pattern)
+            } else {
+                Pattern::Token(args.pattern)
+            };
+            rules.push(Rule { pattern, constructor });
+        }
+    }
+
+    let Some(error) = error else {
+        return Err(Error::new_spanned(&input.ident, "one unit variant must be marked `#[error]`"));
+    };
+
+    let lens = rules.iter().map(|rule| match &rule.pattern {
+        Pattern::Token(token) => quote! {
+            if rest.starts_with(#token) { #token.len() } else { 0 }
+        },
+        Pattern::Regex(regex) => {
+            let anchored = LitStr::new(&format!("^(?:{})", regex.value()), regex.span());
+            quote! {{
+                static REGEX: ::std::sync::OnceLock<::reusable_lexer::__private::Regex> = ::std::sync::OnceLock::new();
+                REGEX
+                    .get_or_init(|| ::reusable_lexer::__private::Regex::new(#anchored).unwrap())
+                    .find(rest)
+                    .map_or(0, |found| found.end())
+            }}
+        }
+    });
+    let constructors = rules.iter().enumerate().map(|(i, rule)| {
+        let constructor = &rule.constructor;
+        quote!(#i => #constructor,)
+    });
+
+    let name = &input.ident;
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let impl_generics = if input.generics.lifetimes().next().is_some() {
+        let (impl_generics, _, _) = input.generics.split_for_impl();
+        quote!(#impl_generics)
+    } else {
+        let params = input.generics.params.iter();
+        quote!(<#lifetime, #(#params),*>)
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::reusable_lexer::Lexable<#lifetime> for #name #ty_generics #where_clause {
+            fn lex(rest: &#lifetime str) -> ::core::option::Option<(Self, usize)> {
+                let lens: &[usize] = &[#(#lens),*];
+                let (rule, len) = lens
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .fold((None, 0), |best, (i, len)| if len > best.1 { (Some(i), len) } else { best });
+                let text: &#lifetime str = &rest[..len];
+                let token = match rule? {
+                    #(#constructors)*
+                    _ => return ::core::option::Option::None
+                };
+                let _ = text;
+                Some((token, len))
+            }
+
+            fn error() -> Self {
+                Self::#error
+            }
+        }
+    })
+}
+
+/// The expression producing a variant with a field from the matched `text`
+fn field_constructor(ident: &syn::Ident, ty: &Type, callback: Option<&Expr>, lifetime: &Lifetime) -> TokenStream2 {
+    match callback {
+        Some(callback) => quote! {{
+            let callback: fn(&#lifetime str) -> #ty = #callback;
+            Self::#ident(callback(text))
+        }},
+        None => quote!(Self::#ident(::core::convert::From::from(text)))
+    }
+}
@@ -0,0 +1,108 @@
+//! Python bindings for `reusable_lexer`, built with maturin.
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::IntoPyObjectExt;
+use reusable_lexer::{LexerBuilder, OwnedTokenKind, SharedLexer, SharedToken};
+
+/// A lexer over a source string, iterating over its tokens
+#[pyclass(name = "Lexer", module = "reusable_lexer")]
+struct PyLexer {
+    lexer: SharedLexer
+}
+
+#[pymethods]
+impl PyLexer {
+    /// Creates a lexer over a source string, recognizing `keywords` and
+    /// producing whitespace and comments as tokens if `trivia` is true
+    #[new]
+    #[pyo3(signature = (source, keywords = Vec::new(), trivia = false))]
+    fn new(source: &str, keywords: Vec<String>, trivia: bool) -> Self {
+        let builder = LexerBuilder::new().keywords(keywords).trivia(trivia);
+        Self { lexer: builder.build_shared(source) }
+    }
+
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<PyToken> {
+        self.lexer.next().map(PyToken::new)
+    }
+}
+
+/// A token, with its kind's name and the text it was lexed from
+#[pyclass(name = "Token", module = "reusable_lexer", frozen, get_all)]
+struct PyToken {
+    /// The name of the token's kind, e.g. `"Ident"`
+    kind: &'static str,
+    /// The exact source text the token was lexed from
+    text: String,
+    /// The value of a number or the text a string, identifier or other
+    /// token holds, `None` for tokens that hold nothing
+    value: Option<PyObject>,
+    /// The byte offset of the token's first byte
+    start: usize,
+    /// The byte offset just past the token's last byte
+    end: usize,
+    /// The 1-based line the token starts on
+    line: usize,
+    /// The 1-based column the token starts at
+    column: usize
+}
+
+impl PyToken {
+    fn new(token: SharedToken) -> Self {
+        let value = Python::with_gil(|py| match &token.kind {
+            OwnedTokenKind::Num(value) => value.into_py_any(py).ok(),
+            OwnedTokenKind::Float(text) => text.parse::<f64>().ok().and_then(|value| value.into_py_any(py).ok()),
+            OwnedTokenKind::Ident(text) | OwnedTokenKind::Keyword(text) | OwnedTokenKind::Punct(text)
+            | OwnedTokenKind::Str(text) | OwnedTokenKind::Text(text) | OwnedTokenKind::Whitespace(text)
+            | OwnedTokenKind::Comment(text) => text.into_py_any(py).ok(),
+            OwnedTokenKind::Opr(op) => op.to_string().into_py_any(py).ok(),
+            OwnedTokenKind::Error(kind) => kind.to_string().into_py_any(py).ok(),
+            _ => None
+        });
+        Self {
+            kind: token.kind.as_kind().name(),
+            text: token.text().to_string(),
+            value,
+            start: token.span.start,
+            end: token.span.end,
+            line: token.position.0,
+            column: token.position.1
+        }
+    }
+}
+
+#[pymethods]
+impl PyToken {
+    fn __repr__(&self) -> String {
+        format!("Token({}, {:?}, {}:{})", self.kind, self.text, self.line, self.column)
+    }
+}
+
+/// Lexes a source string into a list of tokens
+///
+/// Raises `ValueError` at the first lexical error if `strict` is true.
+#[pyfunction]
+#[pyo3(signature = (source, keywords = Vec::new(), trivia = false, strict = false))]
+fn tokenize(source: &str, keywords: Vec<String>, trivia: bool, strict: bool) -> PyResult<Vec<PyToken>> {
+    let builder = LexerBuilder::new().keywords(keywords).trivia(trivia);
+    builder
+        .build_shared(source)
+        .map(|token| match &token.kind {
+            OwnedTokenKind::Error(kind) if strict => {
+                let (line, column) = token.position;
+                Err(PyValueError::new_err(format!("{kind} at {line}:{column}")))
+            },
+            _ => Ok(PyToken::new(token))
+        })
+        .collect()
+}
+
+#[pymodule(name = "reusable_lexer")]
+fn python_module(module: &Bound<PyModule>) -> PyResult<()> {
+    module.add_class::<PyLexer>()?;
+    module.add_class::<PyToken>()?;
+    module.add_function(wrap_pyfunction!(tokenize, module)?)
+}
@@ -0,0 +1,121 @@
+//! Classifying ASCII characters with tables instead of predicates.
+use crate::builder::Config;
+use crate::line_index::next_column;
+use crate::Lexer;
+
+/// The kind of token a character starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenStart {
+    String,
+    Ident,
+    Number,
+    OpeningBracket,
+    ClosingBracket,
+    /// An operator, a token of the fallback rule or an error
+    Other
+}
+
+impl TokenStart {
+    /// Finds the kind of token a character starts
+    fn of(ch: char, config: &Config) -> Self {
+        if config.string_delimiters.contains(&ch) {
+            return Self::String;
+        }
+        if (config.ident_start)(ch) {
+            return Self::Ident;
+        }
+
+        match ch {
+            '0'..='9' => Self::Number,
+            '(' => Self::OpeningBracket,
+            ')' => Self::ClosingBracket,
+            _ => Self::Other
+        }
+    }
+}
+
+/// The classes of each ASCII character and the tokens they start, as
+/// configured
+///
+/// Line breaks are in no class, so runs of a class never change rows.
+#[derive(Debug, Clone)]
+pub(crate) struct AsciiClasses {
+    classes: [u8; 128],
+    starts: [TokenStart; 128]
+}
+
+impl AsciiClasses {
+    /// Characters that can continue an identifier
+    pub const IDENT_CONTINUE: u8 = 1;
+    /// Characters skipped as whitespace
+    pub const WHITESPACE: u8 = 2;
+    /// Decimal digits
+    pub const DIGIT: u8 = 4;
+
+    /// A table to fill in with [`AsciiClasses::new`]
+    pub const EMPTY: Self = Self { classes: [0; 128], starts: [TokenStart::Other; 128] };
+
+    /// Classifies the ASCII characters with the configured predicates
+    pub fn new(config: &Config) -> Self {
+        let mut table = Self::EMPTY;
+        for (byte, (class, start)) in (0..).zip(table.classes.iter_mut().zip(&mut table.starts)) {
+            let ch = char::from(byte);
+            *start = TokenStart::of(ch, config);
+            if ch == '\n' || ch == '\r' {
+                continue;
+            }
+            if (config.ident_continue)(ch) {
+                *class |= Self::IDENT_CONTINUE;
+            }
+            if (config.is_whitespace)(ch) {
+                *class |= Self::WHITESPACE;
+            }
+            if ch.is_ascii_digit() {
+                *class |= Self::DIGIT;
+            }
+        }
+        table
+    }
+
+    /// Is a byte an ASCII character of a class?
+    #[inline]
+    pub fn is(&self, byte: u8, class: u8) -> bool {
+        self.classes.get(usize::from(byte)).is_some_and(|classes| classes & class != 0)
+    }
+}
+
+impl Lexer<'_> {
+    /// Skips a run of ASCII characters of a class without decoding them
+    ///
+    /// The run stops before the last ASCII character ahead of a non-ASCII
+    /// one, which may combine with it into one column, and before the end of
+    /// the source. The rest of the run is left to [`Lexer::next_char`].
+    pub(crate) fn skip_ascii(&mut self, class: u8) {
+        let bytes = self.source.as_bytes();
+        let mut end = self.pos;
+        while end + 1 < bytes.len() && bytes[end + 1].is_ascii() && self.config.ascii.is(bytes[end], class) {
+            end += 1;
+        }
+        if end == self.pos || self.eof {
+            return;
+        }
+
+        let config = &self.config;
+        for &byte in &bytes[self.pos..end] {
+            self.col = next_column(self.col, char::from(byte), config.encoding, config.tab_width);
+        }
+        self.move_to(end);
+    }
+
+    /// The kind of token the current character starts
+    ///
+    /// ASCII characters are looked up in a table, others classified with the
+    /// configured predicates.
+    #[inline]
+    pub(crate) fn token_start(&self) -> TokenStart {
+        match self.config.ascii.starts.get(self.prev as usize) {
+            Some(&start) => start,
+            None => TokenStart::of(self.prev, &self.config)
+        }
+    }
+}
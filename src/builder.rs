@@ -0,0 +1,683 @@
+//! Configuration for constructing lexers.
+use crate::ascii::AsciiClasses;
+use crate::operators::OperatorTable;
+use crate::{IdentCase, KeywordTable, Lexer, Op, PositionEncoding, Token, TokenKind};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The settings a lexer is built with
+#[derive(Debug, Clone)]
+pub(crate) struct Config {
+    /// The unit columns are counted in
+    pub encoding: PositionEncoding,
+    /// The distance between tab stops
+    pub tab_width: usize,
+    /// Whether a `\r` not followed by `\n` ends a line
+    pub lone_cr_newlines: bool,
+    /// Whether a leading byte-order mark is skipped
+    pub skip_bom: bool,
+    /// The numbers the first row and column are reported as
+    pub base: (usize, usize),
+    /// Identifiers lexed as keywords
+    pub keywords: KeywordTable<()>,
+    /// Whether keywords are matched regardless of case
+    pub case_insensitive_keywords: bool,
+    /// Whether identifiers are folded to lowercase
+    pub fold_ident_case: bool,
+    /// Prefixes starting a comment that runs to the end of the line
+    pub line_comments: Vec<String>,
+    /// Delimiter pairs surrounding a block comment
+    pub block_comments: Vec<(String, String)>,
+    /// Characters that open and close a string literal
+    pub string_delimiters: Vec<char>,
+    /// Whether a doubled quote escapes the quote instead of a backslash
+    pub doubled_quotes: bool,
+    /// The operators that are recognised
+    pub operators: OperatorTable,
+    /// Which characters are skipped as whitespace
+    pub is_whitespace: fn(char) -> bool,
+    /// Whether a `\` at the end of a line joins it to the next
+    pub line_continuations: bool,
+    /// Whether newlines and indentation produce layout tokens
+    pub indentation: bool,
+    /// Whether newlines produce layout tokens
+    pub newlines: bool,
+    /// Whether indentation depending on the width of tabs is an error
+    pub tab_errors: bool,
+    /// Whether whitespace and comments are produced as tokens
+    pub trivia: bool,
+    /// Whether comments are collected on the side
+    pub collect_comments: bool,
+    /// Whether warnings are collected on the side
+    pub warnings: bool,
+    /// Whether figures about the source are collected on the side
+    pub stats: bool,
+    /// The naming conventions identifiers are warned about not following
+    pub naming_conventions: Vec<IdentCase>,
+    /// The quote, opener and closer of interpolated strings
+    pub interpolation: Option<(char, String, String)>,
+    /// The delimiters of the regions lexed in a template
+    pub regions: Option<(String, String)>,
+    /// Which characters can start an identifier
+    pub ident_start: fn(char) -> bool,
+    /// Which characters can continue an identifier
+    pub ident_continue: fn(char) -> bool,
+    /// The classes of ASCII characters, kept in sync with the settings
+    /// classifying characters
+    pub ascii: AsciiClasses,
+    /// Whether non-ASCII decimal digits in numbers count for their value
+    pub convert_digits: bool,
+    /// Whether a `-` in prefix position is folded into the number after it
+    pub negative_literals: bool,
+    /// Lexes project-specific tokens no other rule matches
+    pub fallback: Option<for<'a> fn(&mut Lexer<'a>) -> Option<TokenKind<'a>>>,
+    /// Lexes number literals in place of the built-in integers
+    pub number_rule: Option<for<'a> fn(&mut Lexer<'a>) -> Option<TokenKind<'a>>>,
+    /// The longest source lexed, in bytes
+    pub max_source_len: Option<usize>,
+    /// The longest token lexed, in bytes
+    pub max_token_len: Option<usize>,
+    /// The most delimiters and modes open at once
+    pub max_nesting: Option<usize>,
+    /// Called with every token lexed and every comment skipped
+    pub observer: Option<Observer>,
+    /// Asked every so often whether to stop lexing
+    pub cancel: Option<Cancel>,
+    /// Whether identifiers are normalized to NFC
+    #[cfg(feature = "unicode")]
+    pub normalize_idents: bool
+}
+
+impl Config {
+    /// Does the lexer produce layout tokens?
+    #[inline]
+    pub fn layout(&self) -> bool {
+        self.indentation || self.newlines
+    }
+
+    /// The most bytes lexing a token can look at from where it ends, the
+    /// length of the longest operator or delimiter matched by its prefix
+    pub fn lookahead(&self) -> usize {
+        let comments = self.line_comments.iter().chain(self.block_comments.iter().flat_map(|(open, close)| [open, close]));
+        let interpolation = self.interpolation.iter().flat_map(|(_, open, close)| [open, close]);
+        let regions = self.regions.iter().flat_map(|(open, close)| [open, close]);
+        comments.chain(interpolation).chain(regions).map(String::len).fold(self.operators.longest(), usize::max).max(1)
+    }
+
+    /// Reclassifies ASCII characters after a setting classifying them changed
+    pub fn classify_ascii(&mut self) {
+        self.ascii = AsciiClasses::new(self);
+    }
+}
+
+/// Whether a character can start an identifier by default
+fn default_ident_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
+}
+
+/// Whether a character can continue an identifier by default
+fn default_ident_continue(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut config = Self {
+            encoding: PositionEncoding::default(),
+            tab_width: 1,
+            lone_cr_newlines: false,
+            skip_bom: true,
+            base: (1, 1),
+            keywords: KeywordTable::new(),
+            case_insensitive_keywords: false,
+            fold_ident_case: false,
+            line_comments: vec!["//".to_string()],
+            block_comments: Vec::new(),
+            string_delimiters: Vec::new(),
+            doubled_quotes: false,
+            operators: OperatorTable::default(),
+            is_whitespace: char::is_whitespace,
+            line_continuations: false,
+            indentation: false,
+            newlines: false,
+            tab_errors: false,
+            trivia: false,
+            collect_comments: false,
+            warnings: false,
+            stats: false,
+            naming_conventions: Vec::new(),
+            interpolation: None,
+            regions: None,
+            ident_start: default_ident_start,
+            ident_continue: default_ident_continue,
+            ascii: AsciiClasses::EMPTY,
+            convert_digits: false,
+            negative_literals: false,
+            fallback: None,
+            number_rule: None,
+            max_source_len: None,
+            max_token_len: None,
+            max_nesting: None,
+            observer: None,
+            cancel: None,
+            #[cfg(feature = "unicode")]
+            normalize_idents: false
+        };
+        config.classify_ascii();
+        config
+    }
+}
+
+/// A callback observing tokens as they're lexed
+#[derive(Clone)]
+pub(crate) struct Observer(pub Arc<dyn Fn(&Token) + Send + Sync>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+/// A callback telling lexers whether the job they're doing was cancelled
+#[derive(Clone)]
+pub(crate) struct Cancel(pub Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl fmt::Debug for Cancel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Cancel")
+    }
+}
+
+/// Collects lexing options and produces configured lexers
+///
+/// A builder can be reused to create any number of lexers with the same
+/// settings.
+#[derive(Debug, Clone, Default)]
+pub struct LexerBuilder {
+    pub(crate) config: Arc<Config>
+}
+
+impl LexerBuilder {
+    /// Creates a builder with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The settings being built, copied first if a lexer still shares them
+    #[inline]
+    pub(crate) fn config_mut(&mut self) -> &mut Config {
+        Arc::make_mut(&mut self.config)
+    }
+
+    /// Sets the unit reported columns are counted in
+    ///
+    /// With the `unicode` feature, `PositionEncoding::Graphemes` counts
+    /// extended grapheme clusters instead of individual characters.
+    pub fn encoding(mut self, encoding: PositionEncoding) -> Self {
+        self.config_mut().encoding = encoding;
+        self
+    }
+
+    /// Sets the distance between tab stops
+    ///
+    /// Tabs advance the column to the next tab stop, as editors display them.
+    /// A width of 1, the default, counts each tab as a single column.
+    pub fn tab_width(mut self, tab_width: usize) -> Self {
+        self.config_mut().tab_width = tab_width;
+        self
+    }
+
+    /// Sets whether a `\r` not followed by `\n` ends a line
+    ///
+    /// `\r\n` is always treated as a single line ending. Enabling this also
+    /// accepts the lone `\r` line endings of old Mac files.
+    pub fn lone_cr_newlines(mut self, enabled: bool) -> Self {
+        self.config_mut().lone_cr_newlines = enabled;
+        self
+    }
+
+    /// Sets whether a leading UTF-8 byte-order mark is skipped
+    ///
+    /// Enabled by default. When disabled the mark is lexed like any other
+    /// character.
+    pub fn skip_bom(mut self, enabled: bool) -> Self {
+        self.config_mut().skip_bom = enabled;
+        self
+    }
+
+    /// Sets the numbers the first row and column are reported as
+    ///
+    /// Positions are 1-based for both by default; use `(0, 0)` for 0-based
+    /// positions.
+    pub fn position_base(mut self, row: usize, col: usize) -> Self {
+        self.config_mut().base = (row, col);
+        self
+    }
+
+    /// Sets the identifiers lexed as keywords
+    pub fn keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>
+    {
+        self.config_mut().keywords = keywords.into_iter().map(|keyword| (keyword.into(), ())).collect();
+        self
+    }
+
+    /// Sets whether keywords are matched regardless of case
+    ///
+    /// Keyword tokens keep the spelling used in the source.
+    pub fn case_insensitive_keywords(mut self, enabled: bool) -> Self {
+        self.config_mut().case_insensitive_keywords = enabled;
+        self
+    }
+
+    /// Sets whether identifiers are folded to lowercase, for case-insensitive
+    /// languages
+    ///
+    /// `Ident` tokens then hold the folded name for symbol tables to compare,
+    /// while the spelling as written, for error messages, is the token's text
+    /// (see [`Lexer::spanned_text`]). Identifiers only own their text when
+    /// folding changed it. Keywords are matched as set by
+    /// [`LexerBuilder::case_insensitive_keywords`].
+    pub fn fold_ident_case(mut self, enabled: bool) -> Self {
+        self.config_mut().fold_ident_case = enabled;
+        self
+    }
+
+    /// Sets the prefixes that start a comment running to the end of the line
+    ///
+    /// Defaults to `//`.
+    pub fn line_comments<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>
+    {
+        self.config_mut().line_comments = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the delimiter pairs that surround a block comment, e.g. `("/*", "*/")`
+    ///
+    /// There are no block comments by default.
+    pub fn block_comments<I, S>(mut self, delimiters: I) -> Self
+    where
+        I: IntoIterator<Item = (S, S)>,
+        S: Into<String>
+    {
+        self.config_mut().block_comments = delimiters
+            .into_iter()
+            .map(|(open, close)| (open.into(), close.into()))
+            .collect();
+        self
+    }
+
+    /// Sets the characters that open and close a string literal
+    ///
+    /// A backslash escapes the character following it. There are no string
+    /// literals by default.
+    pub fn string_delimiters<I>(mut self, delimiters: I) -> Self
+    where
+        I: IntoIterator<Item = char>
+    {
+        let config = self.config_mut();
+        config.string_delimiters = delimiters.into_iter().collect();
+        config.classify_ascii();
+        self
+    }
+
+    /// Sets whether a quote is escaped by doubling it, as in SQL or Pascal
+    ///
+    /// `'it''s'` is then a single string, and backslashes are ordinary
+    /// characters. String tokens hold the text as written, with the quotes
+    /// still doubled.
+    pub fn doubled_quote_escapes(mut self, enabled: bool) -> Self {
+        self.config_mut().doubled_quotes = enabled;
+        self
+    }
+
+    /// Sets whether indentation is significant, as in Python or Haskell
+    ///
+    /// Lines with tokens on them end in a `Newline` token, and changes in the
+    /// indentation of a line's first token produce `Indent` and `Dedent`
+    /// tokens. Dedenting to a width no enclosing block has is an error.
+    /// Blank lines, comment-only lines and lines inside brackets are ignored.
+    pub fn indentation(mut self, enabled: bool) -> Self {
+        self.config_mut().indentation = enabled;
+        self
+    }
+
+    /// Sets whether indentation that's only consistent with its block's for
+    /// some widths of tab is an error, as Python's `TabError`
+    ///
+    /// A line indented more than its block with tabs one column wide but not
+    /// with tabs eight columns wide, or the other way around, is followed by
+    /// a [`LexErrorKind::InconsistentTabs`](crate::LexErrorKind::InconsistentTabs)
+    /// error. Otherwise it's a
+    /// [`LexWarningKind::MixedIndentation`](crate::LexWarningKind::MixedIndentation)
+    /// warning, if checking for them.
+    pub fn tab_errors(mut self, enabled: bool) -> Self {
+        self.config_mut().tab_errors = enabled;
+        self
+    }
+
+    /// Sets whether the ends of lines are significant, as in Go or JavaScript
+    ///
+    /// Lines with tokens on them end in a `Newline` token instead of the line
+    /// break being skipped as whitespace. Blank lines, comment-only lines and
+    /// lines inside brackets are ignored.
+    pub fn newlines(mut self, enabled: bool) -> Self {
+        self.config_mut().newlines = enabled;
+        self
+    }
+
+    /// Sets whether a `\` at the end of a line joins it to the next, as in C
+    /// macros and shell commands
+    ///
+    /// The backslash and line break are skipped as whitespace, so they don't
+    /// end a line of layout or a directive.
+    pub fn line_continuations(mut self, enabled: bool) -> Self {
+        self.config_mut().line_continuations = enabled;
+        self
+    }
+
+    /// Sets whether whitespace and comments are kept as trivia tokens
+    ///
+    /// Instead of being skipped, they're produced as `Whitespace` and
+    /// `Comment` tokens, so formatters and refactoring tools can reconstruct
+    /// the source. A skipped byte-order mark is part of the first whitespace
+    /// token.
+    pub fn trivia(mut self, enabled: bool) -> Self {
+        self.config_mut().trivia = enabled;
+        self
+    }
+
+    /// Sets whether comments are collected as they're lexed, to read with
+    /// [`Lexer::comments`] even when they're skipped
+    ///
+    /// For documentation extractors and license-header checkers that need
+    /// the comments but not the rest of the trivia.
+    pub fn collect_comments(mut self, enabled: bool) -> Self {
+        self.config_mut().collect_comments = enabled;
+        self
+    }
+
+    /// Sets whether the lexer checks for source text that lexes but is
+    /// likely a mistake, to read with [`Lexer::warnings`]
+    ///
+    /// See [`LexWarningKind`](crate::LexWarningKind) for what's checked.
+    pub fn warnings(mut self, enabled: bool) -> Self {
+        self.config_mut().warnings = enabled;
+        self
+    }
+
+    /// Sets whether the lexer collects figures about the source as it goes,
+    /// to read with [`Lexer::stats`]
+    ///
+    /// For analysis tools that want token counts and line counts without a
+    /// second pass.
+    pub fn collect_stats(mut self, enabled: bool) -> Self {
+        self.config_mut().stats = enabled;
+        self
+    }
+
+    /// Sets the naming conventions identifiers should follow, warning about
+    /// those following none of them
+    ///
+    /// E.g. `[IdentCase::Snake, IdentCase::Pascal, IdentCase::ScreamingSnake]`
+    /// for Rust's variables, types and constants. The warnings are read with
+    /// [`Lexer::warnings`], whether or not other warnings are enabled. No
+    /// conventions are checked by default.
+    pub fn naming_conventions(mut self, conventions: impl IntoIterator<Item = IdentCase>) -> Self {
+        self.config_mut().naming_conventions = conventions.into_iter().collect();
+        self
+    }
+
+    /// Lexes strings opened by `quote` as interpolated strings
+    ///
+    /// The string's contents are lexed in a [`Mode::Interpolated`](crate::Mode::Interpolated) mode, in
+    /// which `open` and `close` surround embedded tokens, e.g. `'"', "${", "}"`.
+    pub fn interpolated_strings(mut self, quote: char, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.config_mut().interpolation = Some((quote, open.into(), close.into()));
+        self
+    }
+
+    /// Lexes only the regions between `open` and `close`, e.g. `<%` and `%>`
+    /// in a template or `` ``` `` around fenced code blocks
+    ///
+    /// Lexers start in a [`Mode::Template`](crate::Mode::Template) mode, in
+    /// which the host text outside the regions is lexed as
+    /// [`TokenKind::Text`](crate::TokenKind::Text) and the delimiters as
+    /// [`TokenKind::Punct`](crate::TokenKind::Punct). Regions are lexed with
+    /// the other settings.
+    pub fn embedded_regions(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.config_mut().regions = Some((open.into(), close.into()));
+        self
+    }
+
+    /// Sets the built-in operators that are recognised, with their default spellings
+    ///
+    /// All built-in operators but `++` and `--` are recognised by default.
+    /// Operators added with [`LexerBuilder::operator`] are kept.
+    pub fn operators<I>(mut self, operators: I) -> Self
+    where
+        I: IntoIterator<Item = Op>
+    {
+        let table = &mut self.config_mut().operators;
+        table.clear_builtin();
+        table.insert_builtin(operators);
+        self
+    }
+
+    /// Sets whether `++` and `--` are lexed as [`Op::Increment`] and
+    /// [`Op::Decrement`]
+    ///
+    /// Disabled by default, so `--x` is two `-` operators, as languages
+    /// without them expect. The longest operator is always lexed, so `+++`
+    /// is `++` followed by `+`.
+    pub fn increment_operators(mut self, enabled: bool) -> Self {
+        let table = &mut self.config_mut().operators;
+        if enabled {
+            table.insert_builtin([Op::Increment, Op::Decrement]);
+        } else {
+            table.remove_builtin(&[Op::Increment, Op::Decrement]);
+        }
+        self
+    }
+
+    /// Adds an operator, lexed as a [`TokenKind::Punct`](crate::TokenKind::Punct)
+    ///
+    /// Operators may be any string, e.g. `<=>` or `::=`. The longest
+    /// operator matching the source is always the one lexed.
+    pub fn operator(mut self, symbol: impl Into<String>) -> Self {
+        self.config_mut().operators.insert(symbol.into(), None);
+        self
+    }
+
+    /// Adds a spelling for a built-in operator, e.g. `==` for [`Op::Equal`]
+    pub fn operator_as(mut self, symbol: impl Into<String>, op: Op) -> Self {
+        self.config_mut().operators.insert(symbol.into(), Some(op));
+        self
+    }
+
+    /// Sets which characters are skipped as whitespace
+    ///
+    /// Defaults to [`char::is_whitespace`], which includes line breaks, form
+    /// feeds and no-break spaces. Other characters are lexed as tokens, or as
+    /// unexpected. When producing `Newline` or indentation tokens, line breaks
+    /// are part of the layout whether they count as whitespace or not.
+    pub fn whitespace(mut self, is_whitespace: fn(char) -> bool) -> Self {
+        let config = self.config_mut();
+        config.is_whitespace = is_whitespace;
+        config.classify_ascii();
+        self
+    }
+
+    /// Sets which characters can start an identifier
+    ///
+    /// Defaults to ASCII letters and `_`.
+    pub fn ident_start(mut self, ident_start: fn(char) -> bool) -> Self {
+        let config = self.config_mut();
+        config.ident_start = ident_start;
+        config.classify_ascii();
+        self
+    }
+
+    /// Sets which characters can continue an identifier
+    ///
+    /// Defaults to alphanumeric characters and `_`. Allowing characters such
+    /// as `-` here gives lisp-case identifiers.
+    pub fn ident_continue(mut self, ident_continue: fn(char) -> bool) -> Self {
+        let config = self.config_mut();
+        config.ident_continue = ident_continue;
+        config.classify_ascii();
+        self
+    }
+
+    /// Sets a rule for project-specific tokens, e.g. `#?` or emoji operators
+    ///
+    /// The rule is tried on characters that no other rule matches, before
+    /// reporting them as unexpected. It consumes the token with
+    /// [`Lexer::next_char`] and returns its kind, or returns `None` without
+    /// consuming anything. A token always covers at least the character it
+    /// starts at.
+    pub fn fallback(mut self, rule: for<'a> fn(&mut Lexer<'a>) -> Option<TokenKind<'a>>) -> Self {
+        self.config_mut().fallback = Some(rule);
+        self
+    }
+
+    /// Sets a rule for number literals, e.g. with fractions, exponents or
+    /// hexadecimal digits
+    ///
+    /// The rule is tried on tokens starting with an ASCII digit, and works
+    /// like a [fallback rule](LexerBuilder::fallback). Numbers it returns
+    /// `None` for are lexed as decimal integers.
+    pub fn number_rule(mut self, rule: for<'a> fn(&mut Lexer<'a>) -> Option<TokenKind<'a>>) -> Self {
+        self.config_mut().number_rule = Some(rule);
+        self
+    }
+
+    /// Sets whether non-ASCII decimal digits in numbers, e.g. `١٢٣`, count
+    /// for their value
+    ///
+    /// Numbers start with an ASCII digit but continue with any numeric
+    /// character. By default a number with non-ASCII digits is a
+    /// [`LexErrorKind::NonAsciiDigit`](crate::LexErrorKind::NonAsciiDigit)
+    /// error. When enabled, decimal digits of other scripts are converted,
+    /// while other numeric characters such as `²` are still errors.
+    pub fn convert_non_ascii_digits(mut self, enabled: bool) -> Self {
+        self.config_mut().convert_digits = enabled;
+        self
+    }
+
+    /// Sets whether a `-` directly before a number is part of it, where it
+    /// can't be an infix operator
+    ///
+    /// The `-` is folded into the number unless the token before it can end
+    /// an operand, as told by [`TokenKind::ends_operand`], so `x-1` is still
+    /// a subtraction while `f(-1)` and `return -1` have negative literals.
+    pub fn negative_literals(mut self, enabled: bool) -> Self {
+        self.config_mut().negative_literals = enabled;
+        self
+    }
+
+    /// Limits the length of sources lexed, in bytes
+    ///
+    /// A longer source is lexed as a single
+    /// [`LexErrorKind::SourceTooLong`](crate::LexErrorKind::SourceTooLong)
+    /// error, so services lexing untrusted input can bound the work done.
+    /// There is no limit by default.
+    pub fn max_source_len(mut self, limit: usize) -> Self {
+        self.config_mut().max_source_len = Some(limit);
+        self
+    }
+
+    /// Limits the length of tokens, in bytes
+    ///
+    /// Longer tokens are lexed as
+    /// [`LexErrorKind::TokenTooLong`](crate::LexErrorKind::TokenTooLong)
+    /// errors covering them. There is no limit by default.
+    pub fn max_token_len(mut self, limit: usize) -> Self {
+        self.config_mut().max_token_len = Some(limit);
+        self
+    }
+
+    /// Limits how deeply delimiters and modes, such as the embedded
+    /// expressions of interpolated strings, can nest
+    ///
+    /// A delimiter or mode opened past the limit is lexed as a
+    /// [`LexErrorKind::TooDeeplyNested`](crate::LexErrorKind::TooDeeplyNested)
+    /// error, so parsers recursing on them can bound their depth. There is
+    /// no limit by default.
+    pub fn max_nesting(mut self, limit: usize) -> Self {
+        self.config_mut().max_nesting = Some(limit);
+        self
+    }
+
+    /// Sets a callback invoked with every token lexed, and every comment
+    /// skipped without being produced as a token
+    ///
+    /// For logging, counting or profiling tokens without wrapping the lexer.
+    /// Tokens are observed as they're lexed, so a peeked token is observed
+    /// once, before it's returned. A [`StreamingLexer`](crate::StreamingLexer)
+    /// observes the tokens it produces once each, but not skipped comments.
+    pub fn on_token(mut self, observer: impl Fn(&Token) + Send + Sync + 'static) -> Self {
+        self.config_mut().observer = Some(Observer(Arc::new(observer)));
+        self
+    }
+
+    /// Sets a callback asked every few hundred tokens whether to stop
+    /// lexing, e.g. `move || flag.load(Ordering::Relaxed)` for an
+    /// `Arc<AtomicBool>`
+    ///
+    /// Once it returns `true`, the rest of the source is lexed as a single
+    /// [`LexErrorKind::Cancelled`](crate::LexErrorKind::Cancelled) error,
+    /// so editors can abandon lexing a huge file when the request for it is
+    /// stale. A single token is never interrupted.
+    pub fn cancel_when(mut self, cancelled: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        self.config_mut().cancel = Some(Cancel(Arc::new(cancelled)));
+        self
+    }
+
+    /// Classifies identifier characters per Unicode Standard Annex #31
+    ///
+    /// Identifiers start with an `XID_Start` character or `_` and continue
+    /// with `XID_Continue` characters, so `café` and `変数` are identifiers.
+    #[cfg(feature = "unicode")]
+    pub fn unicode_idents(self) -> Self {
+        self.ident_start(|ch| unicode_ident::is_xid_start(ch) || ch == '_')
+            .ident_continue(unicode_ident::is_xid_continue)
+    }
+
+    /// Sets whether identifiers are normalized to NFC
+    ///
+    /// Visually identical identifiers then compare equal. Identifiers only
+    /// own their text when normalizing changed it.
+    #[cfg(feature = "unicode")]
+    pub fn normalize_idents(mut self, enabled: bool) -> Self {
+        self.config_mut().normalize_idents = enabled;
+        self
+    }
+
+    /// Creates a lexer over a `&str` using these settings
+    pub fn build<'a>(&self, source: &'a str) -> Lexer<'a> {
+        Lexer::with_config(source, Arc::clone(&self.config))
+    }
+
+    /// Creates a lexer over a `&str` embedded in a larger file
+    ///
+    /// See [`Lexer::with_offset`].
+    pub fn build_with_offset<'a>(&self, source: &'a str, offset: usize, position: (usize, usize)) -> Lexer<'a> {
+        self.build(source).starting_at(offset, position)
+    }
+
+    /// Creates a lexer for a named source string, e.g. a file and its path
+    ///
+    /// See [`Lexer::new_named`].
+    pub fn build_named<'a>(&self, source: &'a str, name: impl Into<Arc<str>>) -> Lexer<'a> {
+        self.build(source).named(name.into())
+    }
+}
@@ -0,0 +1,90 @@
+//! Lexing byte slices that may not be valid UTF-8.
+use crate::builder::Config;
+use crate::{LexErrorKind, Lexer, LexerBuilder, Span, Token, TokenKind};
+use alloc::sync::Arc;
+use core::str::{self, Utf8Chunks, Utf8Error};
+
+impl<'a> Lexer<'a> {
+    /// Creates a new lexer from bytes, validating them as UTF-8 up front
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, Utf8Error> {
+        str::from_utf8(bytes).map(Self::new)
+    }
+
+    /// Creates a new lexer from bytes, validating them as UTF-8 while lexing
+    ///
+    /// See [`BytesLexer`].
+    pub fn from_bytes_lossy(bytes: &'a [u8]) -> BytesLexer<'a> {
+        BytesLexer::new(bytes, Arc::default())
+    }
+}
+
+impl LexerBuilder {
+    /// Creates a lexer over bytes, validating them as UTF-8 up front
+    pub fn build_from_bytes<'a>(&self, bytes: &'a [u8]) -> Result<Lexer<'a>, Utf8Error> {
+        str::from_utf8(bytes).map(|source| self.build(source))
+    }
+
+    /// Creates a lexer over bytes, validating them as UTF-8 while lexing
+    ///
+    /// See [`BytesLexer`].
+    pub fn build_from_bytes_lossy<'a>(&self, bytes: &'a [u8]) -> BytesLexer<'a> {
+        BytesLexer::new(bytes, Arc::clone(&self.config))
+    }
+}
+
+/// A lexer over bytes that may not be valid UTF-8
+///
+/// Each invalid sequence is lexed as a [`LexErrorKind::InvalidUtf8`] error
+/// taking up one column, as a replacement character would, with spans and
+/// positions still matching the bytes. The valid text between invalid
+/// sequences is lexed separately, so no token or mode continues across one.
+#[derive(Debug, Clone)]
+pub struct BytesLexer<'a> {
+    /// The settings each run of valid text is lexed with
+    config: Arc<Config>,
+    /// The runs of valid text and invalid bytes still to be lexed
+    chunks: Utf8Chunks<'a>,
+    /// The lexer over the current run of valid text
+    lexer: Lexer<'a>,
+    /// The invalid bytes following the current run
+    invalid: usize
+}
+
+impl<'a> BytesLexer<'a> {
+    /// Creates a new lexer over bytes with the given settings
+    fn new(bytes: &'a [u8], config: Arc<Config>) -> Self {
+        let lexer = Lexer::with_config("", Arc::clone(&config));
+        Self { config, chunks: bytes.utf8_chunks(), lexer, invalid: 0 }
+    }
+}
+
+impl<'a> Iterator for BytesLexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            if let Some(token) = self.lexer.next() {
+                return Some(token);
+            }
+
+            let start = self.lexer.byte_offset();
+            let (row, col) = self.lexer.pos();
+            if self.invalid > 0 {
+                let span = Span { start, end: start + self.invalid };
+                let kind = TokenKind::Error(LexErrorKind::InvalidUtf8);
+                self.lexer = Lexer::with_config("", Arc::clone(&self.config)).starting_at(span.end, (row, col + 1));
+                self.invalid = 0;
+                return Some(Token { kind, position: (row, col), span });
+            }
+
+            let chunk = self.chunks.next()?;
+            self.lexer = Lexer::with_config(chunk.valid(), Arc::clone(&self.config)).starting_at(start, (row, col));
+            // Only the start of the bytes can have a byte-order mark
+            if start > 0 && self.lexer.bom > 0 {
+                self.lexer.bom = 0;
+                self.lexer.move_to(0);
+            }
+            self.invalid = chunk.invalid().len();
+        }
+    }
+}
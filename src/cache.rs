@@ -0,0 +1,330 @@
+//! Caching lexed token streams.
+use crate::{LexErrorKind, Op, Span, Symbol, Token, TokenKind, TokenStream};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The bytes starting every cache, the last being the format's version
+const MAGIC: &[u8] = b"RLTC\x02";
+
+/// Why a cache couldn't be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheError {
+    /// The cache was made from a different source, so it has to be relexed
+    SourceChanged,
+    /// The bytes aren't a cache written by this version of the crate
+    Corrupt
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SourceChanged => f.write_str("the source changed since the tokens were cached"),
+            Self::Corrupt => f.write_str("corrupt token cache")
+        }
+    }
+}
+
+impl core::error::Error for CacheError {}
+
+/// Hashes a source with 64-bit FNV-1a, which is stable across platforms and
+/// versions unlike `core::hash`
+fn hash(source: &str) -> u64 {
+    source.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3))
+}
+
+impl<'a> TokenStream<'a> {
+    /// Encodes the tokens compactly, along with the source's name and a hash
+    /// of it to check they still match it when loaded with
+    /// [`TokenStream::from_cache`]
+    ///
+    /// Text is stored as where it lies in the source. Text that doesn't, as a
+    /// fallback rule can produce, is restored as the text of its token.
+    pub fn to_cache(&self) -> Vec<u8> {
+        let mut output = MAGIC.to_vec();
+        output.extend_from_slice(&hash(self.source).to_le_bytes());
+        write_varint(&mut output, self.source.len() as u64);
+        write_varint(&mut output, self.offset as u64);
+        output.push(u8::from(self.name.is_some()));
+        if let Some(name) = &self.name {
+            write_string(&mut output, name);
+        }
+        write_varint(&mut output, self.tokens.len() as u64);
+
+        let (mut start, mut row) = (self.offset, 0);
+        for token in &self.tokens {
+            output.push(tag(&token.kind));
+            let (line, col) = token.position;
+            write_varint(&mut output, (token.span.start - start) as u64);
+            write_varint(&mut output, (token.span.end - token.span.start) as u64);
+            write_varint(&mut output, (line - row) as u64);
+            write_varint(&mut output, col as u64);
+            (start, row) = (token.span.start, line);
+
+            match &token.kind {
+                TokenKind::Ident(Cow::Borrowed(text)) => self.write_slice(&mut output, text, token.span),
+                TokenKind::Ident(Cow::Owned(ident)) => write_string(&mut output, ident),
+                TokenKind::Keyword(text) | TokenKind::Punct(text) | TokenKind::Str(text) | TokenKind::Text(text) => {
+                    self.write_slice(&mut output, text, token.span);
+                },
+                TokenKind::Whitespace(text) | TokenKind::Comment(text) | TokenKind::Float(text) => {
+                    self.write_slice(&mut output, text, token.span);
+                },
+                TokenKind::Symbol(symbol) => write_varint(&mut output, u64::from(symbol.as_u32())),
+                TokenKind::Num(value) => write_varint(&mut output, u64::from((value << 1 ^ value >> 31) as u32)),
+                TokenKind::Error(err) => write_error(&mut output, err),
+                _ => {}
+            }
+        }
+        output
+    }
+
+    /// Writes where text lies in the source, as its start relative to the
+    /// token's and its length, or nothing but a marker if it isn't in the
+    /// source
+    fn write_slice(&self, output: &mut Vec<u8>, text: &str, span: Span) {
+        let start = (text.as_ptr() as usize).wrapping_sub(self.source.as_ptr() as usize);
+        if start > self.source.len() || text.len() > self.source.len() - start {
+            output.push(0);
+            return;
+        }
+        output.push(1);
+        let relative = (start + self.offset) as i64 - span.start as i64;
+        write_varint(output, (relative << 1 ^ relative >> 63) as u64);
+        write_varint(output, text.len() as u64);
+    }
+
+    /// Loads tokens encoded with [`TokenStream::to_cache`], checking they
+    /// were lexed from `source`
+    pub fn from_cache(source: &'a str, cache: &[u8]) -> Result<Self, CacheError> {
+        let mut decoder = Decoder { bytes: cache.strip_prefix(MAGIC).ok_or(CacheError::Corrupt)? };
+        let cached_hash = decoder.take(8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()))?;
+        if decoder.usize()? != source.len() || cached_hash != hash(source) {
+            return Err(CacheError::SourceChanged);
+        }
+
+        let offset = decoder.usize()?;
+        let name = match decoder.byte()? {
+            0 => None,
+            1 => Some(decoder.string()?.into()),
+            _ => return Err(CacheError::Corrupt)
+        };
+        let count = decoder.usize()?;
+        let mut decoder = TokenDecoder { decoder, source, offset, start: offset, row: 0 };
+        // Every token takes up at least 5 bytes, which bounds the allocation
+        let mut tokens = Vec::with_capacity(count.min(cache.len() / 5));
+        for _ in 0..count {
+            tokens.push(decoder.token()?);
+        }
+        if !decoder.decoder.bytes.is_empty() {
+            return Err(CacheError::Corrupt);
+        }
+        Ok(Self { source, offset, name, tokens })
+    }
+}
+
+/// The byte a token's encoding starts with, built-in operators counting up
+/// from 0 in declaration order
+fn tag(kind: &TokenKind) -> u8 {
+    match kind {
+        TokenKind::Opr(op) => op.clone() as u8,
+        TokenKind::Ident(Cow::Borrowed(_)) => 16,
+        TokenKind::Ident(Cow::Owned(_)) => 17,
+        TokenKind::Symbol(_) => 18,
+        TokenKind::Keyword(_) => 19,
+        TokenKind::Punct(_) => 20,
+        TokenKind::Str(_) => 21,
+        TokenKind::Text(_) => 22,
+        TokenKind::Whitespace(_) => 23,
+        TokenKind::Comment(_) => 24,
+        TokenKind::Num(_) => 25,
+        TokenKind::Newline => 26,
+        TokenKind::Indent => 27,
+        TokenKind::Dedent => 28,
+        TokenKind::OpeningBracket => 29,
+        TokenKind::ClosingBracket => 30,
+        TokenKind::Error(_) => 31,
+        TokenKind::Float(_) => 32
+    }
+}
+
+/// Writes an unsigned LEB128 integer
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Writes a string, prefixed by its length
+fn write_string(output: &mut Vec<u8>, text: &str) {
+    write_varint(output, text.len() as u64);
+    output.extend_from_slice(text.as_bytes());
+}
+
+fn write_error(output: &mut Vec<u8>, err: &LexErrorKind) {
+    match err {
+        LexErrorKind::UnexpectedChar(ch) => {
+            output.push(0);
+            write_varint(output, u64::from(*ch));
+        },
+        LexErrorKind::UnterminatedString => output.push(1),
+        LexErrorKind::UnterminatedComment => output.push(2),
+        LexErrorKind::InconsistentDedent => output.push(3),
+        LexErrorKind::InvalidUtf8 => output.push(4),
+        LexErrorKind::SourceTooLong => output.push(7),
+        LexErrorKind::TokenTooLong => output.push(8),
+        LexErrorKind::TooDeeplyNested => output.push(9),
+        LexErrorKind::Cancelled => output.push(11),
+        LexErrorKind::InconsistentTabs => output.push(12),
+        LexErrorKind::NonAsciiDigit(ch) => {
+            output.push(10);
+            write_varint(output, u64::from(*ch));
+        },
+        LexErrorKind::Expected { expected, found } => {
+            output.push(5 + u8::from(found.is_some()));
+            write_string(output, expected);
+            if let Some(found) = found {
+                write_string(output, found);
+            }
+        }
+    }
+}
+
+/// Reads the integers and strings of a cache
+struct Decoder<'c> {
+    bytes: &'c [u8]
+}
+
+impl<'c> Decoder<'c> {
+    fn take(&mut self, len: usize) -> Result<&'c [u8], CacheError> {
+        if len > self.bytes.len() {
+            return Err(CacheError::Corrupt);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, CacheError> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, CacheError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte < 0x80 {
+                return Ok(value);
+            }
+        }
+        Err(CacheError::Corrupt)
+    }
+
+    fn usize(&mut self) -> Result<usize, CacheError> {
+        usize::try_from(self.varint()?).map_err(|_| CacheError::Corrupt)
+    }
+
+    fn string(&mut self) -> Result<String, CacheError> {
+        let len = self.usize()?;
+        let bytes = self.take(len)?;
+        core::str::from_utf8(bytes).map(ToString::to_string).map_err(|_| CacheError::Corrupt)
+    }
+
+    fn error(&mut self) -> Result<LexErrorKind, CacheError> {
+        Ok(match self.byte()? {
+            0 => {
+                let ch = u32::try_from(self.varint()?).ok().and_then(char::from_u32);
+                LexErrorKind::UnexpectedChar(ch.ok_or(CacheError::Corrupt)?)
+            },
+            1 => LexErrorKind::UnterminatedString,
+            2 => LexErrorKind::UnterminatedComment,
+            3 => LexErrorKind::InconsistentDedent,
+            4 => LexErrorKind::InvalidUtf8,
+            7 => LexErrorKind::SourceTooLong,
+            8 => LexErrorKind::TokenTooLong,
+            9 => LexErrorKind::TooDeeplyNested,
+            11 => LexErrorKind::Cancelled,
+            12 => LexErrorKind::InconsistentTabs,
+            10 => {
+                let ch = u32::try_from(self.varint()?).ok().and_then(char::from_u32);
+                LexErrorKind::NonAsciiDigit(ch.ok_or(CacheError::Corrupt)?)
+            },
+            tag @ (5 | 6) => LexErrorKind::Expected {
+                expected: self.string()?,
+                found: if tag == 6 { Some(self.string()?) } else { None }
+            },
+            _ => return Err(CacheError::Corrupt)
+        })
+    }
+}
+
+/// Reads the tokens of a cache, borrowing their text from the source
+struct TokenDecoder<'c, 'a> {
+    decoder: Decoder<'c>,
+    source: &'a str,
+    /// The byte offset of the source within its file
+    offset: usize,
+    /// The start of the previous token
+    start: usize,
+    /// The row of the previous token
+    row: usize
+}
+
+impl<'a> TokenDecoder<'_, 'a> {
+    fn token(&mut self) -> Result<Token<'a>, CacheError> {
+        let tag = self.decoder.byte()?;
+        self.start = self.start.checked_add(self.decoder.usize()?).ok_or(CacheError::Corrupt)?;
+        let end = self.start.checked_add(self.decoder.usize()?).ok_or(CacheError::Corrupt)?;
+        let span = Span { start: self.start, end };
+        self.row = self.row.checked_add(self.decoder.usize()?).ok_or(CacheError::Corrupt)?;
+        let position = (self.row, self.decoder.usize()?);
+
+        let kind = match tag {
+            0..=12 => TokenKind::Opr(Op::from_index(usize::from(tag)).ok_or(CacheError::Corrupt)?),
+            16 => TokenKind::Ident(Cow::Borrowed(self.slice(span)?)),
+            17 => TokenKind::Ident(Cow::Owned(self.decoder.string()?)),
+            18 => TokenKind::Symbol(Symbol(u32::try_from(self.decoder.varint()?).map_err(|_| CacheError::Corrupt)?)),
+            19 => TokenKind::Keyword(self.slice(span)?),
+            20 => TokenKind::Punct(self.slice(span)?),
+            21 => TokenKind::Str(self.slice(span)?),
+            22 => TokenKind::Text(self.slice(span)?),
+            23 => TokenKind::Whitespace(self.slice(span)?),
+            24 => TokenKind::Comment(self.slice(span)?),
+            25 => {
+                let value = u32::try_from(self.decoder.varint()?).map_err(|_| CacheError::Corrupt)?;
+                TokenKind::Num((value >> 1) as i32 ^ -((value & 1) as i32))
+            },
+            26 => TokenKind::Newline,
+            27 => TokenKind::Indent,
+            28 => TokenKind::Dedent,
+            29 => TokenKind::OpeningBracket,
+            30 => TokenKind::ClosingBracket,
+            31 => TokenKind::Error(self.decoder.error()?),
+            32 => TokenKind::Float(self.slice(span)?),
+            _ => return Err(CacheError::Corrupt)
+        };
+        Ok(Token { kind, position, span })
+    }
+
+    /// Reads text written by [`TokenStream::write_slice`], falling back to
+    /// the token's text
+    fn slice(&mut self, span: Span) -> Result<&'a str, CacheError> {
+        let (start, end) = match self.decoder.byte()? {
+            0 => (span.start, span.end),
+            1 => {
+                let relative = self.decoder.varint()?;
+                let relative = (relative >> 1) as i64 ^ -((relative & 1) as i64);
+                let start = (span.start as i64).checked_add(relative).and_then(|start| usize::try_from(start).ok());
+                let start = start.ok_or(CacheError::Corrupt)?;
+                (start, start.checked_add(self.decoder.usize()?).ok_or(CacheError::Corrupt)?)
+            },
+            _ => return Err(CacheError::Corrupt)
+        };
+        let range = start.checked_sub(self.offset).zip(end.checked_sub(self.offset));
+        range.and_then(|(start, end)| self.source.get(start..end)).ok_or(CacheError::Corrupt)
+    }
+}
@@ -0,0 +1,158 @@
+//! Grouping kinds of token into broad categories.
+use crate::TokenKind;
+use core::fmt;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+/// A set of broad categories of token, such as literals or operators
+///
+/// Categories combine with `|`, so a parser can check a token against
+/// several at once, as in `kind.categories().intersects(TokenCategory::LITERAL
+/// | TokenCategory::KEYWORD)`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenCategory(u8);
+
+impl TokenCategory {
+    /// Numbers and strings
+    pub const LITERAL: Self = Self(1);
+    /// Built-in and registered operators, delimiters excluded
+    pub const OPERATOR: Self = Self(1 << 1);
+    /// Tokens opening or closing a [`Delimiter`](crate::Delimiter)
+    pub const DELIMITER: Self = Self(1 << 2);
+    /// Keywords
+    pub const KEYWORD: Self = Self(1 << 3);
+    /// Whitespace and comments
+    pub const TRIVIA: Self = Self(1 << 4);
+
+    /// The names of the categories, in the order of their bits
+    const NAMES: [&'static str; 5] = ["LITERAL", "OPERATOR", "DELIMITER", "KEYWORD", "TRIVIA"];
+
+    /// The set of no categories
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// The set of every category
+    #[inline]
+    pub const fn all() -> Self {
+        Self((1 << Self::NAMES.len()) - 1)
+    }
+
+    /// The bits of the set
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Creates a set from its bits, returning `None` if any don't stand for
+    /// a category
+    #[inline]
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::all().0 == 0 {
+            Some(Self(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Is the set empty?
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Does the set include every category of `other`?
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Does the set share any category with `other`?
+    #[inline]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for TokenCategory {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for TokenCategory {
+    #[inline]
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for TokenCategory {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+impl BitAndAssign for TokenCategory {
+    #[inline]
+    fn bitand_assign(&mut self, other: Self) {
+        self.0 &= other.0;
+    }
+}
+
+impl Not for TokenCategory {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        Self(!self.0 & Self::all().0)
+    }
+}
+
+impl fmt::Debug for TokenCategory {
+    /// Writes the categories' names separated by `|`, as in
+    /// `TokenCategory(LITERAL | KEYWORD)`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TokenCategory(")?;
+        let mut names = Self::NAMES.iter().enumerate().filter(|&(bit, _)| self.0 & 1 << bit != 0).map(|(_, name)| name);
+        if let Some(name) = names.next() {
+            f.write_str(name)?;
+            for name in names {
+                write!(f, " | {name}")?;
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+impl TokenKind<'_> {
+    /// The categories this kind of token falls into
+    ///
+    /// Identifiers, layout tokens and errors fall into none.
+    pub fn categories(&self) -> TokenCategory {
+        let mut categories = TokenCategory::empty();
+        if self.is_literal() {
+            categories |= TokenCategory::LITERAL;
+        }
+        if self.is_operator() {
+            categories |= TokenCategory::OPERATOR;
+        }
+        if self.is_delimiter() {
+            categories |= TokenCategory::DELIMITER;
+        }
+        if self.is_keyword() {
+            categories |= TokenCategory::KEYWORD;
+        }
+        if self.is_trivia() {
+            categories |= TokenCategory::TRIVIA;
+        }
+        categories
+    }
+}
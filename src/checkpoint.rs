@@ -0,0 +1,103 @@
+//! Saving and restoring lexer state for backtracking.
+use crate::stats::Tally;
+use crate::{Lexer, LexerState, Token};
+use alloc::collections::VecDeque;
+use core::ops::{Deref, DerefMut};
+
+/// A saved lexer state, restored with [`Lexer::rewind`]
+///
+/// Includes the mode stack, layout and lookahead, so rewinding restores
+/// exactly the tokens that followed the checkpoint. Comments and warnings
+/// collected since are dropped on rewinding, so a checkpoint only keeps how
+/// many there were, and costs no more the further into the source it is.
+#[derive(Debug, Clone)]
+pub struct Checkpoint<'a> {
+    /// The lexer's progress through the source
+    state: LexerState,
+    /// The tokens lexed ahead
+    lookahead: VecDeque<Token<'a>>,
+    /// The number of comments collected
+    comments: usize,
+    /// The number of warnings found
+    warnings: usize,
+    /// The figures about the source lexed
+    tally: Tally
+}
+
+impl<'a> Lexer<'a> {
+    /// Saves the lexer's state to return to later
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint {
+            state: self.state(),
+            lookahead: self.lookahead.clone(),
+            comments: self.comments.len(),
+            warnings: self.warnings.len(),
+            tally: self.tally.clone()
+        }
+    }
+
+    /// Restores a state saved with [`Lexer::checkpoint`]
+    ///
+    /// The checkpoint must be from this lexer, and from before any comments
+    /// or warnings were taken from it.
+    #[inline]
+    pub fn rewind(&mut self, checkpoint: Checkpoint<'a>) {
+        self.resume(checkpoint.state);
+        self.lookahead = checkpoint.lookahead;
+        self.comments.truncate(checkpoint.comments);
+        self.warnings.truncate(checkpoint.warnings);
+        self.tally = checkpoint.tally;
+    }
+
+    /// Lexes speculatively, rewinding when the guard is dropped unless
+    /// [`Speculation::commit`] is called
+    #[inline]
+    pub fn speculate(&mut self) -> Speculation<'_, 'a> {
+        let checkpoint = Some(self.checkpoint());
+        Speculation { lexer: self, checkpoint }
+    }
+}
+
+/// A guard over a lexer returned by [`Lexer::speculate`]
+///
+/// Dereferences to the lexer, and rewinds it to where speculation started
+/// when dropped unless committed.
+#[derive(Debug)]
+pub struct Speculation<'l, 'a> {
+    lexer: &'l mut Lexer<'a>,
+    /// The state to rewind to, `None` once committed
+    checkpoint: Option<Checkpoint<'a>>
+}
+
+impl Speculation<'_, '_> {
+    /// Keeps the tokens lexed during speculation
+    #[inline]
+    pub fn commit(mut self) {
+        self.checkpoint = None;
+    }
+}
+
+impl<'a> Deref for Speculation<'_, 'a> {
+    type Target = Lexer<'a>;
+
+    #[inline]
+    fn deref(&self) -> &Lexer<'a> {
+        self.lexer
+    }
+}
+
+impl<'a> DerefMut for Speculation<'_, 'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Lexer<'a> {
+        self.lexer
+    }
+}
+
+impl Drop for Speculation<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(checkpoint) = self.checkpoint.take() {
+            self.lexer.rewind(checkpoint);
+        }
+    }
+}
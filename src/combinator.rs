@@ -0,0 +1,109 @@
+//! Feeding tokens to parser combinator crates.
+#[cfg(feature = "nom")]
+use crate::Token;
+#[cfg(feature = "chumsky")]
+use crate::{Lexer, TokenKind};
+#[cfg(feature = "nom")]
+use core::iter::Enumerate;
+#[cfg(feature = "chumsky")]
+use core::ops::Range;
+#[cfg(feature = "nom")]
+use core::slice::Iter;
+#[cfg(feature = "nom")]
+use nom::{Input, Needed, Offset};
+
+#[cfg(feature = "chumsky")]
+impl<'a> Lexer<'a> {
+    /// Feeds the lexer's tokens to a `chumsky` parser, each spanned by the
+    /// bytes it was lexed from
+    ///
+    /// Tokens are lexed as the parser asks for them.
+    pub fn chumsky_stream(self) -> chumsky::Stream<'a, TokenKind<'a>, Range<usize>, impl Iterator<Item = (TokenKind<'a>, Range<usize>)> + 'a> {
+        let end = self.offset + self.source.len();
+        chumsky::Stream::from_iter(end..end, self.map(|token| (token.kind, token.span.start..token.span.end)))
+    }
+}
+
+/// Lexed tokens as the input of a `nom` parser
+///
+/// Parsers consume references to tokens, so tokens can be matched with
+/// `nom::bytes::complete::take(1usize)` and `nom::combinator::verify`, or any
+/// function taking and returning `Tokens`.
+#[cfg(feature = "nom")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tokens<'s, 'a>(&'s [Token<'a>]);
+
+#[cfg(feature = "nom")]
+impl<'s, 'a> Tokens<'s, 'a> {
+    /// Wraps a slice of tokens
+    #[inline]
+    pub fn new(tokens: &'s [Token<'a>]) -> Self {
+        Self(tokens)
+    }
+
+    /// The tokens not yet consumed
+    #[inline]
+    pub fn tokens(&self) -> &'s [Token<'a>] {
+        self.0
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<'s, 'a> Input for Tokens<'s, 'a> {
+    type Item = &'s Token<'a>;
+    type Iter = Iter<'s, Token<'a>>;
+    type IterIndices = Enumerate<Iter<'s, Token<'a>>>;
+
+    #[inline]
+    fn input_len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    fn take(&self, index: usize) -> Self {
+        Self(&self.0[..index])
+    }
+
+    #[inline]
+    fn take_from(&self, index: usize) -> Self {
+        Self(&self.0[index..])
+    }
+
+    #[inline]
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        let (prefix, suffix) = self.0.split_at(index);
+        (Self(suffix), Self(prefix))
+    }
+
+    #[inline]
+    fn position<P: Fn(Self::Item) -> bool>(&self, predicate: P) -> Option<usize> {
+        self.0.iter().position(predicate)
+    }
+
+    #[inline]
+    fn iter_elements(&self) -> Self::Iter {
+        self.0.iter()
+    }
+
+    #[inline]
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.0.iter().enumerate()
+    }
+
+    #[inline]
+    fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+        match self.0.len().checked_sub(count) {
+            Some(_) => Ok(count),
+            None => Err(Needed::new(count - self.0.len()))
+        }
+    }
+}
+
+/// Measures how many tokens were consumed, for `nom::combinator::recognize`
+#[cfg(feature = "nom")]
+impl Offset for Tokens<'_, '_> {
+    #[inline]
+    fn offset(&self, second: &Self) -> usize {
+        (second.0.as_ptr() as usize - self.0.as_ptr() as usize) / core::mem::size_of::<Token>().max(1)
+    }
+}
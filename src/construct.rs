@@ -0,0 +1,105 @@
+//! Building tokens by hand, e.g. the tokens a test expects.
+use crate::{Op, Span, Token, TokenKind};
+use alloc::borrow::Cow;
+use core::ops::Range;
+
+impl From<Range<usize>> for Span {
+    #[inline]
+    fn from(range: Range<usize>) -> Self {
+        Span { start: range.start, end: range.end }
+    }
+}
+
+impl<'a> TokenKind<'a> {
+    /// An operator
+    #[inline]
+    pub fn op(op: Op) -> Self {
+        Self::Opr(op)
+    }
+
+    /// An identifier, borrowing its text
+    #[inline]
+    pub fn ident(ident: &'a str) -> Self {
+        Self::Ident(Cow::Borrowed(ident))
+    }
+
+    /// A keyword
+    #[inline]
+    pub fn keyword(keyword: &'a str) -> Self {
+        Self::Keyword(keyword)
+    }
+
+    /// A registered operator or mode delimiter
+    #[inline]
+    pub fn punct(symbol: &'a str) -> Self {
+        Self::Punct(symbol)
+    }
+
+    /// A number literal
+    #[inline]
+    pub fn num(value: i32) -> Self {
+        Self::Num(value)
+    }
+
+    /// A string literal with the given contents
+    #[inline]
+    pub fn str(contents: &'a str) -> Self {
+        Self::Str(contents)
+    }
+}
+
+impl<'a> Token<'a> {
+    /// Creates a token of a kind lexed from a span
+    ///
+    /// The position is the one a source of a single line of ASCII would
+    /// give, row 1 and the column after `span.start`; set another with
+    /// [`Token::at`].
+    #[inline]
+    pub fn new(kind: TokenKind<'a>, span: impl Into<Span>) -> Self {
+        let span = span.into();
+        Self { kind, position: (1, span.start + 1), span }
+    }
+
+    /// Sets the token's position
+    #[inline]
+    pub fn at(mut self, row: usize, col: usize) -> Self {
+        self.position = (row, col);
+        self
+    }
+
+    /// An operator token
+    #[inline]
+    pub fn op(op: Op, span: impl Into<Span>) -> Self {
+        Self::new(TokenKind::op(op), span)
+    }
+
+    /// An identifier token
+    #[inline]
+    pub fn ident(ident: &'a str, span: impl Into<Span>) -> Self {
+        Self::new(TokenKind::ident(ident), span)
+    }
+
+    /// A keyword token
+    #[inline]
+    pub fn keyword(keyword: &'a str, span: impl Into<Span>) -> Self {
+        Self::new(TokenKind::keyword(keyword), span)
+    }
+
+    /// A registered operator or mode delimiter token
+    #[inline]
+    pub fn punct(symbol: &'a str, span: impl Into<Span>) -> Self {
+        Self::new(TokenKind::punct(symbol), span)
+    }
+
+    /// A number literal token
+    #[inline]
+    pub fn num(value: i32, span: impl Into<Span>) -> Self {
+        Self::new(TokenKind::num(value), span)
+    }
+
+    /// A string literal token with the given contents
+    #[inline]
+    pub fn str(contents: &'a str, span: impl Into<Span>) -> Self {
+        Self::new(TokenKind::str(contents), span)
+    }
+}
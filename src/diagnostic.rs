@@ -0,0 +1,37 @@
+//! Reporting errors with miette.
+use crate::{LexError, Span};
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use core::fmt::Display;
+use miette::{Diagnostic, LabeledSpan, NamedSource, Report, SourceSpan};
+
+impl From<Span> for SourceSpan {
+    #[inline]
+    fn from(span: Span) -> Self {
+        SourceSpan::new(span.start.into(), span.end - span.start)
+    }
+}
+
+impl Diagnostic for LexError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let label = LabeledSpan::new_primary_with_span(Some(self.kind.to_string()), self.span);
+        Some(Box::new(core::iter::once(label)))
+    }
+}
+
+impl LexError {
+    /// A miette report of the error, labeling the source it occurred in
+    ///
+    /// `source` is the file the error's span is relative to, and is named
+    /// after the error's source name, if any.
+    pub fn report(self, source: &str) -> Report {
+        match self.source_name.clone() {
+            Some(name) => Report::new(self).with_source_code(NamedSource::new(name, source.to_string())),
+            None => Report::new(self).with_source_code(source.to_string())
+        }
+    }
+}
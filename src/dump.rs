@@ -0,0 +1,91 @@
+//! A stable text format for snapshot tests.
+use crate::{LexErrorKind, Token, TokenKind, TokenStream};
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+impl Token<'_> {
+    /// Writes the token as a line of the dump format, without the newline
+    ///
+    /// A line is the token's position, its span and the name of its kind,
+    /// followed by what the kind holds if anything, all separated by spaces:
+    ///
+    /// ```text
+    /// 1:1 0..3 Keyword "let"
+    /// 1:5 4..5 Num 7
+    /// 1:7 6..7 Opr +
+    /// 1:8 7..8 Newline
+    /// ```
+    ///
+    /// Text is quoted, with `\`, `"` and control characters escaped as in
+    /// Rust, and symbols are written as `#n`. Errors are written as their
+    /// [code](crate::LexErrorKind::code), followed by the quoted character
+    /// or expected and found text they hold, never as their message, which
+    /// may be reworded. The format doesn't change between semver-compatible
+    /// versions, so snapshots of it only change when the tokens do.
+    pub fn dump(&self) -> String {
+        let mut dump = String::new();
+        let _ = self.write_dump(&mut dump);
+        dump
+    }
+
+    /// Writes the token in the format of [`Token::dump`]
+    fn write_dump(&self, output: &mut impl Write) -> fmt::Result {
+        let (row, col) = self.position;
+        write!(output, "{row}:{col} {}..{} {}", self.span.start, self.span.end, self.kind.name())?;
+        match &self.kind {
+            TokenKind::Opr(op) => write!(output, " {op}"),
+            TokenKind::Ident(text) => write_quoted(output, text),
+            TokenKind::Keyword(text) | TokenKind::Punct(text) | TokenKind::Str(text) | TokenKind::Text(text) => {
+                write_quoted(output, text)
+            },
+            TokenKind::Whitespace(text) | TokenKind::Comment(text) => write_quoted(output, text),
+            TokenKind::Symbol(symbol) => write!(output, " {symbol}"),
+            TokenKind::Num(value) => write!(output, " {value}"),
+            TokenKind::Float(text) => write!(output, " {text}"),
+            TokenKind::Error(kind) => {
+                write!(output, " {}", kind.code())?;
+                match kind {
+                    LexErrorKind::UnexpectedChar(ch) | LexErrorKind::NonAsciiDigit(ch) => {
+                        write_quoted(output, ch.encode_utf8(&mut [0; 4]))
+                    },
+                    LexErrorKind::Expected { expected, found } => {
+                        write_quoted(output, expected)?;
+                        found.as_deref().map_or(Ok(()), |found| write_quoted(output, found))
+                    },
+                    _ => Ok(())
+                }
+            },
+            TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent => Ok(()),
+            TokenKind::OpeningBracket | TokenKind::ClosingBracket => Ok(())
+        }
+    }
+}
+
+impl TokenStream<'_> {
+    /// Writes every token in the format of [`Token::dump`], one per line
+    pub fn dump(&self) -> String {
+        let mut dump = String::new();
+        for token in &self.tokens {
+            let _ = token.write_dump(&mut dump);
+            dump.push('\n');
+        }
+        dump
+    }
+}
+
+/// Writes a space and then text in double quotes, escaping it
+fn write_quoted(output: &mut impl Write, text: &str) -> fmt::Result {
+    output.write_str(" \"")?;
+    for ch in text.chars() {
+        match ch {
+            '"' => output.write_str("\\\"")?,
+            '\\' => output.write_str("\\\\")?,
+            '\n' => output.write_str("\\n")?,
+            '\r' => output.write_str("\\r")?,
+            '\t' => output.write_str("\\t")?,
+            ch if ch.is_control() => write!(output, "\\u{{{:x}}}", ch as u32)?,
+            ch => output.write_char(ch)?
+        }
+    }
+    output.write_char('"')
+}
@@ -0,0 +1,176 @@
+//! Lexical errors.
+use crate::{Delimiter, Span};
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::fmt;
+
+/// The different kinds of lexical error
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LexErrorKind {
+    /// A character that doesn't start any token
+    UnexpectedChar(char),
+    /// A string literal missing its closing delimiter
+    UnterminatedString,
+    /// A block comment missing its closing delimiter
+    UnterminatedComment,
+    /// A dedent to a width no enclosing block is indented by
+    InconsistentDedent,
+    /// Bytes that aren't valid UTF-8
+    InvalidUtf8,
+    /// A digit other than `0` to `9` in a number, unless configured to
+    /// convert them
+    NonAsciiDigit(char),
+    /// A source longer than the configured limit, covering all of it
+    SourceTooLong,
+    /// A token longer than the configured limit
+    TokenTooLong,
+    /// A delimiter or mode opened past the configured nesting limit
+    TooDeeplyNested,
+    /// The rest of a source left unlexed because lexing was cancelled
+    Cancelled,
+    /// Indentation only consistent with its block's for some widths of tab,
+    /// if configured to be an error
+    InconsistentTabs,
+    /// A token other than the one a parser expected, from [`Lexer::expect`](crate::Lexer::expect)
+    Expected {
+        expected: String,
+        /// The token found, `None` at the end of the source
+        found: Option<String>
+    }
+}
+
+/// The code of each kind of error and its explanation
+///
+/// Codes are in no particular order, but are never renumbered or reused, so
+/// they can be documented.
+const CODES: [(&str, &str); 12] = [
+    ("L0001", "A character that doesn't start any token the lexer is configured for, such as a `$` in a \
+               language with no `$` operator. It's skipped, and lexing continues after it."),
+    ("L0002", "A string literal that reaches the end of the source without its closing quote. The rest of \
+               the source is part of the string."),
+    ("L0003", "A block comment that reaches the end of the source without its closing delimiter. The rest \
+               of the source is part of the comment."),
+    ("L0004", "A line indented less than the line before it, but by a width no enclosing block is \
+               indented by, so it's unclear which block it belongs to."),
+    ("L0005", "Bytes that aren't valid UTF-8, in a source read as bytes. They're skipped."),
+    ("L0006", "A token other than the one a parser expected."),
+    ("L0007", "A source longer than the configured limit. None of it is lexed."),
+    ("L0008", "A token longer than the configured limit."),
+    ("L0009", "A bracket or mode opened inside more than the configured number of others."),
+    ("L0010", "A digit other than `0` to `9` in a number, such as an Arabic-Indic digit, which may look \
+               like another. The lexer can be configured to take such digits for their value."),
+    ("L0011", "The rest of a source, left unlexed because the lexer's cancellation callback asked it to stop."),
+    ("L0012", "A line indented more than its block with tabs one column wide but not with tabs eight columns \
+               wide, or the other way around, as Python's `TabError`. Which block the line is in depends on \
+               the width of tabs, so it's reported after its indent or dedent tokens.")
+];
+
+impl LexErrorKind {
+    /// A stable code for the kind of error, e.g. `L0002` for an unterminated
+    /// string
+    ///
+    /// Codes are never renumbered or reused, so compilers can document them
+    /// and users look them up with [`LexErrorKind::explain`].
+    pub fn code(&self) -> &'static str {
+        let index = match self {
+            Self::UnexpectedChar(_) => 0,
+            Self::UnterminatedString => 1,
+            Self::UnterminatedComment => 2,
+            Self::InconsistentDedent => 3,
+            Self::InvalidUtf8 => 4,
+            Self::Expected { .. } => 5,
+            Self::SourceTooLong => 6,
+            Self::TokenTooLong => 7,
+            Self::TooDeeplyNested => 8,
+            Self::NonAsciiDigit(_) => 9,
+            Self::Cancelled => 10,
+            Self::InconsistentTabs => 11
+        };
+        CODES[index].0
+    }
+
+    /// A longer explanation of the errors with a code, as for a compiler's
+    /// `--explain`
+    pub fn explain(code: &str) -> Option<&'static str> {
+        CODES.iter().find(|(known, _)| known.eq_ignore_ascii_case(code)).map(|&(_, explanation)| explanation)
+    }
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedChar(ch) => write!(f, "unexpected character `{ch}`"),
+            Self::UnterminatedString => f.write_str("unterminated string"),
+            Self::UnterminatedComment => f.write_str("unterminated comment"),
+            Self::InconsistentDedent => f.write_str("inconsistent dedent"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::NonAsciiDigit(ch) => write!(f, "non-ASCII digit `{ch}` in a number"),
+            Self::SourceTooLong => f.write_str("source exceeds the length limit"),
+            Self::TokenTooLong => f.write_str("token exceeds the length limit"),
+            Self::TooDeeplyNested => f.write_str("nesting exceeds the depth limit"),
+            Self::Cancelled => f.write_str("lexing was cancelled"),
+            Self::InconsistentTabs => f.write_str("inconsistent use of tabs and spaces in indentation"),
+            Self::Expected { expected, found: Some(found) } => write!(f, "expected `{expected}`, found `{found}`"),
+            Self::Expected { expected, found: None } => write!(f, "expected `{expected}`, found end of input")
+        }
+    }
+}
+
+/// A delimiter that isn't balanced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DelimiterError {
+    /// An opening delimiter that is never closed
+    Unclosed {
+        delimiter: Delimiter,
+        span: Span
+    },
+    /// A closing delimiter with nothing open
+    Unopened {
+        delimiter: Delimiter,
+        span: Span
+    },
+    /// A closing delimiter that doesn't match the innermost open one
+    Mismatched {
+        open: Span,
+        close: Span,
+        expected: Delimiter,
+        found: Delimiter
+    }
+}
+
+/// A lexical error along with where it occurred
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LexError {
+    /// What went wrong
+    pub kind: LexErrorKind,
+    /// The error's position in file
+    pub position: (usize, usize),
+    /// The bytes that couldn't be lexed
+    pub span: Span,
+    /// The name of the source it occurred in, if it was given one
+    pub source_name: Option<Arc<str>>
+}
+
+impl LexError {
+    /// The stable code of the kind of error
+    ///
+    /// See [`LexErrorKind::code`].
+    #[inline]
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (row, col) = self.position;
+        match &self.source_name {
+            Some(name) => write!(f, "{} at {name}:{row}:{col}", self.kind),
+            None => write!(f, "{} at {row}:{col}", self.kind)
+        }
+    }
+}
+
+impl core::error::Error for LexError {}
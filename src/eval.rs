@@ -0,0 +1,161 @@
+//! Evaluating built-in operators on constant operands.
+use crate::Op;
+use core::cmp::Ordering;
+use core::fmt;
+
+/// The result of applying an operator
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    /// The result of integer arithmetic
+    Int(i64),
+    /// The result of floating-point arithmetic
+    Float(f64),
+    /// The result of a comparison
+    Bool(bool)
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value:?}"),
+            Self::Bool(value) => write!(f, "{value}")
+        }
+    }
+}
+
+/// Why an operator couldn't be applied
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EvalError {
+    /// The result doesn't fit in an `i64`
+    Overflow,
+    /// An integer was divided by zero, or taken modulo zero
+    DivisionByZero,
+    /// The operator takes one operand, not two
+    NotBinary(Op)
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Overflow => f.write_str("arithmetic overflow"),
+            Self::DivisionByZero => f.write_str("division by zero"),
+            Self::NotBinary(op) => write!(f, "`{op}` isn't a binary operator")
+        }
+    }
+}
+
+impl core::error::Error for EvalError {}
+
+impl Op {
+    /// Applies the operator to two integers
+    ///
+    /// Arithmetic is checked, and `/` and `%` truncate toward zero as in
+    /// Rust. Comparisons give a [`Value::Bool`].
+    pub fn apply_i64(&self, a: i64, b: i64) -> Result<Value, EvalError> {
+        let arithmetic = |result: Option<i64>| result.map(Value::Int).ok_or(EvalError::Overflow);
+        match self {
+            Self::Plus => arithmetic(a.checked_add(b)),
+            Self::Minus => arithmetic(a.checked_sub(b)),
+            Self::Multiply => arithmetic(a.checked_mul(b)),
+            Self::Divide | Self::Modulo if b == 0 => Err(EvalError::DivisionByZero),
+            Self::Divide => arithmetic(a.checked_div(b)),
+            Self::Modulo => arithmetic(a.checked_rem(b)),
+            _ => self.compare(a.partial_cmp(&b))
+        }
+    }
+
+    /// Applies the operator to two floats
+    ///
+    /// Arithmetic follows IEEE 754, so dividing by zero gives an infinity or
+    /// NaN rather than an error. Comparisons give a [`Value::Bool`].
+    pub fn apply_f64(&self, a: f64, b: f64) -> Result<Value, EvalError> {
+        match self {
+            Self::Plus => Ok(Value::Float(a + b)),
+            Self::Minus => Ok(Value::Float(a - b)),
+            Self::Multiply => Ok(Value::Float(a * b)),
+            Self::Divide => Ok(Value::Float(a / b)),
+            Self::Modulo => Ok(Value::Float(a % b)),
+            _ => self.compare(a.partial_cmp(&b))
+        }
+    }
+
+    /// Applies a comparison to operands ordered as given, unordered operands
+    /// (NaN) being unequal
+    fn compare(&self, ordering: Option<Ordering>) -> Result<Value, EvalError> {
+        let holds = match self {
+            Self::Equal => ordering == Some(Ordering::Equal),
+            Self::NotEqual => ordering != Some(Ordering::Equal),
+            Self::Greater => ordering == Some(Ordering::Greater),
+            Self::GreaterOrEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            Self::Less => ordering == Some(Ordering::Less),
+            Self::LessOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            _ => return Err(EvalError::NotBinary(self.clone()))
+        };
+        Ok(Value::Bool(holds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_arithmetic() {
+        assert_eq!(Op::Plus.apply_i64(2, 3), Ok(Value::Int(5)));
+        assert_eq!(Op::Minus.apply_i64(2, 3), Ok(Value::Int(-1)));
+        assert_eq!(Op::Multiply.apply_i64(-2, 3), Ok(Value::Int(-6)));
+        // Division and remainder truncate toward zero
+        assert_eq!(Op::Divide.apply_i64(-7, 2), Ok(Value::Int(-3)));
+        assert_eq!(Op::Modulo.apply_i64(-7, 2), Ok(Value::Int(-1)));
+    }
+
+    #[test]
+    fn integer_overflow() {
+        assert_eq!(Op::Plus.apply_i64(i64::MAX, 1), Err(EvalError::Overflow));
+        assert_eq!(Op::Minus.apply_i64(i64::MIN, 1), Err(EvalError::Overflow));
+        assert_eq!(Op::Multiply.apply_i64(i64::MIN, -1), Err(EvalError::Overflow));
+        assert_eq!(Op::Divide.apply_i64(i64::MIN, -1), Err(EvalError::Overflow));
+        assert_eq!(Op::Modulo.apply_i64(i64::MIN, -1), Err(EvalError::Overflow));
+    }
+
+    #[test]
+    fn integer_division_by_zero() {
+        assert_eq!(Op::Divide.apply_i64(1, 0), Err(EvalError::DivisionByZero));
+        assert_eq!(Op::Modulo.apply_i64(i64::MIN, 0), Err(EvalError::DivisionByZero));
+    }
+
+    #[test]
+    fn float_arithmetic() {
+        assert_eq!(Op::Divide.apply_f64(1.0, 4.0), Ok(Value::Float(0.25)));
+        assert_eq!(Op::Modulo.apply_f64(-7.5, 2.0), Ok(Value::Float(-1.5)));
+        assert_eq!(Op::Divide.apply_f64(-1.0, 0.0), Ok(Value::Float(f64::NEG_INFINITY)));
+        assert!(matches!(Op::Divide.apply_f64(0.0, 0.0), Ok(Value::Float(value)) if value.is_nan()));
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(Op::Less.apply_i64(1, 2), Ok(Value::Bool(true)));
+        assert_eq!(Op::GreaterOrEqual.apply_i64(2, 2), Ok(Value::Bool(true)));
+        assert_eq!(Op::NotEqual.apply_i64(2, 2), Ok(Value::Bool(false)));
+        assert_eq!(Op::LessOrEqual.apply_f64(-0.0, 0.0), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn nan_is_unordered() {
+        for op in [Op::Equal, Op::Greater, Op::GreaterOrEqual, Op::Less, Op::LessOrEqual] {
+            assert_eq!(op.apply_f64(f64::NAN, f64::NAN), Ok(Value::Bool(false)), "{op}");
+            assert_eq!(op.apply_f64(f64::NAN, 1.0), Ok(Value::Bool(false)), "{op}");
+        }
+        assert_eq!(Op::NotEqual.apply_f64(f64::NAN, f64::NAN), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn unary_operators() {
+        for op in [Op::Increment, Op::Decrement] {
+            assert_eq!(op.apply_i64(1, 2), Err(EvalError::NotBinary(op.clone())));
+            assert_eq!(op.apply_f64(1.0, 2.0), Err(EvalError::NotBinary(op.clone())));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Helpers for parsers consuming tokens.
+use crate::{LexError, LexErrorKind, Lexer, Span, Token, TokenKind};
+use alloc::string::ToString;
+
+impl<'a> Lexer<'a> {
+    /// Consumes the next token if it is `kind`, returning whether it was
+    pub fn eat(&mut self, kind: &TokenKind) -> bool {
+        let matches = self.peek_token().is_some_and(|token| token.kind == *kind);
+        if matches {
+            self.next();
+        }
+        matches
+    }
+
+    /// Consumes the next token if it is `kind`, or reports what was found
+    /// instead without consuming it
+    ///
+    /// The error reads like "expected `)`, found `+` at 3:14".
+    pub fn expect(&mut self, kind: &TokenKind) -> Result<Token<'a>, LexError> {
+        self.peek_token();
+        let (found, position, span) = match self.lookahead.pop_front() {
+            Some(token) if token.kind == *kind => return Ok(token),
+            Some(token) => {
+                let found = (Some(token.kind.to_string()), token.position, token.span);
+                self.lookahead.push_front(token);
+                found
+            },
+            None => {
+                let end = self.byte_offset();
+                (None, self.pos(), Span { start: end, end })
+            }
+        };
+        let kind = LexErrorKind::Expected { expected: kind.to_string(), found };
+        Err(LexError { kind, position, span, source_name: self.name.clone() })
+    }
+
+    /// Discards tokens up to the first that satisfies `sync`, which is left
+    /// to be lexed next, returning how many were discarded
+    ///
+    /// For panic-mode error recovery, e.g. skipping to the next `;`.
+    pub fn skip_until(&mut self, mut sync: impl FnMut(&Token<'a>) -> bool) -> usize {
+        let mut skipped = 0;
+        while self.peek_token().is_some_and(|token| !sync(token)) {
+            self.next();
+            skipped += 1;
+        }
+        skipped
+    }
+
+    /// Discards tokens up to the first of `kind`, which is left to be lexed
+    /// next, returning how many were discarded
+    #[inline]
+    pub fn skip_to_kind(&mut self, kind: &TokenKind) -> usize {
+        self.skip_until(|token| token.kind == *kind)
+    }
+
+    /// Splits a `>` off the next token if it's an operator starting with
+    /// one, such as `>>` or `>=`, returning whether it did
+    ///
+    /// Lets parsers of generics, e.g. `Vec<Vec<i32>>`, close one argument
+    /// list at a time without relexing. Both parts are lexed as the
+    /// operators they spell, or as `Punct` tokens if they aren't operators.
+    pub fn split_right_shift(&mut self) -> bool {
+        self.peek_token();
+        let Some(token) = self.lookahead.pop_front() else {
+            return false;
+        };
+        let span = token.span;
+        let text = self.text(span);
+        if !token.kind.is_operator() || text.len() < 2 || !text.starts_with('>') {
+            self.lookahead.push_front(token);
+            return false;
+        }
+
+        let (row, col) = token.position;
+        let split = span.start + 1;
+        self.lookahead.push_front(Token {
+            kind: self.operator_kind(&text[1..]),
+            position: (row, col + 1),
+            span: Span { start: split, end: span.end }
+        });
+        self.lookahead.push_front(Token {
+            kind: self.operator_kind(&text[..1]),
+            position: token.position,
+            span: Span { start: span.start, end: split }
+        });
+        true
+    }
+
+    /// The kind of token an operator's text is lexed as on its own
+    fn operator_kind(&self, text: &'a str) -> TokenKind<'a> {
+        match self.config.operators.longest_match(text) {
+            Some((len, Some(op))) if len == text.len() => TokenKind::Opr(op),
+            _ => TokenKind::Punct(text)
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! A C interface, declared in `include/reusable_lexer.h`.
+use crate::{Lexer, Token, TokenKind};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ptr;
+
+/// A token as seen from C
+///
+/// Its text is the bytes of the source from `start` to `end`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RlToken {
+    /// The kind of token, one of the `RL_KIND_` constants of the header
+    pub kind: u32,
+    /// The byte offset of the token's first byte
+    pub start: usize,
+    /// The byte offset just past the token's last byte
+    pub end: usize,
+    /// The 1-based line the token starts on
+    pub line: usize,
+    /// The 1-based column the token starts at, in codepoints
+    pub column: usize,
+    /// The value of a number, 0 for other tokens
+    pub value: i32
+}
+
+/// The tokens of a source, freed with [`reusable_lexer_free_tokens`]
+#[repr(C)]
+#[derive(Debug)]
+pub struct RlTokenArray {
+    /// The first token, null if there are none
+    pub tokens: *mut RlToken,
+    /// The number of tokens
+    pub len: usize
+}
+
+impl From<&Token<'_>> for RlToken {
+    fn from(token: &Token) -> Self {
+        let kind = token.kind.id().into();
+        let value = match token.kind {
+            TokenKind::Num(value) => value,
+            _ => 0
+        };
+        Self { kind, start: token.span.start, end: token.span.end, line: token.position.0, column: token.position.1, value }
+    }
+}
+
+/// Lexes `len` bytes of source with the default settings
+///
+/// Invalid UTF-8 gives error tokens.
+///
+/// # Safety
+///
+/// `source` must point to `len` readable bytes, or be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn reusable_lexer_tokenize(source: *const u8, len: usize) -> RlTokenArray {
+    let bytes = match len {
+        0 => &[],
+        // SAFETY: the caller guarantees the bytes are readable
+        _ => unsafe { core::slice::from_raw_parts(source, len) }
+    };
+    let tokens: Vec<RlToken> = Lexer::from_bytes_lossy(bytes).map(|token| RlToken::from(&token)).collect();
+    if tokens.is_empty() {
+        return RlTokenArray { tokens: ptr::null_mut(), len: 0 };
+    }
+
+    let len = tokens.len();
+    let tokens = Box::into_raw(tokens.into_boxed_slice()).cast::<RlToken>();
+    RlTokenArray { tokens, len }
+}
+
+/// Frees tokens returned by [`reusable_lexer_tokenize`]
+///
+/// # Safety
+///
+/// `array` must have been returned by [`reusable_lexer_tokenize`] and not
+/// freed already.
+#[no_mangle]
+pub unsafe extern "C" fn reusable_lexer_free_tokens(array: RlTokenArray) {
+    if !array.tokens.is_null() {
+        // SAFETY: the array was a boxed slice of this length
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(array.tokens, array.len)) });
+    }
+}
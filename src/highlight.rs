@@ -0,0 +1,151 @@
+//! Rendering source as syntax-highlighted HTML.
+use crate::{KindFilter, Token, TokenKind};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// Renders source as HTML, wrapping each token in a `<span>` with a CSS class
+/// for its kind
+///
+/// Starts out giving keywords, identifiers, operators, literals, mode text,
+/// comments and errors a class of their own, for languages to override with
+/// [`HtmlHighlighter::with`]. Tokens no rule applies to are written as plain
+/// text, as is the source between tokens.
+#[derive(Debug, Clone)]
+pub struct HtmlHighlighter {
+    /// The kinds each rule applies to, with the class it gives them
+    rules: Vec<(KindFilter, &'static str)>
+}
+
+impl Default for HtmlHighlighter {
+    fn default() -> Self {
+        Self::empty()
+            .with(|kind| kind.is_keyword(), "keyword")
+            .with(|kind| kind.is_ident(), "ident")
+            .with(|kind| kind.is_operator(), "op")
+            .with(|kind| matches!(kind, TokenKind::Num(_) | TokenKind::Float(_)), "num")
+            .with(|kind| matches!(kind, TokenKind::Str(_)), "str")
+            .with(|kind| matches!(kind, TokenKind::Text(_)), "text")
+            .with(|kind| matches!(kind, TokenKind::Comment(_)), "comment")
+            .with(|kind| kind.is_error(), "error")
+    }
+}
+
+impl HtmlHighlighter {
+    /// Creates a highlighter with the default classes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a highlighter giving no tokens a class
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Gives the tokens `applies` to a CSS class, taking priority over the
+    /// rules before it
+    pub fn with(mut self, applies: KindFilter, class: &'static str) -> Self {
+        self.rules.push((applies, class));
+        self
+    }
+
+    /// The CSS class a kind of token is given
+    pub fn class(&self, kind: &TokenKind) -> Option<&'static str> {
+        self.rules.iter().rev().find(|(applies, _)| applies(kind)).map(|&(_, class)| class)
+    }
+
+    /// Renders tokens lexed from `source` as HTML
+    ///
+    /// The HTML is meant to go inside a `<pre>` element. Only comments lexed
+    /// as tokens, with [`LexerBuilder::trivia`](crate::LexerBuilder::trivia),
+    /// are highlighted. The tokens must be in source order, as a lexer
+    /// produces them.
+    pub fn render<'t, 'a: 't>(&self, source: &str, tokens: impl IntoIterator<Item = &'t Token<'a>>) -> String {
+        let mut html = String::with_capacity(source.len());
+        let _ = self.write(&mut html, source, tokens);
+        html
+    }
+
+    /// Writes tokens lexed from `source` as HTML
+    ///
+    /// See [`HtmlHighlighter::render`].
+    pub fn write<'t, 'a: 't>(&self, output: &mut impl Write, source: &str, tokens: impl IntoIterator<Item = &'t Token<'a>>) -> fmt::Result {
+        let mut end = 0;
+        for token in tokens {
+            let start = token.span.start.max(end);
+            let Some(text) = source.get(start..token.span.end).filter(|text| !text.is_empty()) else {
+                continue;
+            };
+            write_escaped(output, &source[end..start])?;
+            match self.class(&token.kind) {
+                Some(class) => {
+                    write!(output, "<span class=\"{class}\">")?;
+                    write_escaped(output, text)?;
+                    output.write_str("</span>")?;
+                },
+                None => write_escaped(output, text)?
+            }
+            end = token.span.end;
+        }
+        write_escaped(output, &source[end..])
+    }
+}
+
+/// Writes text with the characters special to HTML escaped
+fn write_escaped(output: &mut impl Write, text: &str) -> fmt::Result {
+    let mut written = 0;
+    for (i, ch) in text.char_indices() {
+        let escaped = match ch {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' => "&quot;",
+            '\'' => "&#39;",
+            _ => continue
+        };
+        output.write_str(&text[written..i])?;
+        output.write_str(escaped)?;
+        written = i + 1;
+    }
+    output.write_str(&text[written..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LexerBuilder;
+
+    /// Lexes a source and renders it with a highlighter
+    fn render(highlighter: &HtmlHighlighter, builder: &LexerBuilder, source: &str) -> String {
+        let tokens: Vec<_> = builder.build(source).collect();
+        highlighter.render(source, &tokens)
+    }
+
+    #[test]
+    fn wraps_tokens_in_classes() {
+        let builder = LexerBuilder::new().keywords(["if"]).string_delimiters(['"']).trivia(true);
+        assert_eq!(
+            render(&HtmlHighlighter::new(), &builder, "if a < 1 // \"x\"\n  \"<b>\" $"),
+            concat!(
+                "<span class=\"keyword\">if</span> <span class=\"ident\">a</span> <span class=\"op\">&lt;</span> ",
+                "<span class=\"num\">1</span> <span class=\"comment\">// &quot;x&quot;</span>\n  ",
+                "<span class=\"str\">&quot;&lt;b&gt;&quot;</span> <span class=\"error\">$</span>"
+            )
+        );
+    }
+
+    #[test]
+    fn skipped_text_is_escaped_plainly() {
+        // Without trivia, comments are written as the text between tokens
+        let builder = LexerBuilder::new();
+        assert_eq!(render(&HtmlHighlighter::empty(), &builder, "a // <'&'>"), "a // &lt;&#39;&amp;&#39;&gt;");
+    }
+
+    #[test]
+    fn later_rules_take_priority() {
+        let highlighter = HtmlHighlighter::new().with(|kind| matches!(kind, TokenKind::Ident(name) if name == "self"), "self");
+        assert_eq!(highlighter.class(&TokenKind::Ident("self".into())), Some("self"));
+        assert_eq!(highlighter.class(&TokenKind::Ident("x".into())), Some("ident"));
+        assert_eq!(highlighter.class(&TokenKind::Newline), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Relexing only the part of a token stream an edit affects.
+use crate::builder::Config;
+use crate::{Lexer, LexerBuilder, Span, Token, TokenKind, TokenStream};
+use alloc::borrow::Cow;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A change to a source string
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Edit {
+    /// The bytes of the old source replaced
+    pub range: Range<usize>,
+    /// The length in bytes of the text replacing them
+    pub len: usize
+}
+
+impl Edit {
+    /// Creates an edit replacing a range of bytes with some text
+    pub fn new(range: Range<usize>, text: &str) -> Self {
+        Self { range, len: text.len() }
+    }
+
+    /// The number of line breaks in a string
+    fn lines(text: &str) -> isize {
+        text.bytes().filter(|&byte| byte == b'\n').count() as isize
+    }
+}
+
+/// Moves a slice of the old source to the same text in the new source,
+/// `shift` bytes later
+///
+/// Returns `None` for text that wasn't lexed from the old source.
+fn rebase<'b>(text: &str, old: &str, new: &'b str, shift: isize) -> Option<&'b str> {
+    let start = (text.as_ptr() as usize).wrapping_sub(old.as_ptr() as usize);
+    if start > old.len() || text.len() > old.len() - start {
+        return None;
+    }
+    let start = start.checked_add_signed(shift)?;
+    new.get(start..start + text.len())
+}
+
+/// Moves a token of the old source to the new source, `shift` bytes and
+/// `rows` lines later
+fn rebase_token<'b>(token: &Token, old: &str, new: &'b str, offset: usize, shift: isize, rows: isize) -> Option<Token<'b>> {
+    let span = Span {
+        start: token.span.start.checked_add_signed(shift)?,
+        end: token.span.end.checked_add_signed(shift)?
+    };
+    let text = |text| rebase(text, old, new, shift);
+    let kind = match &token.kind {
+        TokenKind::Opr(op) => TokenKind::Opr(op.clone()),
+        TokenKind::Ident(Cow::Borrowed(ident)) => TokenKind::Ident(Cow::Borrowed(text(ident)?)),
+        TokenKind::Ident(Cow::Owned(ident)) => TokenKind::Ident(Cow::Owned(ident.clone())),
+        TokenKind::Symbol(symbol) => TokenKind::Symbol(*symbol),
+        TokenKind::Keyword(keyword) => TokenKind::Keyword(text(keyword)?),
+        // Symbols from a fallback rule may not be slices of the source
+        TokenKind::Punct(symbol) => TokenKind::Punct(text(symbol).or_else(|| new.get(span.start - offset..span.end - offset))?),
+        TokenKind::Num(value) => TokenKind::Num(*value),
+        TokenKind::Float(number) => TokenKind::Float(text(number)?),
+        TokenKind::Str(contents) => TokenKind::Str(text(contents)?),
+        TokenKind::Text(contents) => TokenKind::Text(text(contents)?),
+        TokenKind::Newline => TokenKind::Newline,
+        TokenKind::Indent => TokenKind::Indent,
+        TokenKind::Dedent => TokenKind::Dedent,
+        TokenKind::Whitespace(contents) => TokenKind::Whitespace(text(contents)?),
+        TokenKind::Comment(contents) => TokenKind::Comment(text(contents)?),
+        TokenKind::OpeningBracket => TokenKind::OpeningBracket,
+        TokenKind::ClosingBracket => TokenKind::ClosingBracket,
+        TokenKind::Error(err) => TokenKind::Error(err.clone())
+    };
+    let (row, col) = token.position;
+    Some(Token { kind, position: (row.checked_add_signed(rows)?, col), span })
+}
+
+/// Relexes the part of a token stream an edit affects
+///
+/// Returns the new stream and the range of bytes of the new source whose
+/// tokens were relexed.
+fn relex<'b>(config: &Arc<Config>, old: &TokenStream, source: &'b str, edit: &Edit) -> (TokenStream<'b>, Range<usize>) {
+    let offset = old.offset;
+    let lexer = |start: usize, position| {
+        let mut lexer = Lexer::with_config(&source[start..], Arc::clone(config)).starting_at(offset + start, position);
+        // Only the start of the whole source can have a byte-order mark
+        if start > 0 && lexer.bom > 0 {
+            lexer.bom = 0;
+            lexer.move_to(0);
+        }
+        lexer
+    };
+    let full = || {
+        let tokens = lexer(0, config.base).collect();
+        (TokenStream { source, offset, name: old.name.clone(), tokens }, 0..source.len())
+    };
+
+    // Modes, layout, negative literals and nesting carry state from one
+    // token to the next, which the stream doesn't keep, and the source's
+    // length limit applies to all of it
+    let stateful = config.interpolation.is_some() || config.regions.is_some() || config.negative_literals;
+    if config.layout() || stateful || config.max_nesting.is_some() || config.max_source_len.is_some() {
+        return full();
+    }
+    let (Some(replaced), Some(inserted)) = (
+        old.source.get(edit.range.clone()),
+        source.get(edit.range.start..edit.range.start + edit.len)
+    ) else {
+        return full();
+    };
+    let shift = edit.len as isize - replaced.len() as isize;
+    let rows = Edit::lines(inserted) - Edit::lines(replaced);
+
+    // Lexing restarts at the last token ending before the edit, so that
+    // the edit is lexed along with anything it joins onto
+    let old_source = old.source;
+    let old_tokens = &old.tokens;
+    let touched = old_tokens.partition_point(|token| token.span.end - offset < edit.range.start);
+    let restart = touched.saturating_sub(1);
+    let mut tokens = Vec::with_capacity(old_tokens.len());
+    for token in &old_tokens[..restart] {
+        match rebase_token(token, old_source, source, offset, 0, 0) {
+            Some(token) => tokens.push(token),
+            None => return full()
+        }
+    }
+    let (start, mut lexer) = match old_tokens.get(restart) {
+        Some(token) if restart > 0 => {
+            let start = token.span.start - offset;
+            (start, lexer(start, token.position))
+        },
+        _ => (0, lexer(0, config.base))
+    };
+
+    // Old tokens after the edit are reused from the first one a new token
+    // matches, as everything after it lexes the same
+    let edit_end = offset + edit.range.start + edit.len;
+    let mut next_old = touched;
+    for token in lexer.by_ref() {
+        if token.span.start >= edit_end {
+            let old_start = token.span.start as isize - shift;
+            while old_tokens.get(next_old).is_some_and(|old| (old.span.start as isize) < old_start) {
+                next_old += 1;
+            }
+            let matches = old_tokens.get(next_old).is_some_and(|old| {
+                rebase_token(old, old_source, source, offset, shift, rows).as_ref() == Some(&token)
+            });
+            if matches {
+                let end = token.span.start - offset;
+                for old in &old_tokens[next_old..] {
+                    match rebase_token(old, old_source, source, offset, shift, rows) {
+                        Some(token) => tokens.push(token),
+                        None => return full()
+                    }
+                }
+                return (TokenStream { source, offset, name: old.name.clone(), tokens }, start..end);
+            }
+        }
+        tokens.push(token);
+    }
+
+    (TokenStream { source, offset, name: old.name.clone(), tokens }, start..source.len())
+}
+
+impl<'a> TokenStream<'a> {
+    /// Relexes the tokens an edit affects, lexed with the default settings
+    ///
+    /// See [`LexerBuilder::relex`].
+    pub fn relex<'b>(&self, source: &'b str, edit: &Edit) -> (TokenStream<'b>, Range<usize>) {
+        relex(&Arc::default(), self, source, edit)
+    }
+}
+
+impl LexerBuilder {
+    /// Relexes the tokens an edit to their source affects
+    ///
+    /// `stream` must have been lexed with these settings, and `source` is
+    /// the edited source. Tokens before and after the edit are reused where
+    /// relexing them would give the same tokens. Returns the new stream and
+    /// the range of bytes of the new source whose tokens were relexed.
+    ///
+    /// Newline, indentation and interpolated string tokens depend on what
+    /// came before them, so with those settings the whole source is relexed.
+    pub fn relex<'b>(&self, stream: &TokenStream, source: &'b str, edit: &Edit) -> (TokenStream<'b>, Range<usize>) {
+        relex(&self.config, stream, source, edit)
+    }
+}
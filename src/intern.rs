@@ -0,0 +1,225 @@
+//! Interning identifiers as small ids.
+use crate::{Lexer, Token, TokenKind};
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::sync::{PoisonError, RwLock};
+
+/// An identifier interned by an [`Interner`]
+///
+/// Symbols from the same interner are equal exactly when their identifiers
+/// are, so comparing them doesn't touch the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol(pub(crate) u32);
+
+impl Symbol {
+    /// The symbol's id, counting up from 0 in the order identifiers were interned
+    #[inline]
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A table of interned identifiers
+///
+/// One interner can be shared by the lexers of several files so the same
+/// identifier gets the same [`Symbol`] in all of them.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    /// The symbol of each identifier
+    symbols: BTreeMap<Arc<str>, Symbol>,
+    /// The identifier of each symbol
+    idents: Vec<Arc<str>>
+}
+
+impl Interner {
+    /// Creates an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the symbol for an identifier, interning it if it's new
+    ///
+    /// # Panics
+    ///
+    /// If more than `u32::MAX` identifiers are interned.
+    pub fn intern(&mut self, ident: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(ident) {
+            return symbol;
+        }
+
+        let symbol = Symbol(u32::try_from(self.idents.len()).expect("too many interned identifiers"));
+        let ident: Arc<str> = ident.into();
+        self.idents.push(Arc::clone(&ident));
+        self.symbols.insert(ident, symbol);
+        symbol
+    }
+
+    /// Gets the symbol for an identifier if it has been interned
+    pub fn get(&self, ident: &str) -> Option<Symbol> {
+        self.symbols.get(ident).copied()
+    }
+
+    /// The identifier a symbol stands for
+    ///
+    /// # Panics
+    ///
+    /// If the symbol came from a different interner.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.idents[symbol.0 as usize]
+    }
+
+    /// The number of identifiers interned
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.idents.len()
+    }
+
+    /// Has nothing been interned?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.idents.is_empty()
+    }
+}
+
+/// An interner thread-safe lexers can share
+///
+/// Clones share the same table, so lexers on different threads give each
+/// identifier the same [`Symbol`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct SharedInterner {
+    interner: Arc<RwLock<Interner>>
+}
+
+#[cfg(feature = "std")]
+impl SharedInterner {
+    /// Creates an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the symbol for an identifier, interning it if it's new
+    pub fn intern(&self, ident: &str) -> Symbol {
+        if let Some(symbol) = self.get(ident) {
+            return symbol;
+        }
+        self.interner.write().unwrap_or_else(PoisonError::into_inner).intern(ident)
+    }
+
+    /// Gets the symbol for an identifier if it has been interned
+    pub fn get(&self, ident: &str) -> Option<Symbol> {
+        self.interner.read().unwrap_or_else(PoisonError::into_inner).get(ident)
+    }
+
+    /// The identifier a symbol stands for
+    ///
+    /// # Panics
+    ///
+    /// If the symbol came from a different interner.
+    pub fn resolve(&self, symbol: Symbol) -> Arc<str> {
+        let interner = self.interner.read().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(&interner.idents[symbol.0 as usize])
+    }
+
+    /// The number of identifiers interned
+    pub fn len(&self) -> usize {
+        self.interner.read().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Has nothing been interned?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Something identifiers can be interned into
+pub trait Intern {
+    /// Gets the symbol for an identifier, interning it if it's new
+    fn intern(&mut self, ident: &str) -> Symbol;
+}
+
+impl Intern for Interner {
+    #[inline]
+    fn intern(&mut self, ident: &str) -> Symbol {
+        Interner::intern(self, ident)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Intern for SharedInterner {
+    #[inline]
+    fn intern(&mut self, ident: &str) -> Symbol {
+        SharedInterner::intern(self, ident)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Intern for &SharedInterner {
+    #[inline]
+    fn intern(&mut self, ident: &str) -> Symbol {
+        SharedInterner::intern(self, ident)
+    }
+}
+
+impl<I: Intern + ?Sized> Intern for &mut I {
+    #[inline]
+    fn intern(&mut self, ident: &str) -> Symbol {
+        I::intern(self, ident)
+    }
+}
+
+/// A lexer producing [`TokenKind::Symbol`] for identifiers, returned by
+/// [`Lexer::interned`]
+#[derive(Debug)]
+pub struct Interned<'a, I> {
+    lexer: Lexer<'a>,
+    interner: I
+}
+
+impl<'a, I> Interned<'a, I> {
+    /// The underlying lexer
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'a> {
+        &self.lexer
+    }
+
+    /// The interner identifiers are added to
+    #[inline]
+    pub fn interner(&self) -> &I {
+        &self.interner
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Interns identifiers, producing their symbols instead of their text
+    ///
+    /// Takes an `&mut Interner`, or a `SharedInterner` for lexers running on
+    /// several threads.
+    #[inline]
+    pub fn interned<I: Intern>(self, interner: I) -> Interned<'a, I> {
+        Interned { lexer: self, interner }
+    }
+}
+
+impl<'a, I: Intern> Iterator for Interned<'a, I> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let mut token = self.lexer.next()?;
+        if let TokenKind::Ident(ident) = &token.kind {
+            token.kind = TokenKind::Symbol(self.interner.intern(ident));
+        }
+        Some(token)
+    }
+}
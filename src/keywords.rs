@@ -0,0 +1,213 @@
+//! Fast keyword lookup.
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Do two strings match ignoring case?
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    if a.is_ascii() && b.is_ascii() {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a.chars().flat_map(char::to_lowercase).eq(b.chars().flat_map(char::to_lowercase))
+    }
+}
+
+/// The number of characters inserted, deleted, substituted or swapped with
+/// the next to turn one string into another
+fn edit_distance(a: &str, b: &str, ignore_case: bool) -> usize {
+    let fold = |ch: char| if ignore_case { ch.to_lowercase().next().unwrap_or(ch) } else { ch };
+    let a: Vec<char> = a.chars().map(fold).collect();
+    let b: Vec<char> = b.chars().map(fold).collect();
+
+    // The distances from the prefixes of `a` one and two characters shorter,
+    // and from the current prefix, to each prefix of `b`
+    let mut before = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        row[0] = i;
+        for j in 1..=b.len() {
+            let mut distance = (prev[j - 1] + usize::from(a[i - 1] != b[j - 1])).min(prev[j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(before[j - 2] + 1);
+            }
+            row[j] = distance;
+        }
+        core::mem::swap(&mut before, &mut prev);
+        core::mem::swap(&mut prev, &mut row);
+    }
+    prev[b.len()]
+}
+
+/// A map from keywords to values, e.g. a project's own keyword enum
+///
+/// Keywords are bucketed by their length in bytes, so a lookup only compares
+/// against keywords of the same length and most identifiers are rejected
+/// without comparing any text.
+#[derive(Debug, Clone)]
+pub struct KeywordTable<K> {
+    /// The keywords of each length, sorted
+    buckets: Vec<Vec<(Box<str>, K)>>,
+    /// The number of keywords
+    len: usize
+}
+
+impl<K> Default for KeywordTable<K> {
+    fn default() -> Self {
+        Self { buckets: Vec::new(), len: 0 }
+    }
+}
+
+impl<K> KeywordTable<K> {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of keywords in the table
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the table empty?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds a keyword, returning the value it previously mapped to
+    pub fn insert(&mut self, keyword: impl Into<Box<str>>, value: K) -> Option<K> {
+        let keyword = keyword.into();
+        if self.buckets.len() <= keyword.len() {
+            self.buckets.resize_with(keyword.len() + 1, Vec::new);
+        }
+
+        let bucket = &mut self.buckets[keyword.len()];
+        match bucket.binary_search_by(|(existing, _)| (**existing).cmp(&*keyword)) {
+            Ok(i) => Some(core::mem::replace(&mut bucket[i].1, value)),
+            Err(i) => {
+                bucket.insert(i, (keyword, value));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// The keywords with the same length as a string
+    #[inline]
+    fn bucket(&self, text: &str) -> &[(Box<str>, K)] {
+        self.buckets.get(text.len()).map_or(&[], Vec::as_slice)
+    }
+
+    /// Looks up the value a keyword maps to
+    pub fn get(&self, text: &str) -> Option<&K> {
+        let bucket = self.bucket(text);
+        bucket
+            .binary_search_by(|(keyword, _)| (**keyword).cmp(text))
+            .ok()
+            .map(|i| &bucket[i].1)
+    }
+
+    /// Looks up the value a keyword maps to, ignoring case
+    pub fn get_ignore_case(&self, text: &str) -> Option<&K> {
+        self.bucket(text)
+            .iter()
+            .find(|(keyword, _)| eq_ignore_case(keyword, text))
+            .map(|(_, value)| value)
+    }
+
+    /// Is the string a keyword?
+    #[inline]
+    pub fn contains(&self, text: &str) -> bool {
+        self.get(text).is_some()
+    }
+
+    /// The keyword closest to a string by edit distance, for suggesting
+    /// "did you mean `while`?"
+    ///
+    /// An edit inserts, deletes or substitutes a character, or swaps two
+    /// adjacent ones. Only keywords a third of the string's length away or
+    /// closer (at least one edit) are suggested, the shortest first on ties.
+    pub fn suggest(&self, text: &str) -> Option<&str> {
+        self.closest(text, false)
+    }
+
+    /// The keyword closest to a string by edit distance, ignoring case
+    ///
+    /// See [`KeywordTable::suggest`].
+    pub fn suggest_ignore_case(&self, text: &str) -> Option<&str> {
+        self.closest(text, true)
+    }
+
+    /// The keyword closest to a string within the distance suggested
+    fn closest(&self, text: &str, ignore_case: bool) -> Option<&str> {
+        let len = text.chars().count();
+        let limit = (len / 3).max(1);
+        self.iter()
+            .map(|(keyword, _)| keyword)
+            .filter(|keyword| keyword.chars().count().abs_diff(len) <= limit)
+            .map(|keyword| (edit_distance(keyword, text, ignore_case), keyword))
+            .filter(|&(distance, _)| distance <= limit)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, keyword)| keyword)
+    }
+
+    /// Iterates over the keywords and their values, shortest first
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &K)> {
+        self.buckets.iter().flatten().map(|(keyword, value)| (&**keyword, value))
+    }
+}
+
+impl<S: Into<Box<str>>, K> FromIterator<(S, K)> for KeywordTable<K> {
+    fn from_iter<I: IntoIterator<Item = (S, K)>>(iter: I) -> Self {
+        let mut table = Self::new();
+        for (keyword, value) in iter {
+            table.insert(keyword, value);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LexerBuilder;
+
+    /// A table of some C keywords
+    fn table() -> KeywordTable<()> {
+        ["if", "in", "for", "else", "while", "return"].into_iter().map(|keyword| (keyword, ())).collect()
+    }
+
+    #[test]
+    fn suggests_keywords_one_edit_away() {
+        let table = table();
+        assert_eq!(table.suggest("whiel"), Some("while"));
+        assert_eq!(table.suggest("retrun"), Some("return"));
+        assert_eq!(table.suggest("els"), Some("else"));
+        assert_eq!(table.suggest("fore"), Some("for"));
+        assert_eq!(table.suggest("iff"), Some("if"));
+    }
+
+    #[test]
+    fn doesnt_suggest_distant_keywords() {
+        let table = table();
+        assert_eq!(table.suggest("x"), None);
+        assert_eq!(table.suggest("whatever"), None);
+        // Two edits are too many for a string this short
+        assert_eq!(table.suggest("wihel"), None);
+        assert_eq!(KeywordTable::<()>::new().suggest("while"), None);
+    }
+
+    #[test]
+    fn suggests_ignoring_case() {
+        let table = table();
+        assert_eq!(table.suggest("WHILE"), None);
+        assert_eq!(table.suggest_ignore_case("WHILE"), Some("while"));
+        assert_eq!(table.suggest_ignore_case("Whiel"), Some("while"));
+
+        let builder = LexerBuilder::new().keywords(["while"]);
+        assert_eq!(builder.build("").suggest_keyword("Whiel"), None);
+        assert_eq!(builder.case_insensitive_keywords(true).build("").suggest_keyword("Whiel"), Some("while"));
+    }
+}
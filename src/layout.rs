@@ -0,0 +1,164 @@
+//! Layout tokens for languages with significant newlines and indentation.
+use crate::line_index::next_column;
+use crate::{Delimiter, LexErrorKind, Lexer, TokenKind, BOM};
+use alloc::vec::Vec;
+
+/// The indentation of an enclosing block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Block {
+    /// Its width in columns
+    width: usize,
+    /// Its widths with tabs one and eight columns wide, if checking them
+    tabs: (usize, usize)
+}
+
+/// The lexer's progress through the layout of the source
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Layout {
+    /// The indentation of the enclosing blocks
+    indents: Vec<Block>,
+    /// Dedents still to be produced
+    pending_dedents: usize,
+    /// An error about the indentation still to be reported
+    pending_error: Option<LexErrorKind>,
+    /// Whether a token has been produced on the current line
+    line_has_tokens: bool,
+    /// The number of brackets currently open
+    depth: usize
+}
+
+impl<'a> Lexer<'a> {
+    /// Is the lexer producing layout tokens at this point?
+    #[inline]
+    pub(crate) fn tracks_layout(&self) -> bool {
+        self.config.layout() && self.modes.is_empty() && self.layout.depth == 0
+    }
+
+    /// Should whitespace trimming stop at the end of this line?
+    #[inline]
+    pub(crate) fn keeps_line_end(&self) -> bool {
+        self.tracks_layout() && self.layout.line_has_tokens
+    }
+
+    /// Removes a line break ending a line with tokens on it
+    ///
+    /// Also produces any dedents still due.
+    pub(crate) fn trim_newline(&mut self) -> Option<TokenKind<'a>> {
+        if self.layout.pending_dedents > 0 {
+            self.layout.pending_dedents -= 1;
+            return Some(TokenKind::Dedent);
+        }
+        if let Some(err) = self.layout.pending_error.take() {
+            return Some(TokenKind::Error(err));
+        }
+        if !self.keeps_line_end() || !self.at_line_end() {
+            return None;
+        }
+
+        if self.prev == '\r' && self.peek() == Some('\n') {
+            self.next_char();
+        }
+        self.next_char();
+        self.layout.line_has_tokens = false;
+        Some(TokenKind::Newline)
+    }
+
+    /// Compares the indentation of a line's first token with the enclosing
+    /// block's, producing an indent or dedent if it changed
+    pub(crate) fn trim_indentation(&mut self) -> Option<TokenKind<'a>> {
+        if !self.tracks_layout() || self.layout.line_has_tokens {
+            return None;
+        }
+        self.layout.line_has_tokens = true;
+        if !self.config.indentation {
+            return None;
+        }
+
+        let width = self.indentation_width(self.config.tab_width);
+        let tabs = if self.config.warnings || self.config.tab_errors { self.tab_widths() } else { (0, 0) };
+        let layout = &mut self.layout;
+        let current = layout.indents.last().copied().unwrap_or_default();
+
+        if width > current.width {
+            layout.indents.push(Block { width, tabs });
+            self.check_tabs(current, tabs);
+            return Some(TokenKind::Indent);
+        }
+
+        while layout.indents.last().is_some_and(|block| block.width > width) {
+            layout.indents.pop();
+            layout.pending_dedents += 1;
+        }
+        let block = layout.indents.last().copied().unwrap_or_default();
+        layout.pending_error = (block.width != width).then_some(LexErrorKind::InconsistentDedent);
+        self.check_tabs(block, tabs);
+        self.trim_newline()
+    }
+
+    /// The leading whitespace of the line the lexer is at the start of
+    ///
+    /// Measured from the line's text rather than the lexer's column, which
+    /// doesn't start at the base for a source starting mid-line.
+    fn indentation(&self) -> &'a str {
+        let line_start = self.source[..self.pos].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+        let line = self.slice(line_start, self.pos);
+        let line = if line_start == 0 && self.config.skip_bom { line.strip_prefix(BOM).unwrap_or(line) } else { line };
+        &line[..line.len() - line.trim_start_matches(self.config.is_whitespace).len()]
+    }
+
+    /// The width in columns of the indentation of the line the lexer is at
+    /// the start of, with tabs of a width
+    fn indentation_width(&self, tab_width: usize) -> usize {
+        let encoding = self.config.encoding;
+        self.indentation().chars().fold(1, |col, ch| next_column(col, ch, encoding, tab_width)) - 1
+    }
+
+    /// The widths of the indentation of the line the lexer is at the start
+    /// of, with tabs one and eight columns wide
+    fn tab_widths(&self) -> (usize, usize) {
+        (self.indentation_width(1), self.indentation_width(8))
+    }
+
+    /// Checks that a line's indentation compares with a block's the same
+    /// whatever the width of tabs, as Python does
+    ///
+    /// Reports an error if configured to, or a warning if checking for them.
+    fn check_tabs(&mut self, block: Block, tabs: (usize, usize)) {
+        let inconsistent = tabs.0.cmp(&block.tabs.0) != tabs.1.cmp(&block.tabs.1);
+        if inconsistent && self.config.tab_errors {
+            self.layout.pending_error.get_or_insert(LexErrorKind::InconsistentTabs);
+        } else if self.config.warnings {
+            self.check_indentation(inconsistent);
+        }
+    }
+
+    /// Produces the layout tokens closing the source
+    pub(crate) fn trim_layout_end(&mut self) -> Option<TokenKind<'a>> {
+        if !self.config.layout() {
+            return None;
+        }
+        if self.layout.line_has_tokens {
+            self.layout.line_has_tokens = false;
+            return Some(TokenKind::Newline);
+        }
+
+        self.layout.indents.pop().map(|_| TokenKind::Dedent)
+    }
+
+    /// Tracks the delimiters a token opens and closes
+    ///
+    /// `modes` is the number of modes the lexer was in before the token, so
+    /// a `}` closing an embedded expression isn't taken for a brace.
+    pub(crate) fn track_layout(&mut self, kind: &TokenKind<'a>, modes: usize) {
+        if self.modes.len() != modes {
+            return;
+        }
+        if Delimiter::opened_by(kind).is_some() {
+            self.layout.depth += 1;
+        } else if Delimiter::closed_by(kind).is_some() {
+            self.layout.depth = self.layout.depth.saturating_sub(1);
+        }
+    }
+}
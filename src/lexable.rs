@@ -0,0 +1,73 @@
+//! Lexing into token enums deriving `Lexable`.
+use crate::{Lexer, Span};
+use core::marker::PhantomData;
+
+/// A token type that matches its own patterns against the source
+///
+/// Usually derived with `#[derive(Lexable)]` under the `derive` feature.
+pub trait Lexable<'a>: Sized {
+    /// Lexes the longest token at the start of the source, along with its
+    /// length in bytes
+    fn lex(rest: &'a str) -> Option<(Self, usize)>;
+
+    /// The token standing for text no pattern matches
+    fn error() -> Self;
+}
+
+/// A lexer producing tokens of a [`Lexable`] type
+///
+/// Whitespace and comments are skipped as configured on the underlying
+/// lexer. Yields each token along with the bytes it was lexed from.
+#[derive(Debug, Clone)]
+pub struct LexableLexer<'a, T> {
+    lexer: Lexer<'a>,
+    marker: PhantomData<fn() -> T>
+}
+
+impl<'a, T> LexableLexer<'a, T> {
+    /// The underlying lexer
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'a> {
+        &self.lexer
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Lexes tokens of a [`Lexable`] type instead of [`TokenKind`](crate::TokenKind)s
+    pub fn with_tokens<T: Lexable<'a>>(self) -> LexableLexer<'a, T> {
+        LexableLexer { lexer: self, marker: PhantomData }
+    }
+}
+
+impl<'a, T: Lexable<'a>> Iterator for LexableLexer<'a, T> {
+    type Item = (T, Span);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let lexer = &mut self.lexer;
+        loop {
+            lexer.trim_whitespace();
+            if lexer.is_over() {
+                return None;
+            }
+
+            let start = lexer.pos;
+            let token = match lexer.trim_comment() {
+                Ok(true) => continue,
+                Ok(false) => match T::lex(lexer.rest()) {
+                    Some((token, len)) if len > 0 => {
+                        lexer.advance_to(start + len);
+                        token
+                    },
+                    _ => {
+                        lexer.next_char();
+                        T::error()
+                    }
+                },
+                Err(_) => T::error()
+            };
+
+            let span = Span { start: lexer.offset + start, end: lexer.offset + lexer.pos };
+            return Some((token, span));
+        }
+    }
+}
@@ -1,9 +1,153 @@
 #![allow(dead_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
 //! This module was designed to be reusable between programming language projects.
-use std::str::Chars;
+//!
+//! Without the default `std` feature the library only depends on `alloc`.
+//!
+//! The `wasm` and `ffi` features export bindings for JavaScript and C, built
+//! as a C-compatible library with, e.g.
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::str::Chars;
+
+mod ascii;
+mod builder;
+mod bytes;
+mod cache;
+mod category;
+mod checkpoint;
+mod combinator;
+mod construct;
+mod digits;
+mod dump;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod error;
+mod eval;
+mod expect;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod highlight;
+mod incremental;
+mod intern;
+mod keywords;
+mod layout;
+mod lexable;
+mod line_index;
+#[cfg(feature = "unicode")]
+mod lint;
+mod macros;
+mod minify;
+mod mode;
+mod operators;
+mod owned;
+#[cfg(feature = "std")]
+mod parallel;
+mod precedence;
+mod pretty;
+#[cfg(feature = "profiling")]
+mod profiling;
+pub mod profiles;
+mod rule;
+mod seek;
+mod semantic;
+mod session;
+mod shared;
+mod significant;
+mod spanned;
+mod state;
+mod stats;
+mod stream;
+mod streaming;
+mod sublex;
+mod tokenize;
+mod tree;
+mod trivia;
+mod triples;
+mod warning;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use builder::LexerBuilder;
+pub use bytes::BytesLexer;
+pub use cache::CacheError;
+pub use category::TokenCategory;
+pub use checkpoint::{Checkpoint, Speculation};
+#[cfg(feature = "nom")]
+pub use combinator::Tokens;
+pub use error::{DelimiterError, LexError, LexErrorKind};
+pub use eval::{EvalError, Value};
+#[cfg(feature = "ffi")]
+pub use ffi::{reusable_lexer_free_tokens, reusable_lexer_tokenize, RlToken, RlTokenArray};
+pub use highlight::HtmlHighlighter;
+pub use incremental::Edit;
+pub use intern::{Intern, Interned, Interner, Symbol};
+#[cfg(feature = "std")]
+pub use intern::SharedInterner;
+pub use keywords::KeywordTable;
+pub use lexable::{Lexable, LexableLexer};
+#[cfg(feature = "derive")]
+pub use reusable_lexer_derive::Lexable;
+use ascii::{AsciiClasses, TokenStart};
+use builder::Config;
+use layout::Layout;
+use line_index::{next_column, starts_column};
+use stats::Tally;
+pub use line_index::{LineIndex, PositionEncoding};
+pub use mode::Mode;
+pub use owned::{OwnedToken, OwnedTokenKind};
+pub use precedence::{Associativity, PrecedenceTable};
+pub use pretty::PrettyPrinter;
+#[cfg(feature = "profiling")]
+pub use profiling::{LexProfile, TokenClass};
+#[cfg(feature = "unicode")]
+pub use lint::{check_idents, IdentLint, IdentLintKind};
+pub use rule::{RuleLexer, TokenRule};
+pub use semantic::{KindFilter, SemanticTokenMap};
+pub use session::{IncludeError, IncludeErrorKind, IncludeSession, SessionToken};
+pub use shared::{SharedLexer, SharedToken};
+pub use significant::Significant;
+pub use spanned::SpannedText;
+pub use state::LexerState;
+pub use stats::LexStats;
+pub use stream::{Cursor, TokenStream};
+#[cfg(feature = "async")]
+pub use streaming::AsyncReaderLexer;
+#[cfg(feature = "std")]
+pub use streaming::ReaderLexer;
+pub use streaming::{FragmentLexer, StreamingLexer};
+pub use tokenize::TokenizeStats;
+pub use tree::{match_delimiters, token_trees, validate_delimiters, Delimiter, DelimiterMap, Group, TokenTree};
+pub use triples::{Triple, Triples};
+pub use trivia::{AttachedTrivia, TriviaToken};
+pub use warning::{IdentCase, LexWarning, LexWarningKind};
+#[cfg(feature = "wasm")]
+pub use wasm::tokenize;
+
+/// Items used by the code `#[derive(Lexable)]` generates
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use regex::Regex;
+}
+
+/// The UTF-8 byte-order mark
+pub(crate) const BOM: char = '\u{FEFF}';
+
+/// The number of tokens lexed between asking whether lexing was cancelled
+const CANCEL_INTERVAL: usize = 256;
 
 /// Mathematical operations (e.g. +, -, *, /)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Op {
     Plus,
     Minus,
@@ -16,67 +160,502 @@ pub enum Op {
     GreaterOrEqual,
     Less,
     LessOrEqual,
+    Increment,
+    Decrement,
 }
 
 /// The different kinds of token
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenKind<'a> {
     Opr(Op),
-    Ident(&'a str),
+    /// An identifier, normalized if the lexer was configured to
+    Ident(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'a, str>),
+    /// An identifier interned by [`Lexer::interned`]
+    Symbol(Symbol),
+    /// An identifier registered as a keyword
+    Keyword(&'a str),
+    /// An operator registered at runtime or a delimiter of a [`Mode`], as written
+    Punct(&'a str),
     Num(i32),
+    /// A number literal a number rule lexed as not fitting [`TokenKind::Num`],
+    /// e.g. with a fraction or exponent, as written
+    Float(&'a str),
+    /// A string literal's contents, with escapes left as written
+    Str(&'a str),
+    /// Source text lexed by a raw or interpolated [`Mode`]
+    Text(&'a str),
+    /// The end of a line with tokens on it
+    Newline,
+    /// An increase in indentation, opening a block
+    Indent,
+    /// A decrease in indentation, closing a block
+    Dedent,
+    /// A run of whitespace, only produced when keeping trivia
+    Whitespace(&'a str),
+    /// A comment including its delimiters, only produced when keeping trivia
+    Comment(&'a str),
     OpeningBracket,
-    ClosingBracket
+    ClosingBracket,
+    /// Source text that couldn't be lexed
+    Error(LexErrorKind)
+}
+
+impl TokenKind<'_> {
+    /// Is this a number or string literal?
+    #[inline]
+    pub fn is_literal(&self) -> bool {
+        matches!(self, Self::Num(_) | Self::Float(_) | Self::Str(_))
+    }
+
+    /// Is this an identifier, keywords excluded?
+    #[inline]
+    pub fn is_ident(&self) -> bool {
+        matches!(self, Self::Ident(_) | Self::Symbol(_))
+    }
+
+    /// Is this a keyword?
+    #[inline]
+    pub fn is_keyword(&self) -> bool {
+        matches!(self, Self::Keyword(_))
+    }
+
+    /// Is this a built-in or registered operator, delimiters excluded?
+    #[inline]
+    pub fn is_operator(&self) -> bool {
+        matches!(self, Self::Opr(_) | Self::Punct(_)) && !self.is_delimiter()
+    }
+
+    /// Does this open or close a [`Delimiter`]?
+    #[inline]
+    pub fn is_delimiter(&self) -> bool {
+        Delimiter::opened_by(self).is_some() || Delimiter::closed_by(self).is_some()
+    }
+
+    /// Is this a newline, indent or dedent?
+    #[inline]
+    pub fn is_layout(&self) -> bool {
+        matches!(self, Self::Newline | Self::Indent | Self::Dedent)
+    }
+
+    /// Is this whitespace or a comment?
+    #[inline]
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Self::Whitespace(_) | Self::Comment(_))
+    }
+
+    /// Is this a lexical error?
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Error(_))
+    }
+
+    /// Can this token end an operand, making a `-` after it infix?
+    ///
+    /// Identifiers, literals and closing delimiters can. Keywords can't, so
+    /// the `-` in `return -1` is a prefix operator.
+    #[inline]
+    pub fn ends_operand(&self) -> bool {
+        matches!(self, Self::Ident(_) | Self::Symbol(_) | Self::Num(_) | Self::Float(_) | Self::Str(_))
+            || Delimiter::closed_by(self).is_some()
+    }
+
+    /// The name of the kind's variant, e.g. `"Ident"`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Opr(_) => "Opr",
+            Self::Ident(_) => "Ident",
+            Self::Symbol(_) => "Symbol",
+            Self::Keyword(_) => "Keyword",
+            Self::Punct(_) => "Punct",
+            Self::Num(_) => "Num",
+            Self::Float(_) => "Float",
+            Self::Str(_) => "Str",
+            Self::Text(_) => "Text",
+            Self::Newline => "Newline",
+            Self::Indent => "Indent",
+            Self::Dedent => "Dedent",
+            Self::Whitespace(_) => "Whitespace",
+            Self::Comment(_) => "Comment",
+            Self::OpeningBracket => "OpeningBracket",
+            Self::ClosingBracket => "ClosingBracket",
+            Self::Error(_) => "Error"
+        }
+    }
+
+    /// A number identifying the kind's variant, whatever it holds
+    ///
+    /// Ids count up from 0 in the order the variants were added, so they
+    /// stay the same across versions, and all fit in a `u32` bitset. They
+    /// match the `RL_KIND_*` constants of the C header.
+    #[inline]
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Opr(_) => 0,
+            Self::Ident(_) => 1,
+            Self::Symbol(_) => 2,
+            Self::Keyword(_) => 3,
+            Self::Punct(_) => 4,
+            Self::Num(_) => 5,
+            Self::Str(_) => 6,
+            Self::Text(_) => 7,
+            Self::Newline => 8,
+            Self::Indent => 9,
+            Self::Dedent => 10,
+            Self::Whitespace(_) => 11,
+            Self::Comment(_) => 12,
+            Self::OpeningBracket => 13,
+            Self::ClosingBracket => 14,
+            Self::Error(_) => 15,
+            Self::Float(_) => 16
+        }
+    }
+}
+
+/// Writes the token as written in the source
+///
+/// String literals are shown in double quotes, layout tokens by name,
+/// interned identifiers by their symbol's id and errors by their message.
+impl fmt::Display for TokenKind<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Opr(op) => op.fmt(f),
+            Self::Ident(ident) => f.write_str(ident),
+            Self::Symbol(symbol) => symbol.fmt(f),
+            Self::Keyword(text) | Self::Punct(text) | Self::Text(text) | Self::Float(text) => f.write_str(text),
+            Self::Whitespace(text) | Self::Comment(text) => f.write_str(text),
+            Self::Num(value) => value.fmt(f),
+            Self::Str(contents) => write!(f, "\"{contents}\""),
+            Self::Newline => f.write_str("newline"),
+            Self::Indent => f.write_str("indent"),
+            Self::Dedent => f.write_str("dedent"),
+            Self::OpeningBracket => f.write_str("("),
+            Self::ClosingBracket => f.write_str(")"),
+            Self::Error(err) => err.fmt(f)
+        }
+    }
+}
+
+/// A range of bytes in the source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// The offset of the first byte
+    pub start: usize,
+    /// The offset just past the last byte
+    pub end: usize
 }
 
 /// A lexical token
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token<'a> {
     /// The token's kind
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub kind: TokenKind<'a>,
     /// The token's position in file
-    pub position: (usize, usize)
+    pub position: (usize, usize),
+    /// The bytes the token was lexed from
+    pub span: Span
+}
+
+impl<'a> Token<'a> {
+    /// The exact source text the token was lexed from
+    ///
+    /// `source` is the file its span is relative to, which is the lexed
+    /// source unless the lexer was created with [`Lexer::with_offset`].
+    #[inline]
+    pub fn text(&self, source: &'a str) -> &'a str {
+        &source[self.span.start..self.span.end]
+    }
+
+    /// Is the token the same kind as another, ignoring what it holds?
+    ///
+    /// `token.is_kind(&TokenKind::Num(0))` is true for any number.
+    #[inline]
+    pub fn is_kind(&self, kind: &TokenKind) -> bool {
+        core::mem::discriminant(&self.kind) == core::mem::discriminant(kind)
+    }
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.kind.fmt(f)
+    }
 }
 
 /// The lexer iterator
+///
+/// Producing tokens doesn't allocate: they borrow their text from the
+/// source. The exceptions are identifiers normalized to a different
+/// spelling, and growing the stacks of modes, indentation widths and
+/// peeked tokens past the deepest they've been.
 #[derive(Debug, Clone)]
 pub struct Lexer<'a> {
     /// The source string being read
     source: &'a str,
+    /// The current character, `'\0'` once the lexer is over
     prev: char,
-    /// The previous character
+    /// The character after the current one, kept so peeking is a field read
+    ahead: Option<char>,
+    /// The characters after `ahead`
     chars: Chars<'a>,
+    /// Whether the end of the source has been reached
+    eof: bool,
     /// The utf-8 position in file
     pos: usize,
-    /// The row the lexer is on
+    /// The length of a skipped byte-order mark not yet produced as trivia
+    bom: usize,
+    /// The byte offset of the source within its file
+    offset: usize,
+    /// The name of the source, e.g. its path
+    name: Option<Arc<str>>,
+    /// The 1-based row the lexer is on
     row: usize,
-    /// The column the lexer is on
-    col: usize
+    /// The 1-based column the lexer is on
+    col: usize,
+    /// The 1-based row and column the source starts at
+    origin: (usize, usize),
+    /// The settings the lexer was built with
+    config: Arc<Config>,
+    /// The stack of modes the lexer is in, empty when in the normal mode
+    modes: Vec<Mode>,
+    /// The lexer's progress through newlines and indentation
+    layout: Layout,
+    /// Whether the last token lexed, trivia aside, can end an operand
+    after_operand: bool,
+    /// The number of delimiters open, counted if nesting is limited
+    depth: usize,
+    /// The number of tokens to lex before asking whether lexing was
+    /// cancelled
+    until_cancel_check: usize,
+    /// The comments lexed, if collecting them
+    comments: Vec<Token<'a>>,
+    /// The warnings found, if checking for them
+    warnings: Vec<LexWarning>,
+    /// The figures about the source, if collecting them
+    tally: Tally,
+    /// The time spent on each class of token
+    #[cfg(feature = "profiling")]
+    profile: LexProfile,
+    /// The tokens lexed ahead by [`Lexer::peek_nth`]
+    lookahead: VecDeque<Token<'a>>,
+    /// The span tokens are lexed in, recording the source's length and name
+    #[cfg(feature = "tracing")]
+    span: tracing::Span
 }
 
 impl<'a> Lexer<'a> {
     /// Creates a new lexer from a `&str`
     pub fn new(source: &'a str) -> Self {
-        let mut chars = source.chars();
-        Self {
+        Self::with_config(source, Arc::default())
+    }
+
+    /// Creates a new lexer from a `&str` using the given settings
+    pub(crate) fn with_config(source: &'a str, config: Arc<Config>) -> Self {
+        let bom = if config.skip_bom && source.starts_with(BOM) {
+            BOM.len_utf8()
+        } else {
+            0
+        };
+        let modes = Self::initial_modes(&config);
+        let mut lexer = Self {
             source,
-            prev: chars.next().unwrap_or('\0'),
-            chars,
-            pos: 0,
+            prev: '\0',
+            ahead: None,
+            chars: "".chars(),
+            eof: true,
+            pos: bom,
+            bom,
+            offset: 0,
+            name: None,
             row: 1,
-            col: 1
+            col: 1,
+            origin: (1, 1),
+            config,
+            modes,
+            layout: Layout::default(),
+            after_operand: false,
+            depth: 0,
+            until_cancel_check: 0,
+            comments: Vec::new(),
+            warnings: Vec::new(),
+            tally: Tally::default(),
+            #[cfg(feature = "profiling")]
+            profile: LexProfile::default(),
+            lookahead: VecDeque::new(),
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!("lex", len = source.len(), name = tracing::field::Empty)
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(parent: &lexer.span, "created lexer");
+        lexer.move_to(bom);
+        lexer
+    }
+
+    /// The stack of modes a lexer with the given settings starts in
+    fn initial_modes(config: &Config) -> Vec<Mode> {
+        // Templates start out in their host text
+        config.regions.iter().map(|(open, close)| Mode::Template { open: open.clone(), close: close.clone() }).collect()
+    }
+
+    /// Creates a new lexer from a `&str` embedded in a larger file
+    ///
+    /// `offset` is the byte offset of the source within the file and
+    /// `position` the `(row, col)` it starts at, so that spans and positions
+    /// are reported relative to the file rather than the snippet.
+    pub fn with_offset(source: &'a str, offset: usize, position: (usize, usize)) -> Self {
+        Self::new(source).starting_at(offset, position)
+    }
+
+    /// Creates a new lexer from a named `&str`, e.g. a file and its path
+    ///
+    /// The name is given to the errors and token streams of the lexer, so
+    /// diagnostics can tell which file they're about.
+    pub fn new_named(source: &'a str, name: impl Into<Arc<str>>) -> Self {
+        Self::new(source).named(name.into())
+    }
+
+    /// Gives the lexer's source a name
+    pub(crate) fn named(mut self, name: Arc<str>) -> Self {
+        #[cfg(feature = "tracing")]
+        self.span.record("name", &*name);
+        self.name = Some(name);
+        self
+    }
+
+    /// The name the source was given, if any
+    #[inline]
+    pub fn source_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The configured keyword closest to an unknown identifier, for
+    /// suggesting "did you mean `while`?"
+    ///
+    /// Matches case as the lexer matches keywords. See
+    /// [`KeywordTable::suggest`].
+    pub fn suggest_keyword(&self, ident: &str) -> Option<&str> {
+        if self.config.case_insensitive_keywords {
+            self.config.keywords.suggest_ignore_case(ident)
+        } else {
+            self.config.keywords.suggest(ident)
         }
     }
 
+    /// Moves the start of the lexer to a byte offset and position in a file
+    pub(crate) fn starting_at(mut self, offset: usize, (row, col): (usize, usize)) -> Self {
+        let (row_base, col_base) = self.config.base;
+        self.offset = offset;
+        self.row = row.saturating_sub(row_base) + 1;
+        self.col = col.saturating_sub(col_base) + 1;
+        self.origin = (self.row, self.col);
+        self
+    }
+
     /// The lexer's position in the file
     #[inline]
     pub fn pos(&self) -> (usize, usize) {
-        (self.row, self.col)
+        let (row_base, col_base) = self.config.base;
+        (self.row - 1 + row_base, self.col - 1 + col_base)
+    }
+
+    /// The comments lexed so far, if the lexer was configured to collect
+    /// them, as `Comment` tokens
+    ///
+    /// Comments in tokens already peeked count as lexed. Iterate over the
+    /// lexer with [`Iterator::by_ref`] to keep it for reading them after.
+    #[inline]
+    pub fn comments(&self) -> &[Token<'a>] {
+        &self.comments
+    }
+
+    /// Takes the comments collected so far, leaving none
+    #[inline]
+    pub fn take_comments(&mut self) -> Vec<Token<'a>> {
+        core::mem::take(&mut self.comments)
+    }
+
+    /// The warnings found so far, if the lexer was configured to check for
+    /// them
+    ///
+    /// Warnings about tokens already peeked count as found.
+    #[inline]
+    pub fn warnings(&self) -> &[LexWarning] {
+        &self.warnings
+    }
+
+    /// Takes the warnings found so far, leaving none
+    #[inline]
+    pub fn take_warnings(&mut self) -> Vec<LexWarning> {
+        core::mem::take(&mut self.warnings)
+    }
+
+    /// Is a `-` lexed next in prefix position, following no operand?
+    ///
+    /// The token before it is the last one lexed, peeked tokens included,
+    /// and is judged by [`TokenKind::ends_operand`].
+    #[inline]
+    pub fn in_prefix_position(&self) -> bool {
+        !self.after_operand
     }
 
     /// Is the lexer over?
     #[inline]
     pub fn is_over(&self) -> bool {
-        self.prev == '\0'
+        self.eof
+    }
+
+    /// The lexer's byte offset in the file
+    #[inline]
+    pub fn byte_offset(&self) -> usize {
+        self.offset + self.pos
+    }
+
+    /// The source the lexer hasn't read yet
+    ///
+    /// Tokens already peeked have been read. Together with
+    /// [`Lexer::advance`] this lets another scanner take over part of the
+    /// source, e.g. an embedded language, before the lexer resumes.
+    #[inline]
+    pub fn remaining(&self) -> &'a str {
+        self.rest()
+    }
+
+    /// Skips `len` bytes of the source, keeping the position up to date
+    ///
+    /// Stops at the end of the source, or past `len` if it falls inside a
+    /// character.
+    pub fn advance(&mut self, len: usize) {
+        self.advance_to(self.pos + len);
+    }
+
+    /// The source string the lexer reads
+    ///
+    /// The lexer's spans are offsets into it, plus the offset given to
+    /// [`Lexer::with_offset`].
+    #[inline]
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The exact source text a span of this lexer's tokens covers
+    ///
+    /// Panics if the span isn't within the source; see [`Lexer::text_of`].
+    #[inline]
+    pub fn text(&self, span: Span) -> &'a str {
+        self.slice(span.start - self.offset, span.end - self.offset)
+    }
+
+    /// The source text a span covers, or `None` if it isn't within the
+    /// source or doesn't fall on character boundaries
+    ///
+    /// For spans from anywhere, e.g. kept from an earlier lexer or built
+    /// by hand.
+    pub fn text_of(&self, span: Span) -> Option<&'a str> {
+        let start = span.start.checked_sub(self.offset)?;
+        let end = span.end.checked_sub(self.offset)?;
+        self.source.get(start..end)
     }
 
     /// Takes a slice of the source file
@@ -85,45 +664,200 @@ impl<'a> Lexer<'a> {
         &self.source[a..b]
     }
 
+    /// The source from the current character on
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    /// Advances the iterator up to a byte offset in the source
+    fn advance_to(&mut self, pos: usize) {
+        if self.eof || pos <= self.pos {
+            return;
+        }
+
+        let mut end = pos.min(self.source.len());
+        while !self.source.is_char_boundary(end) {
+            end += 1;
+        }
+        self.jump_to(end);
+    }
+
+    /// Moves the iterator to a character boundary past the current
+    /// character, finding the rows skipped with vectorized searches
+    fn jump_to(&mut self, end: usize) {
+        let bytes = self.source.as_bytes();
+        let config = &self.config;
+
+        // Only the columns of the last line skipped are counted
+        let mut start = self.pos;
+        for i in memchr::memchr2_iter(b'\n', b'\r', &bytes[self.pos..end]) {
+            let i = self.pos + i;
+            if bytes[i] == b'\n' || config.lone_cr_newlines && bytes.get(i + 1) != Some(&b'\n') {
+                self.row += 1;
+                self.col = 1;
+                start = i + 1;
+            }
+        }
+
+        // The `\r` of a `\r\n` pair is part of the line ending, not the line
+        let line = &self.source[start..end];
+        let line = line.strip_suffix('\r').filter(|_| bytes.get(end) == Some(&b'\n')).unwrap_or(line);
+        let tabs = config.tab_width > 1 && memchr::memchr(b'\t', line.as_bytes()).is_some();
+        match config.encoding {
+            PositionEncoding::Utf8 if !tabs => self.col += line.len(),
+            PositionEncoding::Codepoints if !tabs => self.col += line.chars().count(),
+            encoding => for (i, ch) in line.char_indices() {
+                if starts_column(self.source, start + i + ch.len_utf8(), encoding) {
+                    self.col = next_column(self.col, ch, encoding, config.tab_width);
+                }
+            }
+        }
+
+        self.move_to(end);
+    }
+
+    /// Moves the iterator to a character boundary, leaving the position in
+    /// the file to the caller
+    fn move_to(&mut self, pos: usize) {
+        let mut chars = self.source[pos..].chars();
+        let first = chars.next();
+        self.pos = pos;
+        self.prev = first.unwrap_or('\0');
+        self.eof = first.is_none();
+        self.ahead = chars.next();
+        self.chars = chars;
+    }
+
     /// Advances the iterator, returning the next character
     #[inline]
     pub fn next_char(&mut self) -> Option<char> {
-        if let Some(ch) = self.chars.next() {
+        if self.eof {
+            return None;
+        }
+
+        let next = core::mem::replace(&mut self.ahead, self.chars.next());
+        self.pos += self.prev.len_utf8();
+        let crlf = self.prev == '\r' && next == Some('\n');
+        if self.prev == '\n' || self.prev == '\r' && !crlf && self.config.lone_cr_newlines {
+            self.row += 1;
+            self.col = 1;
+        } else if !crlf && starts_column(self.source, self.pos, self.config.encoding) {
+            // The `\r` of a `\r\n` pair is part of the line ending, not the line
+            self.col = next_column(self.col, self.prev, self.config.encoding, self.config.tab_width);
+        }
+
+        if let Some(ch) = next {
             self.prev = ch;
-            self.pos += ch.len_utf8();
-            self.col += 1;
-            if self.prev == '\n' {
-                self.col = 0;
-                self.row += 1;
-            }
-            Some(self.prev)
         } else {
             self.prev = '\0';
-            None
+            self.eof = true;
         }
+        next
+    }
+
+    /// The character the lexer is on, or `None` at the end of the source
+    #[inline]
+    pub fn current(&self) -> Option<char> {
+        (!self.eof).then_some(self.prev)
     }
 
     /// Peeks the next character in the iterator
     #[inline]
     pub fn peek(&self) -> Option<char> {
-        self.chars.clone().next()
+        self.ahead
     }
 
-    /// Removes an identifier from the start of the source string
-    fn trim_ident(&mut self) -> &'a str {
+    /// Peeks the next token without consuming it
+    ///
+    /// The token is lexed once and kept until [`Iterator::next`] returns it,
+    /// so the lexer's position is already past it.
+    #[inline]
+    pub fn peek_token(&mut self) -> Option<&Token<'a>> {
+        self.peek_nth(0)
+    }
+
+    /// Peeks the token `n` places ahead without consuming anything, 0 being
+    /// the next token
+    ///
+    /// Tokens are lexed once and buffered until [`Iterator::next`] returns
+    /// them, so the lexer's position is already past the last one peeked.
+    pub fn peek_nth(&mut self, n: usize) -> Option<&Token<'a>> {
+        while self.lookahead.len() <= n {
+            let token = self.lex_token()?;
+            self.lookahead.push_back(token);
+        }
+        self.lookahead.get(n)
+    }
+
+    /// Removes an identifier or keyword from the start of the source string
+    fn trim_ident(&mut self) -> TokenKind<'a> {
         let start_pos = self.pos;
 
-        while self.prev.is_alphanumeric() || self.prev == '_' {
+        self.next_char();
+        self.skip_ascii(AsciiClasses::IDENT_CONTINUE);
+        while !self.is_over() && (self.config.ident_continue)(self.prev) {
             self.next_char();
         }
 
-        self.slice(start_pos, self.pos)
+        let text = self.slice(start_pos, self.pos);
+        let ident = self.normalize(text);
+        let is_keyword = if self.config.case_insensitive_keywords {
+            self.config.keywords.get_ignore_case(&ident).is_some()
+        } else {
+            self.config.keywords.contains(&ident)
+        };
+        if is_keyword {
+            TokenKind::Keyword(text)
+        } else {
+            TokenKind::Ident(self.fold_case(ident))
+        }
+    }
+
+    /// Folds an identifier to lowercase if the lexer was configured to
+    fn fold_case(&self, ident: Cow<'a, str>) -> Cow<'a, str> {
+        if !self.config.fold_ident_case || ident.is_ascii() && !ident.bytes().any(|byte| byte.is_ascii_uppercase()) {
+            return ident;
+        }
+
+        let folded = ident.to_lowercase();
+        if folded == ident {
+            ident
+        } else {
+            Cow::Owned(folded)
+        }
+    }
+
+    /// Normalizes an identifier to NFC if the lexer was configured to
+    #[cfg(feature = "unicode")]
+    fn normalize(&self, ident: &'a str) -> Cow<'a, str> {
+        use alloc::string::String;
+        use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+        if !self.config.normalize_idents || is_nfc_quick(ident.chars()) == IsNormalized::Yes {
+            return Cow::Borrowed(ident);
+        }
+
+        let normalized: String = ident.nfc().collect();
+        if normalized == ident {
+            Cow::Borrowed(ident)
+        } else {
+            Cow::Owned(normalized)
+        }
+    }
+
+    /// Normalizes an identifier to NFC if the lexer was configured to
+    #[cfg(not(feature = "unicode"))]
+    #[inline]
+    fn normalize(&self, ident: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(ident)
     }
 
     /// Removes a number literal from the start of the source string
     fn trim_number(&mut self) -> &'a str {
         let start_pos = self.pos;
 
+        self.skip_ascii(AsciiClasses::DIGIT);
         while self.prev.is_numeric() {
             self.next_char();
         }
@@ -131,98 +865,402 @@ impl<'a> Lexer<'a> {
         self.slice(start_pos, self.pos)
     }
 
+    /// Is the current `-` the sign of a negative literal?
+    #[inline]
+    fn at_negative_literal(&self) -> bool {
+        self.config.negative_literals
+            && !self.after_operand
+            && self.prev == '-'
+            && self.peek().is_some_and(|ch| ch.is_ascii_digit())
+    }
+
+    /// Removes a number with the `-` before it folded in
+    fn trim_negative_number(&mut self) -> TokenKind<'a> {
+        let start_pos = self.pos;
+        self.next_char();
+
+        match self.config.number_rule.and_then(|rule| self.trim_rule(rule)) {
+            Some(TokenKind::Num(value)) => TokenKind::Num(value.wrapping_neg()),
+            Some(TokenKind::Float(_)) => TokenKind::Float(self.slice(start_pos, self.pos)),
+            Some(kind) => kind,
+            None => {
+                self.trim_number();
+                self.integer(self.slice(start_pos, self.pos))
+            }
+        }
+    }
+
+    /// The token for an integer lexed by the built-in rules, which may have
+    /// a sign and non-ASCII digits
+    fn integer(&self, text: &str) -> TokenKind<'a> {
+        if text.is_ascii() {
+            return TokenKind::Num(text.parse().unwrap_or(0));
+        }
+
+        let mut converted = String::with_capacity(text.len());
+        for ch in text.chars() {
+            match digits::decimal_value(ch) {
+                _ if ch.is_ascii() => converted.push(ch),
+                Some(value) if self.config.convert_digits => converted.extend(char::from_digit(value, 10)),
+                _ => return TokenKind::Error(LexErrorKind::NonAsciiDigit(ch))
+            }
+        }
+        TokenKind::Num(converted.parse().unwrap_or(0))
+    }
+
+    /// Removes a string literal from the start of the source string
+    fn trim_string(&mut self) -> TokenKind<'a> {
+        let quote = self.prev;
+        self.next_char();
+        let start_pos = self.pos;
+
+        loop {
+            if self.prev == quote {
+                if !self.config.doubled_quotes || self.peek() != Some(quote) {
+                    break;
+                }
+                self.next_char();
+            } else if self.is_over() {
+                return TokenKind::Error(LexErrorKind::UnterminatedString);
+            } else if self.prev == '\\' && !self.config.doubled_quotes {
+                self.next_char();
+            }
+            self.next_char();
+        }
+
+        let contents = self.slice(start_pos, self.pos);
+        self.next_char();
+        TokenKind::Str(contents)
+    }
+
+    /// Removes the longest operator from the start of the source string
+    fn trim_operator(&mut self) -> Option<TokenKind<'a>> {
+        let start_pos = self.pos;
+        let (len, op) = self.config.operators.longest_match(self.rest())?;
+
+        self.advance_to(start_pos + len);
+        Some(match op {
+            Some(op) => TokenKind::Opr(op),
+            None => TokenKind::Punct(self.slice(start_pos, self.pos))
+        })
+    }
+
+    /// Is the current character the start of a line ending?
+    #[inline]
+    fn at_line_end(&self) -> bool {
+        match self.prev {
+            '\n' => true,
+            '\r' => self.config.lone_cr_newlines || self.peek() == Some('\n'),
+            _ => false
+        }
+    }
+
     /// Removes a comment from the start of the source string
-    fn trim_comment(&mut self) {
-        while self.prev != '\n' {
+    ///
+    /// Returns whether there was a comment, or an error if a block comment
+    /// isn't closed.
+    fn trim_comment(&mut self) -> Result<bool, LexErrorKind> {
+        let rest = self.rest();
+
+        if self.config.line_comments.iter().any(|prefix| rest.starts_with(prefix.as_str())) {
+            let bytes = self.source.as_bytes();
+            let end = memchr::memchr2_iter(b'\n', b'\r', &bytes[self.pos..])
+                .map(|i| self.pos + i)
+                .find(|&i| bytes[i] == b'\n' || self.config.lone_cr_newlines || bytes.get(i + 1) == Some(&b'\n'))
+                .unwrap_or(bytes.len());
+            self.advance_to(end);
+            return Ok(true);
+        }
+
+        let block_end = self.config.block_comments
+            .iter()
+            .find(|(open, _)| rest.starts_with(open.as_str()))
+            .map(|(open, close)| {
+                memchr::memmem::find(&rest.as_bytes()[open.len()..], close.as_bytes())
+                    .map(|i| open.len() + i + close.len())
+            });
+
+        match block_end {
+            Some(Some(len)) => {
+                self.advance_to(self.pos + len);
+                Ok(true)
+            },
+            Some(None) => {
+                self.advance_to(self.source.len());
+                Err(LexErrorKind::UnterminatedComment)
+            },
+            None => Ok(false)
+        }
+    }
+
+    /// Removes a token matched by the configured fallback rule
+    fn trim_fallback(&mut self) -> Option<TokenKind<'a>> {
+        self.trim_rule(self.config.fallback?)
+    }
+
+    /// Removes a token matched by a rule, which covers at least the
+    /// character it starts at
+    fn trim_rule(&mut self, rule: for<'r> fn(&mut Lexer<'r>) -> Option<TokenKind<'r>>) -> Option<TokenKind<'a>> {
+        let start = self.pos;
+        let kind = rule(self)?;
+        if self.pos == start {
             self.next_char();
         }
+        Some(kind)
+    }
+
+    /// Removes the next token from the start of the source string
+    fn trim_token(&mut self) -> TokenKind<'a> {
+        match self.token_start() {
+            TokenStart::String => self.trim_string(),
+            TokenStart::Ident => self.trim_ident(),
+            TokenStart::Number => match self.config.number_rule.and_then(|rule| self.trim_rule(rule)) {
+                Some(kind) => kind,
+                None => {
+                    let number = self.trim_number();
+                    self.integer(number)
+                }
+            },
+            TokenStart::Other if self.at_negative_literal() => self.trim_negative_number(),
+            TokenStart::OpeningBracket => {
+                self.next_char();
+                TokenKind::OpeningBracket
+            },
+            TokenStart::ClosingBracket => {
+                self.next_char();
+                TokenKind::ClosingBracket
+            },
+            TokenStart::Other => if let Some(kind) = self.trim_operator() {
+                kind
+            } else if let Some(kind) = self.trim_fallback() {
+                kind
+            } else {
+                let ch = self.prev;
+                self.next_char();
+                TokenKind::Error(LexErrorKind::UnexpectedChar(ch))
+            }
+        }
+    }
+
+    /// Creates a token lexed from a byte offset up to the current character
+    #[inline]
+    fn token(&self, kind: TokenKind<'a>, position: (usize, usize), start: usize) -> Token<'a> {
+        let span = Span { start: self.offset + start, end: self.offset + self.pos };
+        Token { kind, position, span }
+    }
+
+    /// Replaces a token with an error if it goes past a configured limit
+    ///
+    /// `modes` is the number of modes the lexer was in before the token.
+    fn check_limits(&mut self, kind: TokenKind<'a>, start: usize, modes: usize) -> TokenKind<'a> {
+        if self.config.max_token_len.is_some_and(|limit| self.pos - start > limit) {
+            return TokenKind::Error(LexErrorKind::TokenTooLong);
+        }
+        let Some(limit) = self.config.max_nesting else {
+            return kind;
+        };
+
+        let opened = Delimiter::opened_by(&kind).is_some();
+        if opened {
+            self.depth += 1;
+        } else if Delimiter::closed_by(&kind).is_some() {
+            self.depth = self.depth.saturating_sub(1);
+        }
+        if (opened || self.modes.len() > modes) && self.depth + self.modes.len() > limit {
+            return TokenKind::Error(LexErrorKind::TooDeeplyNested);
+        }
+        kind
+    }
+
+    /// Passes a token to the configured observer
+    #[inline]
+    fn observed(&self, token: Token<'a>) -> Token<'a> {
+        if let Some(observer) = &self.config.observer {
+            (observer.0)(&token);
+        }
+        token
     }
 
     /// Trims whitespace from the start of the string
+    ///
+    /// In a directive, or when the end of the line is a token, whitespace is
+    /// only trimmed up to the end of the line. Line breaks between lines of
+    /// layout are trimmed even if they aren't whitespace.
     fn trim_whitespace(&mut self) {
-        while self.prev.is_whitespace() {
-            self.next_char();
+        let directive = *self.mode() == Mode::Directive || self.keeps_line_end();
+        let layout = self.tracks_layout();
+        loop {
+            self.skip_ascii(AsciiClasses::WHITESPACE);
+            while !self.is_over() && ((self.config.is_whitespace)(self.prev) || layout && self.at_line_end()) {
+                if directive && self.at_line_end() {
+                    break;
+                }
+                self.next_char();
+            }
+            if !self.trim_line_continuation() {
+                break;
+            }
         }
     }
-}
 
-impl<'a> Iterator for Lexer<'a> {
-    type Item = Token<'a>;
+    /// Removes a `\` and the line break after it, if line continuations are
+    /// enabled, returning whether there was one
+    fn trim_line_continuation(&mut self) -> bool {
+        if !self.config.line_continuations || self.is_over() || self.prev != '\\' {
+            return false;
+        }
+        let after = &self.source.as_bytes()[self.pos + 1..];
+        let len = match after {
+            [b'\r', b'\n', ..] => 2,
+            [b'\n', ..] => 1,
+            [b'\r', ..] if self.config.lone_cr_newlines => 1,
+            _ => return false
+        };
+        self.advance_to(self.pos + 1 + len);
+        true
+    }
 
-    fn next(&mut self) -> Option<Token<'a>> {
-        loop {
-            self.trim_whitespace();
-            let position = self.pos();
+    /// Has lexing been cancelled?
+    ///
+    /// Only asks the callback once every [`CANCEL_INTERVAL`] tokens.
+    fn cancelled(&mut self) -> bool {
+        let Some(cancel) = &self.config.cancel else {
+            return false;
+        };
+        if self.until_cancel_check > 0 {
+            self.until_cancel_check -= 1;
+            return false;
+        }
+        self.until_cancel_check = CANCEL_INTERVAL - 1;
+        (cancel.0)()
+    }
 
-            let kind = match self.prev {
-                'a'..='z' | 'A'..='Z' | '_' => Some(TokenKind::Ident(self.trim_ident())),
-                '0'..='9' => Some(TokenKind::Num(self.trim_number().parse().unwrap_or(0))),
-                '+' => {
-                    self.next_char();
-                    Some(TokenKind::Opr(Op::Plus))
-                },
-                '-' => {
-                    self.next_char();
-                    Some(TokenKind::Opr(Op::Minus))
-                },
-                '*' => {
-                    self.next_char();
-                    Some(TokenKind::Opr(Op::Multiply))
-                },
-                '/' => {
-                    self.next_char();
-                    if self.prev == '/' {
-                        self.trim_comment();
-                        continue;
-                    } else {
-                        Some(TokenKind::Opr(Op::Divide))
-                    }
-                },
-                '%' => {
-                    self.next_char();
-                    Some(TokenKind::Opr(Op::Modulo))
-                },
-                '=' => {
-                    self.next_char();
-                    Some(TokenKind::Opr(Op::Equal))
-                },
-                '>' => {
-                    self.next_char();
-                    if self.prev == '=' {
-                        self.next_char();
-                        Some(TokenKind::Opr(Op::GreaterOrEqual))
-                    } else {
-                        Some(TokenKind::Opr(Op::Greater))
-                    }
-                },
-                '<' => {
-                    self.next_char();
-                    if self.prev == '=' {
-                        self.next_char();
-                        Some(TokenKind::Opr(Op::LessOrEqual))
-                    } else if self.prev == '>' {
-                        self.next_char();
-                        Some(TokenKind::Opr(Op::NotEqual))
-                    } else {
-                        Some(TokenKind::Opr(Op::Less))
+    /// Lexes the next token from the source
+    fn lex_token(&mut self) -> Option<Token<'a>> {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.clone().entered();
+        let too_long = self.config.max_source_len.is_some_and(|limit| self.source.len() > limit);
+        if (too_long || self.cancelled()) && !self.is_over() {
+            let (position, start) = (self.pos(), self.pos - core::mem::take(&mut self.bom));
+            self.advance_to(self.source.len());
+            let kind = TokenKind::Error(if too_long { LexErrorKind::SourceTooLong } else { LexErrorKind::Cancelled });
+            if self.config.stats {
+                self.tally(&kind, position);
+            }
+            return Some(self.token(kind, position, start));
+        }
+        loop {
+            if !self.in_text_mode() {
+                let position = self.pos();
+                let start = self.pos - core::mem::take(&mut self.bom);
+                #[cfg(feature = "profiling")]
+                let started = std::time::Instant::now();
+                self.trim_whitespace();
+                #[cfg(feature = "profiling")]
+                if self.pos > start {
+                    self.profile.record(TokenClass::Whitespace, started);
+                }
+                if self.config.trivia && self.pos > start {
+                    let kind = TokenKind::Whitespace(self.slice(start, self.pos));
+                    if self.config.stats {
+                        self.tally(&kind, position);
                     }
-                },
-                '(' => {
-                    self.next_char();
-                    Some(TokenKind::OpeningBracket)
-                },
-                ')' => {
-                    self.next_char();
-                    Some(TokenKind::ClosingBracket)
-                },
-                _ => None
-            };
+                    return Some(self.observed(self.token(kind, position, start)));
+                }
+            } else if self.bom > 0 {
+                // A byte-order mark is host text like any other
+                let bom = core::mem::take(&mut self.bom);
+                self.move_to(self.pos - bom);
+            }
+            if self.end_mode() {
+                continue;
+            }
+            let position = self.pos();
+            let start = self.pos;
+            let modes = self.modes.len();
+            #[cfg(feature = "profiling")]
+            let started = std::time::Instant::now();
 
-            return if let Some(kind) = kind {
-                Some(Token { kind, position })
+            let kind = if self.is_over() {
+                self.unterminated_mode().or_else(|| self.trim_layout_end())?
+            } else if let Some(kind) = self.trim_newline() {
+                kind
+            } else if let Some(kind) = self.trim_mode_token() {
+                kind
             } else {
-                None
+                match self.trim_comment() {
+                    Ok(true) => {
+                        let kind = TokenKind::Comment(self.slice(start, self.pos));
+                        if self.config.collect_comments {
+                            self.comments.push(self.token(kind.clone(), position, start));
+                        }
+                        if self.config.trivia {
+                            kind
+                        } else {
+                            if self.config.stats {
+                                self.tally(&kind, position);
+                            }
+                            #[cfg(feature = "profiling")]
+                            self.profile.record(TokenClass::Comment, started);
+                            if self.config.observer.is_some() {
+                                self.observed(self.token(kind, position, start));
+                            }
+                            continue;
+                        }
+                    },
+                    Ok(false) => match self.trim_indentation() {
+                        Some(kind) => kind,
+                        None => self.trim_token()
+                    },
+                    Err(err) => TokenKind::Error(err)
+                }
+            };
+            let kind = self.check_limits(kind, start, modes);
+            if self.config.warnings || !self.config.naming_conventions.is_empty() {
+                self.check_token(&kind, position, start);
             }
+            self.track_layout(&kind, modes);
+            if self.config.stats {
+                self.tally(&kind, position);
+            }
+            #[cfg(feature = "profiling")]
+            self.profile.record(TokenClass::of(&kind), started);
+            #[cfg(feature = "tracing")]
+            if let TokenKind::Error(err) = &kind {
+                tracing::debug!(error = %err, row = position.0, col = position.1, "lexical error");
+            }
+            if !kind.is_trivia() {
+                self.after_operand = kind.ends_operand();
+            }
+
+            return Some(self.observed(self.token(kind, position, start)));
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        self.lookahead.pop_front().or_else(|| self.lex_token())
+    }
+
+    /// Tokens consume at least a byte, except for layout tokens and the
+    /// errors closing interpolated strings left open, of which there are at
+    /// most one per mode entered
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let peeked = self.lookahead.len();
+        if self.config.layout() {
+            return (peeked, None);
         }
+
+        // A byte per token, and a closing error per mode, whether entered
+        // already or by one of the remaining tokens, of which there are at
+        // most as many as bytes
+        let bytes = self.source.len() - self.pos + self.bom;
+        let upper = bytes.checked_mul(2).and_then(|n| n.checked_add(peeked + self.modes.len()));
+        (peeked, upper)
     }
 }
+
+impl FusedIterator for Lexer<'_> {}
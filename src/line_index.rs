@@ -0,0 +1,335 @@
+//! Conversion between byte offsets and `(row, col)` positions.
+use crate::BOM;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "unicode")]
+use unicode_segmentation::GraphemeCursor;
+
+/// The unit columns are counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PositionEncoding {
+    /// UTF-8 bytes
+    Utf8,
+    /// UTF-16 code units, as used by the language server protocol
+    Utf16,
+    /// Unicode codepoints
+    #[default]
+    Codepoints,
+    /// Extended grapheme clusters, as editors display them
+    #[cfg(feature = "unicode")]
+    Graphemes
+}
+
+impl PositionEncoding {
+    /// The number of columns a character spans in this encoding
+    ///
+    /// With `PositionEncoding::Graphemes` this is the width of a whole
+    /// cluster, which only the last character of the cluster advances by.
+    #[inline]
+    pub fn len(self, ch: char) -> usize {
+        match self {
+            Self::Utf8 => ch.len_utf8(),
+            Self::Utf16 => ch.len_utf16(),
+            Self::Codepoints => 1,
+            #[cfg(feature = "unicode")]
+            Self::Graphemes => 1
+        }
+    }
+}
+
+/// Does the character at a byte offset start a new column?
+#[cfg(feature = "unicode")]
+#[inline]
+pub(crate) fn starts_column(source: &str, offset: usize, encoding: PositionEncoding) -> bool {
+    encoding != PositionEncoding::Graphemes
+        || GraphemeCursor::new(offset, source.len(), true)
+            .is_boundary(source, 0)
+            .unwrap_or(true)
+}
+
+/// Does the character at a byte offset start a new column?
+#[cfg(not(feature = "unicode"))]
+#[inline]
+pub(crate) fn starts_column(_source: &str, _offset: usize, _encoding: PositionEncoding) -> bool {
+    true
+}
+
+/// The column following a character at a 1-based column
+///
+/// Tabs advance to the next multiple of `tab_width`; a width of 0 or 1 counts
+/// them as a single column.
+#[inline]
+pub(crate) fn next_column(col: usize, ch: char, encoding: PositionEncoding, tab_width: usize) -> usize {
+    if ch == '\t' && tab_width > 1 {
+        col + tab_width - (col - 1) % tab_width
+    } else {
+        col + encoding.len(ch)
+    }
+}
+
+/// Finds the byte offset at which each line of a source string starts
+fn line_starts(source: &str, lone_cr_newlines: bool) -> Vec<usize> {
+    let bytes = source.as_bytes();
+    let mut starts = vec![0];
+
+    for (i, &byte) in bytes.iter().enumerate() {
+        let lone_cr = byte == b'\r' && lone_cr_newlines && bytes.get(i + 1) != Some(&b'\n');
+        if byte == b'\n' || lone_cr {
+            starts.push(i + 1);
+        }
+    }
+
+    starts
+}
+
+/// Maps byte offsets in a source string to `(row, col)` positions and back
+///
+/// Positions are 1-based unless configured otherwise with
+/// [`LineIndex::with_base`].
+///
+/// The start of every line is recorded once up front, so each conversion is a
+/// binary search over the lines followed by a walk over a single line.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    /// The source string being indexed
+    source: &'a str,
+    /// The byte offset at which each line starts
+    line_starts: Vec<usize>,
+    /// The unit columns are counted in
+    encoding: PositionEncoding,
+    /// The distance between tab stops
+    tab_width: usize,
+    /// Whether a `\r` not followed by `\n` ends a line
+    lone_cr_newlines: bool,
+    /// The numbers the first row and column are reported as
+    base: (usize, usize)
+}
+
+impl<'a> LineIndex<'a> {
+    /// Creates a new line index from a `&str`
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            line_starts: line_starts(source, false),
+            encoding: PositionEncoding::default(),
+            tab_width: 1,
+            lone_cr_newlines: false,
+            base: (1, 1)
+        }
+    }
+
+    /// Sets the numbers the first row and column are reported as
+    pub fn with_base(mut self, row: usize, col: usize) -> Self {
+        self.base = (row, col);
+        self
+    }
+
+    /// Sets the unit columns are counted in
+    pub fn with_encoding(mut self, encoding: PositionEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the distance between tab stops, 1 counting tabs as one column
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    /// Sets whether a `\r` not followed by `\n` ends a line
+    pub fn with_lone_cr_newlines(mut self, enabled: bool) -> Self {
+        if self.lone_cr_newlines != enabled {
+            self.lone_cr_newlines = enabled;
+            self.line_starts = line_starts(self.source, enabled);
+        }
+        self
+    }
+
+    /// The number of lines in the source
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The byte range of a 0-based line, excluding its line terminator
+    ///
+    /// A byte-order mark at the start of the source isn't part of any line.
+    pub(crate) fn line_range(&self, line: usize) -> (usize, usize) {
+        let start = match self.line_starts[line] {
+            0 if self.source.starts_with(BOM) => BOM.len_utf8(),
+            start => start
+        };
+        let end = match self.line_starts.get(line + 1) {
+            Some(&next) if self.source[start..next].ends_with("\r\n") => next - 2,
+            Some(&next) => next - 1,
+            None => self.source.len()
+        };
+        (start, end)
+    }
+
+    /// The 0-based line a byte offset is on
+    #[inline]
+    pub(crate) fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// The byte offset a 0-based line starts at
+    #[inline]
+    pub(crate) fn line_start(&self, line: usize) -> usize {
+        self.line_starts[line]
+    }
+
+    /// Does the index break a source string into lines the same way?
+    #[inline]
+    pub(crate) fn indexes(&self, source: &str, lone_cr_newlines: bool) -> bool {
+        core::ptr::eq(self.source, source) && self.lone_cr_newlines == lone_cr_newlines
+    }
+
+    /// Does the character at a byte offset start a new column?
+    #[inline]
+    fn starts_column(&self, offset: usize) -> bool {
+        starts_column(self.source, offset, self.encoding)
+    }
+
+    /// Converts a byte offset into a `(row, col)` position
+    ///
+    /// Offsets within a line terminator map to the column just past the end
+    /// of the line, and a leading byte-order mark takes up no columns.
+    /// Returns `None` if the offset is past the end of the source or doesn't
+    /// fall on a character boundary.
+    pub fn position(&self, offset: usize) -> Option<(usize, usize)> {
+        if !self.source.is_char_boundary(offset) {
+            return None;
+        }
+
+        let line = self.line_of(offset);
+        let (start, end) = self.line_range(line);
+        let mut col = 1;
+
+        for (i, ch) in self.source[start.min(offset)..offset.min(end)].char_indices() {
+            if self.starts_column(start + i + ch.len_utf8()) {
+                col = next_column(col, ch, self.encoding, self.tab_width);
+            }
+        }
+
+        Some((line + self.base.0, col - 1 + self.base.1))
+    }
+
+    /// Converts a `(row, col)` position into a byte offset
+    ///
+    /// The column just past the end of a line maps to its line terminator.
+    /// Returns `None` for positions outside the source or in the middle of a
+    /// character.
+    pub fn offset(&self, (row, col): (usize, usize)) -> Option<usize> {
+        let line = row.checked_sub(self.base.0).filter(|&line| line < self.line_count())?;
+        let col = col.checked_sub(self.base.1)? + 1;
+        let (start, end) = self.line_range(line);
+        let mut current = 1;
+
+        for (i, ch) in self.source[start..end].char_indices() {
+            if current >= col {
+                return (current == col).then_some(start + i);
+            }
+            if self.starts_column(start + i + ch.len_utf8()) {
+                current = next_column(current, ch, self.encoding, self.tab_width);
+            }
+        }
+
+        (current == col).then_some(end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks every position of a source maps to an offset and back
+    fn assert_inverse(index: &LineIndex) {
+        for offset in (0..=index.source.len()).filter(|&offset| index.source.is_char_boundary(offset)) {
+            let position = index.position(offset).unwrap();
+            let back = index.offset(position).and_then(|back| index.position(back));
+            assert_eq!(back, Some(position), "offset {offset}");
+        }
+    }
+
+    #[test]
+    fn encodings() {
+        let source = "aé😀b\nx";
+        let b = source.find('b').unwrap();
+        for (encoding, col) in [(PositionEncoding::Utf8, 8), (PositionEncoding::Utf16, 5), (PositionEncoding::Codepoints, 4)] {
+            let index = LineIndex::new(source).with_encoding(encoding);
+            assert_eq!(index.position(b), Some((1, col)));
+            assert_eq!(index.offset((1, col)), Some(b));
+            assert_eq!(index.position(source.len()), Some((2, 2)));
+            assert_inverse(&index);
+        }
+        let index = LineIndex::new(source).with_encoding(PositionEncoding::Utf8);
+        assert_eq!(index.position(2), None);
+        assert_eq!(index.offset((1, 3)), None);
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn graphemes() {
+        let index = LineIndex::new("e\u{301}x 👍🏽!").with_encoding(PositionEncoding::Graphemes);
+        assert_eq!(index.position(3), Some((1, 2)));
+        assert_eq!(index.offset((1, 2)), Some(3));
+        assert_eq!(index.position(index.source.len() - 1), Some((1, 5)));
+        assert_eq!(index.offset((1, 5)), Some(index.source.len() - 1));
+        assert_inverse(&index);
+    }
+
+    #[test]
+    fn tab_stops() {
+        let index = LineIndex::new("\tx\n  \ty\na\tb").with_tab_width(4);
+        assert_eq!(index.position(1), Some((1, 5)));
+        assert_eq!(index.position(6), Some((2, 5)));
+        assert_eq!(index.position(10), Some((3, 5)));
+        assert_eq!(index.offset((2, 5)), Some(6));
+        assert_eq!(index.offset((2, 4)), None);
+        assert_inverse(&index);
+    }
+
+    #[test]
+    fn crlf() {
+        let index = LineIndex::new("a\r\nb\r\n");
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(index.position(1), Some((1, 2)));
+        assert_eq!(index.position(2), Some((1, 2)));
+        assert_eq!(index.position(3), Some((2, 1)));
+        assert_eq!(index.offset((1, 2)), Some(1));
+        assert_eq!(index.offset((1, 3)), None);
+        assert_inverse(&index);
+    }
+
+    #[test]
+    fn lone_cr() {
+        let index = LineIndex::new("a\rb");
+        assert_eq!(index.position(2), Some((1, 3)));
+        let index = index.with_lone_cr_newlines(true);
+        assert_eq!(index.position(2), Some((2, 1)));
+        assert_eq!(index.offset((2, 1)), Some(2));
+        assert_inverse(&index);
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let index = LineIndex::new("\u{FEFF}ab\nc");
+        assert_eq!(index.position(0), Some((1, 1)));
+        assert_eq!(index.position(3), Some((1, 1)));
+        assert_eq!(index.position(4), Some((1, 2)));
+        assert_eq!(index.offset((1, 1)), Some(3));
+        assert_inverse(&index);
+    }
+
+    #[test]
+    fn base() {
+        let index = LineIndex::new("\u{FEFF}ab\nc").with_base(0, 0);
+        assert_eq!(index.position(4), Some((0, 1)));
+        assert_eq!(index.position(6), Some((1, 0)));
+        assert_eq!(index.offset((0, 0)), Some(3));
+        assert_eq!(index.offset((2, 0)), None);
+        assert_inverse(&index);
+        assert_eq!(LineIndex::new("x").with_base(1, 1).offset((0, 1)), None);
+    }
+}
@@ -0,0 +1,54 @@
+//! Lints for suspicious identifiers.
+use crate::{Span, Token, TokenKind};
+use alloc::vec::Vec;
+use unicode_security::{skeleton, MixedScript};
+
+/// The different kinds of suspicious identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdentLintKind {
+    /// Characters from more than one script, e.g. Latin and Cyrillic
+    MixedScript,
+    /// A non-ASCII character that looks like ASCII text
+    Confusable(char)
+}
+
+/// A suspicious identifier found by [`check_idents`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentLint {
+    /// What is suspicious about the identifier
+    pub kind: IdentLintKind,
+    /// The identifier's position in file
+    pub position: (usize, usize),
+    /// The bytes the identifier was lexed from
+    pub span: Span
+}
+
+/// Does a character look like ASCII text without being ASCII?
+pub(crate) fn is_confusable(ch: char) -> bool {
+    let mut buf = [0; 4];
+    !ch.is_ascii() && skeleton(ch.encode_utf8(&mut buf)).all(|ch| ch.is_ascii())
+}
+
+/// Checks identifiers for mixed scripts and characters confusable with ASCII
+///
+/// This guards languages against homoglyph attacks, where two identifiers
+/// look the same but aren't.
+pub fn check_idents<'t, 'a: 't>(tokens: impl IntoIterator<Item = &'t Token<'a>>) -> Vec<IdentLint> {
+    let mut lints = Vec::new();
+
+    for token in tokens {
+        let TokenKind::Ident(ident) = &token.kind else {
+            continue;
+        };
+        let mut lint = |kind| lints.push(IdentLint { kind, position: token.position, span: token.span });
+
+        if !ident.is_single_script() {
+            lint(IdentLintKind::MixedScript);
+        }
+        if let Some(ch) = ident.chars().find(|&ch| is_confusable(ch)) {
+            lint(IdentLintKind::Confusable(ch));
+        }
+    }
+
+    lints
+}
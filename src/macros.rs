@@ -0,0 +1,171 @@
+//! Declarative lexer definitions and token literals.
+
+/// Defines a specialized lexer from a list of token patterns
+///
+/// Each rule maps a pattern to the token it produces:
+///
+/// - `"<="` matches a literal string, producing the expression after `=>`
+/// - `[start]` matches a run of characters satisfying `start`
+/// - `[start, continue]` matches a character satisfying `start` followed by a
+///   run satisfying `continue`
+/// - `{callback}` matches however many bytes `callback` returns for the
+///   rest of the source, with 0 meaning no match
+///
+/// Rules other than literals produce the result of calling the closure after
+/// `=>` with the matched text. The longest match wins, ties going to the rule
+/// declared first. Characters matching the optional `skip` predicate
+/// separate tokens, and a character no rule matches produces the `error`
+/// token. Both come before the rules.
+///
+/// ```rust
+/// use reusable_lexer::{lexer, Span};
+///
+/// #[derive(Debug, PartialEq)]
+/// pub enum Tok<'a> {
+///     Plus,
+///     LessOrEqual,
+///     Num(u32),
+///     Ident(&'a str),
+///     Error
+/// }
+///
+/// lexer! {
+///     pub struct Calc<'a> -> Tok<'a> {
+///         skip char::is_whitespace;
+///         error Tok::Error;
+///         "+" => Tok::Plus,
+///         "<=" => Tok::LessOrEqual,
+///         [|ch: char| ch.is_ascii_digit()] => |text: &'a str| Tok::Num(text.parse().unwrap()),
+///         [char::is_alphabetic, char::is_alphanumeric] => Tok::Ident
+///     }
+/// }
+///
+/// let tokens: Vec<_> = Calc::new("x1 + 42 <= y ?").map(|(token, _)| token).collect();
+/// assert_eq!(tokens, [Tok::Ident("x1"), Tok::Plus, Tok::Num(42), Tok::LessOrEqual, Tok::Ident("y"), Tok::Error]);
+/// assert_eq!(Calc::new("  <=").next(), Some((Tok::LessOrEqual, Span { start: 2, end: 4 })));
+/// ```
+///
+/// The generated lexer is created with `Calc::new(source)` and iterates over
+/// the tokens along with their [`Span`](crate::Span)s.
+#[macro_export]
+macro_rules! lexer {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident<$lt:lifetime> -> $token:ty {
+            $(skip $skip:expr;)?
+            error $error:expr;
+            $($rule:tt => $action:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        $vis struct $name<$lt> {
+            /// The source string being read
+            source: &$lt str,
+            /// The utf-8 position in file
+            pos: usize
+        }
+
+        impl<$lt> $name<$lt> {
+            /// Creates a new lexer from a `&str`
+            $vis fn new(source: &$lt str) -> Self {
+                Self { source, pos: 0 }
+            }
+        }
+
+        impl<$lt> Iterator for $name<$lt> {
+            type Item = ($token, $crate::Span);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let rest = &self.source[self.pos..];
+                $(let rest = rest.trim_start_matches($skip);)?
+                let start = self.source.len() - rest.len();
+                let first = rest.chars().next()?;
+
+                let lens: &[usize] = &[$($crate::lexer!(@len $rule, rest)),*];
+                let (rule, len) = lens
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .fold((None, 0), |best, (i, len)| if len > best.1 { (Some(i), len) } else { best });
+                let len = if rule.is_some() { len } else { first.len_utf8() };
+
+                self.pos = start + len;
+                let text: &$lt str = &rest[..len];
+                let span = $crate::Span { start, end: self.pos };
+                let mut rules = 0usize..;
+                $(
+                    if rules.next() == rule {
+                        return Some(($crate::lexer!(@action $rule, $action, text), span));
+                    }
+                )*
+                let _ = (text, rules);
+                Some(($error, span))
+            }
+        }
+    };
+
+    (@len $lit:literal, $rest:expr) => {
+        if $rest.starts_with($lit) { $lit.len() } else { 0 }
+    };
+    (@len [$start:expr], $rest:expr) => {
+        $crate::lexer!(@len [$start, $start], $rest)
+    };
+    (@len [$start:expr, $continue:expr], $rest:expr) => {{
+        let mut chars = $rest.char_indices();
+        match chars.next() {
+            Some((_, ch)) if ($start)(ch) => chars
+                .find(|&(_, ch)| !($continue)(ch))
+                .map_or($rest.len(), |(i, _)| i),
+            _ => 0
+        }
+    }};
+    (@len {$callback:expr}, $rest:expr) => {
+        ($callback)($rest)
+    };
+
+    (@action $lit:literal, $action:expr, $text:expr) => {
+        $action
+    };
+    (@action $rule:tt, $action:expr, $text:expr) => {
+        ($action)($text)
+    };
+}
+
+/// Builds an array of token kinds, or of tokens when each is given a span
+///
+/// Kinds are written as their [`TokenKind`](crate::TokenKind) variant, with
+/// text converted with `Into` and operators named by their
+/// [`Op`](crate::Op) variant. Spans follow an `@`, and positions are found as
+/// by [`Token::new`](crate::Token::new). Arrays compare equal to `Vec`s, so
+/// they can be checked against collected tokens.
+///
+/// ```rust
+/// use reusable_lexer::{tokens, LexerBuilder};
+///
+/// let builder = LexerBuilder::new().keywords(["let"]).newlines(true);
+/// let kinds: Vec<_> = builder.build("let x + 5\n").map(|token| token.kind).collect();
+/// assert_eq!(kinds, tokens![Keyword("let"), Ident("x"), Opr(Plus), Num(5), Newline]);
+///
+/// let tokens: Vec<_> = LexerBuilder::new().build("x + y").collect();
+/// assert_eq!(tokens, tokens![Ident("x") @ 0..1, Opr(Plus) @ 2..3, Ident("y") @ 4..5]);
+/// ```
+#[macro_export]
+macro_rules! tokens {
+    (@kind Opr($op:ident)) => {
+        $crate::TokenKind::Opr($crate::Op::$op)
+    };
+    (@kind $variant:ident($arg:expr)) => {
+        $crate::TokenKind::$variant(::core::convert::Into::into($arg))
+    };
+    (@kind $variant:ident) => {
+        $crate::TokenKind::$variant
+    };
+
+    ($($variant:ident $(($($arg:tt)*))? @ $span:expr),+ $(,)?) => {
+        [$($crate::Token::new($crate::tokens!(@kind $variant $(($($arg)*))?), $span)),+]
+    };
+    ($($variant:ident $(($($arg:tt)*))?),* $(,)?) => {
+        [$($crate::tokens!(@kind $variant $(($($arg)*))?)),*]
+    };
+}
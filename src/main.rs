@@ -1,27 +1,1015 @@
-use reusable_lexer::*;
+//! A command line tool lexing files with the library.
+//!
+//! `reusable_lexer [options] [paths]` prints the tokens of each file, of the
+//! files in directories and matching glob patterns, or of stdin given `-` or
+//! no paths. `reusable_lexer diff [options] <old> <new>` compares the tokens
+//! of two files, `reusable_lexer decode <source> [dump]` prints the tokens of
+//! a source written with `--format bin` and `reusable_lexer explain <code>`
+//! explains an error code such as `L0002`. `--record` writes the tokens of
+//! each file to a golden file beside it, and `--verify` checks them against
+//! it, so changes to a lexer's behavior on a directory of samples show up.
+//! Dependencies only the tool needs are behind features.
+use reusable_lexer::{
+    profiles, HtmlHighlighter, LexError, LexErrorKind, LexerBuilder, Span, Token, TokenKind, TokenStream
+};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::fmt::Write as _;
 use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-macro_rules! elapsed {
-    ($($code:tt)*) => {
-        {
-            let start = std::time::Instant::now();
-            $($code)*
-            println!("{:?}", start.elapsed());
+/// How tokens are printed
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Format {
+    /// Rust's `Debug` formatting
+    #[default]
+    Debug,
+    /// A JSON object per line
+    Json,
+    /// The source with its tokens colored by kind
+    Color,
+    /// A token cache, read back along with the source by the `decode`
+    /// subcommand
+    Bin,
+    /// The source as HTML with its tokens highlighted by CSS class
+    Html,
+    /// The source without comments and with as little whitespace as keeps
+    /// its tokens
+    Min
+}
+
+/// What is done with golden files of tokens
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Golden {
+    /// Each file's tokens are written to its golden file
+    Record,
+    /// Each file's tokens are checked against its golden file
+    Verify
+}
+
+/// The command line arguments
+#[derive(Default)]
+struct Options {
+    paths: Vec<String>,
+    /// Whether files are memory-mapped instead of read into memory
+    mmap: bool,
+    format: Format,
+    /// Whether statistics are printed instead of the tokens
+    stats: bool,
+    /// The categories of tokens kept, all of them if empty
+    only: Vec<String>,
+    /// The categories of tokens left out
+    exclude: Vec<String>,
+    /// Whether files are lexed again whenever they change
+    watch: bool,
+    /// The extensions of the files lexed in directories, all of them if empty
+    extensions: Vec<String>,
+    /// Whether lines of stdin are lexed as they're entered
+    repl: bool,
+    /// How many times each file is lexed to time it, instead of printing its
+    /// tokens
+    bench: Option<usize>,
+    /// The settings files are lexed with, from a profile looked up by name
+    profile: LexerBuilder,
+    /// Whether golden files are recorded or verified instead of printing
+    /// the tokens
+    golden: Option<Golden>
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--mmap" if cfg!(feature = "mmap") => options.mmap = true,
+                "--mmap" => return Err("--mmap requires the `mmap` feature".to_string()),
+                "--stats" => options.stats = true,
+                "--watch" => options.watch = true,
+                "--repl" => options.repl = true,
+                "--record" => options.golden = Some(Golden::Record),
+                "--verify" => options.golden = Some(Golden::Verify),
+                "--bench" => {
+                    let runs = args.next().and_then(|runs| runs.parse().ok()).filter(|&runs| runs > 0);
+                    options.bench = Some(runs.ok_or("--bench needs a number of runs")?);
+                },
+                "--color" => options.format = Format::Color,
+                "--only" => options.only.extend(categories(args.next())?),
+                "--exclude" => options.exclude.extend(categories(args.next())?),
+                "--profile" => {
+                    let name = args.next().ok_or("--profile needs a name")?;
+                    let registry = profiles::Registry::new();
+                    let Some(profile) = registry.get(&name) else {
+                        let names: Vec<&str> = registry.names().collect();
+                        return Err(format!("unknown profile {name}, expected one of {}", names.join(", ")));
+                    };
+                    options.profile = profile.clone();
+                },
+                "--ext" => {
+                    let list = args.next().ok_or("--ext needs a list of extensions")?;
+                    options.extensions.extend(list.split(',').map(|ext| ext.trim_start_matches('.').to_string()));
+                },
+                "--format" => options.format = match args.next().as_deref() {
+                    Some("debug") => Format::Debug,
+                    Some("json") => Format::Json,
+                    Some("bin") => Format::Bin,
+                    Some("html") => Format::Html,
+                    Some("min") => Format::Min,
+                    Some(format) => return Err(format!("unknown format {format}")),
+                    None => return Err("--format needs a value".to_string())
+                },
+                flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+                _ => options.paths.push(arg)
+            }
+        }
+
+        let mut paths = Vec::new();
+        for path in &options.paths {
+            let start = paths.len();
+            options.expand(path, &mut paths)?;
+            if paths.len() == start {
+                return Err(format!("no files match {path}"));
+            }
+        }
+        options.paths = paths;
+        if options.format == Format::Bin && options.paths.len() > 1 {
+            return Err("--format bin writes the tokens of one file".to_string());
+        }
+        Ok(options)
+    }
+
+    /// Adds the files a path argument stands for, recursing into directories
+    /// and expanding glob patterns
+    fn expand(&self, path: &str, paths: &mut Vec<String>) -> Result<(), String> {
+        if path.contains(['*', '?']) {
+            // Only the directories the pattern can match below are walked
+            let base: Vec<&str> = path.split('/').take_while(|component| !component.contains(['*', '?'])).collect();
+            let base = base.join("/");
+            let mut found = Vec::new();
+            walk(if base.is_empty() { "." } else { &base }, &base, &mut found)?;
+            paths.extend(found.into_iter().filter(|found| glob_match(path, found) && self.has_extension(found)));
+        } else if Path::new(path).is_dir() {
+            let mut found = Vec::new();
+            walk(path, path.trim_end_matches('/'), &mut found)?;
+            paths.extend(found.into_iter().filter(|found| self.has_extension(found)));
+        } else {
+            paths.push(path.to_string());
+        }
+        Ok(())
+    }
+
+    /// Does a file found in a directory have one of the extensions asked for?
+    fn has_extension(&self, path: &str) -> bool {
+        self.extensions.is_empty() || Path::new(path)
+            .extension()
+            .is_some_and(|ext| self.extensions.iter().any(|wanted| ext == wanted.as_str()))
+    }
+
+    /// Is a token in the categories asked for?
+    fn keeps(&self, token: &Token) -> bool {
+        let category = category(&token.kind);
+        (self.only.is_empty() || self.only.iter().any(|only| only == category))
+            && !self.exclude.iter().any(|exclude| exclude == category)
+    }
+}
+
+/// The categories tokens can be filtered by
+const CATEGORIES: &[&str] = &[
+    "ident", "keyword", "op", "num", "str", "text", "bracket", "layout", "whitespace", "comment", "error"
+];
+
+/// The category of a kind of token
+fn category(kind: &TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Ident(_) | TokenKind::Symbol(_) => "ident",
+        TokenKind::Keyword(_) => "keyword",
+        TokenKind::Opr(_) | TokenKind::Punct(_) => "op",
+        TokenKind::Num(_) | TokenKind::Float(_) => "num",
+        TokenKind::Str(_) => "str",
+        TokenKind::Text(_) => "text",
+        TokenKind::OpeningBracket | TokenKind::ClosingBracket => "bracket",
+        TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent => "layout",
+        TokenKind::Whitespace(_) => "whitespace",
+        TokenKind::Comment(_) => "comment",
+        TokenKind::Error(_) => "error"
+    }
+}
+
+/// Finds the files in a directory and its subdirectories, sorted by path
+///
+/// Hidden files and directories are skipped. `name` is how the directory
+/// is written in the paths found, empty for the current directory.
+fn walk(dir: &str, name: &str, found: &mut Vec<String>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|err| format!("failed to read {dir}: {err}"))?;
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let path = if name.is_empty() { file_name.to_string() } else { format!("{name}/{file_name}") };
+        if entry.path().is_dir() {
+            walk(&entry.path().to_string_lossy(), &path, found)?;
+        } else {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Does a path match a glob pattern?
+///
+/// `*` matches any characters within a path component, `?` any one
+/// character and a `**` component any number of components.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn components(pattern: &[&str], path: &[&str]) -> bool {
+        match pattern.split_first() {
+            None => path.is_empty(),
+            Some((&"**", rest)) => (0..=path.len()).any(|skipped| components(rest, &path[skipped..])),
+            Some((first, rest)) => path
+                .split_first()
+                .is_some_and(|(name, path)| component(first.as_bytes(), name.as_bytes()) && components(rest, path))
+        }
+    }
+
+    fn component(pattern: &[u8], name: &[u8]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some((b'*', rest)) => (0..=name.len()).any(|skipped| component(rest, &name[skipped..])),
+            Some((b'?', rest)) => !name.is_empty() && component(rest, &name[1..]),
+            Some((ch, rest)) => name.first() == Some(ch) && component(rest, &name[1..])
+        }
+    }
+
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    components(&pattern, &path)
+}
+
+/// Parses a comma-separated list of categories
+fn categories(list: Option<String>) -> Result<Vec<String>, String> {
+    let list = list.ok_or("--only and --exclude need a list of categories")?;
+    list.split(',')
+        .map(|category| if CATEGORIES.contains(&category) {
+            Ok(category.to_string())
+        } else {
+            Err(format!("unknown category {category}, expected one of {}", CATEGORIES.join(", ")))
+        })
+        .collect()
+}
+
+/// The path standing for stdin
+const STDIN: &str = "-";
+
+/// The contents of a file
+enum Source {
+    Read(String),
+    /// A memory-mapped file, whose UTF-8 is validated as it's lexed
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap)
+}
+
+impl Source {
+    /// Reads a file, or stdin if the path is `-`
+    fn open(path: &str, options: &Options) -> io::Result<Self> {
+        if path == STDIN {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source)?;
+            return Ok(Self::Read(source));
+        }
+
+        // Options only allow mapping files with the feature enabled
+        if options.mmap {
+            #[cfg(feature = "mmap")]
+            {
+                let file = fs::File::open(path)?;
+                // SAFETY: the map is only read, and modifying a file while
+                // it's being lexed is unsupported as with any other tool
+                return unsafe { memmap2::Mmap::map(&file) }.map(Self::Mapped);
+            }
+        }
+        fs::read_to_string(path).map(Self::Read)
+    }
+
+    /// Lexes the source, invalid UTF-8 giving error tokens
+    fn lex(&self, builder: &LexerBuilder) -> Vec<Token<'_>> {
+        match self {
+            Self::Read(source) => builder.build(source).collect(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(bytes) => builder.build_from_bytes_lossy(bytes).collect()
+        }
+    }
+
+    /// Lexes the source, only counting its tokens
+    fn count(&self, builder: &LexerBuilder) -> usize {
+        match self {
+            Self::Read(source) => builder.build(source).count(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(bytes) => builder.build_from_bytes_lossy(bytes).count()
+        }
+    }
+
+    /// The source as a string, if it's valid UTF-8
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Read(source) => Some(source),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(bytes) => std::str::from_utf8(bytes).ok()
+        }
+    }
+
+    /// The length of the source in bytes
+    fn len(&self) -> usize {
+        match self {
+            Self::Read(source) => source.len(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(bytes) => bytes.len()
+        }
+    }
+
+    /// The text a span of the source covers
+    fn text(&self, span: Span) -> Cow<'_, str> {
+        match self {
+            Self::Read(source) => Cow::Borrowed(&source[span.start..span.end]),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(bytes) => String::from_utf8_lossy(&bytes[span.start..span.end])
+        }
+    }
+}
+
+/// Writes a string as a JSON string literal
+fn write_json_string(output: &mut String, text: &str) {
+    output.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            ch if ch < ' ' => {
+                let _ = write!(output, "\\u{:04x}", u32::from(ch));
+            },
+            ch => output.push(ch)
+        }
+    }
+    output.push('"');
+}
+
+/// Writes a token as a JSON object on one line
+fn write_json_token(output: &mut String, token: &Token, source: &Source) {
+    let Span { start, end } = token.span;
+    let (line, col) = token.position;
+    output.push_str("{\"kind\":");
+    write_json_string(output, token.kind.name());
+    output.push_str(",\"text\":");
+    write_json_string(output, &source.text(token.span));
+    let _ = write!(output, ",\"span\":{{\"start\":{start},\"end\":{end}}},\"line\":{line},\"col\":{col}");
+    if let TokenKind::Error(err) = &token.kind {
+        output.push_str(",\"error\":");
+        write_json_string(output, &err.to_string());
+    }
+    output.push_str("}\n");
+}
+
+/// The ANSI escape code coloring a kind of token, if it's colored
+fn kind_color(kind: &TokenKind) -> Option<&'static str> {
+    match kind {
+        TokenKind::Keyword(_) => Some("\x1b[1;34m"),
+        TokenKind::Ident(_) | TokenKind::Symbol(_) => Some("\x1b[36m"),
+        TokenKind::Opr(_) | TokenKind::Punct(_) => Some("\x1b[33m"),
+        TokenKind::Num(_) | TokenKind::Float(_) => Some("\x1b[35m"),
+        TokenKind::Str(_) | TokenKind::Text(_) => Some("\x1b[32m"),
+        TokenKind::Comment(_) => Some("\x1b[90m"),
+        TokenKind::Error(_) => Some("\x1b[4;31m"),
+        _ => None
+    }
+}
+
+/// Writes the source with each token colored by its kind
+fn write_colored(output: &mut String, tokens: &[Token], source: &Source) {
+    let mut end = 0;
+    for token in tokens {
+        output.push_str(&source.text(Span { start: end, end: token.span.start }));
+        let text = source.text(token.span);
+        match kind_color(&token.kind) {
+            Some(color) => {
+                let _ = write!(output, "{color}{text}\x1b[0m");
+            },
+            None => output.push_str(&text)
+        }
+        end = token.span.end;
+    }
+    output.push_str(&source.text(Span { start: end, end: source.len() }));
+}
+
+/// Statistics about the tokens of one or more files
+#[derive(Default)]
+struct Stats {
+    tokens: usize,
+    bytes: usize,
+    /// The time spent lexing
+    elapsed: Duration,
+    /// The number of tokens of each kind
+    kinds: BTreeMap<&'static str, usize>,
+    /// The number of times each identifier occurs
+    idents: HashMap<String, usize>,
+    /// The length, location and text of the longest token
+    longest: Option<(usize, String, String)>
+}
+
+impl Stats {
+    /// The number of identifiers shown in the histogram
+    const TOP_IDENTS: usize = 20;
+    /// The width of the histogram's longest bar
+    const BAR_WIDTH: usize = 40;
+
+    fn new(path: &str, tokens: &[Token], source: &Source, elapsed: Duration) -> Self {
+        let mut stats = Self { tokens: tokens.len(), bytes: source.len(), elapsed, ..Self::default() };
+        for token in tokens {
+            *stats.kinds.entry(token.kind.name()).or_default() += 1;
+            if let TokenKind::Ident(ident) = &token.kind {
+                *stats.idents.entry(ident.to_string()).or_default() += 1;
+            }
+
+            let len = token.span.end - token.span.start;
+            if Some(&len) > stats.longest.as_ref().map(|(longest, ..)| longest) {
+                let (row, col) = token.position;
+                stats.longest = Some((len, format!("{path}:{row}:{col}"), source.text(token.span).into_owned()));
+            }
+        }
+        stats
+    }
+
+    /// Adds the statistics of another file
+    fn merge(&mut self, other: Self) {
+        self.tokens += other.tokens;
+        self.bytes += other.bytes;
+        self.elapsed += other.elapsed;
+        for (kind, count) in other.kinds {
+            *self.kinds.entry(kind).or_default() += count;
+        }
+        for (ident, count) in other.idents {
+            *self.idents.entry(ident).or_default() += count;
+        }
+        if other.longest.as_ref().map(|(len, ..)| len) > self.longest.as_ref().map(|(len, ..)| len) {
+            self.longest = other.longest;
+        }
+    }
+
+    fn print(&self) {
+        let rate = self.bytes as f64 / 1e6 / self.elapsed.as_secs_f64();
+        println!("tokens: {}", self.tokens);
+        println!("bytes: {} in {:?} ({rate:.1} MB/s)", self.bytes, self.elapsed);
+        if let Some((len, location, text)) = &self.longest {
+            println!("longest token: {len} bytes at {location}: {text:?}");
+        }
+
+        println!("kinds:");
+        for (kind, count) in &self.kinds {
+            println!("  {kind:<16}{count:>10}");
+        }
+
+        let mut idents: Vec<_> = self.idents.iter().collect();
+        idents.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        let Some(&(_, &most)) = idents.first() else {
+            return;
+        };
+        let width = idents.iter().take(Self::TOP_IDENTS).map(|(ident, _)| ident.len()).max().unwrap_or(0);
+        println!("identifiers ({} distinct):", idents.len());
+        for (ident, &count) in idents.into_iter().take(Self::TOP_IDENTS) {
+            let bar = "#".repeat((count * Self::BAR_WIDTH).div_ceil(most));
+            println!("  {ident:<width$} {count:>8} {bar}");
+        }
+    }
+}
+
+/// The outcome of lexing one file
+struct Report {
+    path: String,
+    /// The number of tokens lexed
+    tokens: usize,
+    /// The time spent lexing, excluding reading and printing
+    elapsed: Duration,
+    /// The dump of the tokens
+    output: Vec<u8>,
+    /// The statistics, if they were asked for
+    stats: Option<Stats>,
+    /// The lexical errors found
+    errors: Vec<LexError>
+}
+
+/// Lexes a file, dumping its tokens
+fn lex_file(path: &str, options: &Options) -> Result<Report, String> {
+    let source = Source::open(path, options).map_err(|err| format!("failed to read {path}: {err}"))?;
+
+    // Comments are only highlighted if they're tokens
+    let builder = options.profile.clone().trivia(matches!(options.format, Format::Color | Format::Html));
+    let start = Instant::now();
+    let mut tokens = source.lex(&builder);
+    let elapsed = start.elapsed();
+    let errors = tokens
+        .iter()
+        .filter_map(|token| match &token.kind {
+            TokenKind::Error(kind) => {
+                Some(LexError { kind: kind.clone(), position: token.position, span: token.span, source_name: None })
+            },
+            _ => None
+        })
+        .collect();
+    tokens.retain(|token| options.keeps(token));
+
+    let path = if path == STDIN { "<stdin>" } else { path };
+    let mut output = String::new();
+    let stats = options.stats.then(|| Stats::new(path, &tokens, &source, elapsed));
+    match options.format {
+        _ if options.stats => {},
+        Format::Debug => for token in &tokens {
+            let _ = writeln!(output, "{token:?}");
+        },
+        Format::Json => for token in &tokens {
+            write_json_token(&mut output, token, &source);
+        },
+        Format::Color => write_colored(&mut output, &tokens, &source),
+        Format::Html => {
+            output.push_str("<pre><code>");
+            let _ = HtmlHighlighter::new().write(&mut output, &source.text(Span { start: 0, end: source.len() }), &tokens);
+            output.push_str("</code></pre>\n");
+        },
+        Format::Min => {
+            output.push_str(&options.profile.minify(&source.text(Span { start: 0, end: source.len() })));
+            output.push('\n');
+        },
+        Format::Bin => {
+            let text = source.as_str().ok_or_else(|| format!("{path} isn't valid UTF-8, which --format bin needs"))?;
+            let mut stream = TokenStream::new(builder.build_named(text, path));
+            stream.retain(|token| options.keeps(token));
+            let output = stream.to_cache();
+            return Ok(Report { path: path.to_string(), tokens: tokens.len(), elapsed, output, stats, errors });
+        }
+    }
+    let output = output.into_bytes();
+    Ok(Report { path: path.to_string(), tokens: tokens.len(), elapsed, output, stats, errors })
+}
+
+/// Lexes files on as many threads as there are cores, keeping their order
+fn lex_files(options: &Options) -> Vec<Result<Report, String>> {
+    let paths = &options.paths;
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get).min(paths.len());
+
+    let mut results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| scope.spawn(|| {
+                let mut results = Vec::new();
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(i) else {
+                        break results;
+                    };
+                    results.push((i, lex_file(path, options)));
+                }
+            }))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("lexing thread panicked")).collect()
+    });
+
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Reads a file, or stdin if the path is `-`
+fn read_bytes(path: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let read = if path == STDIN {
+        io::stdin().read_to_end(&mut bytes)
+    } else {
+        fs::File::open(path).and_then(|mut file| file.read_to_end(&mut bytes))
+    };
+    read.map_err(|err| format!("failed to read {path}: {err}"))?;
+    Ok(bytes)
+}
+
+/// Prints the tokens of a source written with `--format bin`, reading them
+/// from a file or stdin
+fn decode(paths: &[String]) -> Result<(), String> {
+    let (source_path, dump_path) = match paths {
+        [source] => (source, STDIN),
+        [source, dump] => (source, dump.as_str()),
+        _ => return Err("decode needs a source and at most one dump".to_string())
+    };
+    if source_path == STDIN && dump_path == STDIN {
+        return Err("decode can't read both the source and the dump from stdin".to_string());
+    }
+
+    let source = String::from_utf8(read_bytes(source_path)?).map_err(|_| format!("{source_path} isn't valid UTF-8"))?;
+    let bytes = read_bytes(dump_path)?;
+    let name = if dump_path == STDIN { "<stdin>" } else { dump_path };
+    let stream = TokenStream::from_cache(&source, &bytes).map_err(|err| format!("{name}: {err}"))?;
+    let mut stdout = io::stdout().lock();
+    for token in stream.tokens() {
+        writeln!(stdout, "{token:?}").map_err(|err| format!("failed to write the tokens: {err}"))?;
+    }
+    Ok(())
+}
+
+/// The number of differing tokens listed by `diff`
+const DIFF_LISTED: usize = 10;
+
+/// Describes where a token is and how it's written
+fn describe(path: &str, token: Option<&Token>, source: &Source) -> String {
+    match token {
+        Some(token) => {
+            let (row, col) = token.position;
+            format!("{path}:{row}:{col} `{}`", source.text(token.span))
+        },
+        None => format!("{path}: end of input")
+    }
+}
+
+/// Compares the tokens of two files, ignoring whitespace and comments
+///
+/// Returns whether they're the same, like `diff`.
+fn diff(options: &Options) -> Result<bool, String> {
+    let [old_path, new_path] = &options.paths[..] else {
+        return Err("diff needs two files".to_string());
+    };
+    let open = |path: &str| Source::open(path, options).map_err(|err| format!("failed to read {path}: {err}"));
+    let (old_source, new_source) = (open(old_path)?, open(new_path)?);
+    let builder = options.profile.clone().trivia(false);
+    let (mut old, mut new) = (old_source.lex(&builder), new_source.lex(&builder));
+    old.retain(|token| options.keeps(token));
+    new.retain(|token| options.keeps(token));
+
+    let prefix = old.iter().zip(&new).take_while(|(old, new)| old.kind == new.kind).count();
+    if prefix == old.len() && prefix == new.len() {
+        println!("no differences in {} tokens", old.len());
+        return Ok(true);
+    }
+    let suffix = old[prefix..].iter().rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old.kind == new.kind)
+        .count();
+    let removed = &old[prefix..old.len() - suffix];
+    let added = &new[prefix..new.len() - suffix];
+
+    println!("first difference:");
+    println!("  {}", describe(old_path, old.get(prefix), &old_source));
+    println!("  {}", describe(new_path, new.get(prefix), &new_source));
+    println!("{} of {} tokens replaced by {} of {} tokens", removed.len(), old.len(), added.len(), new.len());
+    for token in removed.iter().take(DIFF_LISTED) {
+        println!("- {}", describe(old_path, Some(token), &old_source));
+    }
+    for token in added.iter().take(DIFF_LISTED) {
+        println!("+ {}", describe(new_path, Some(token), &new_source));
+    }
+    Ok(false)
+}
+
+/// The extension added to a file's path for its golden file
+const GOLDEN_EXTENSION: &str = "tokens";
+
+/// Records or verifies the golden files of the files, returning whether
+/// every file could be read and its tokens matched
+///
+/// Golden files hold a file's tokens in the dump format, one per line, so
+/// they can be reviewed and diffed. Golden files found in directories
+/// aren't samples themselves.
+fn golden(options: &Options, mode: Golden) -> bool {
+    let extension = format!(".{GOLDEN_EXTENSION}");
+    let samples: Vec<&String> = options.paths.iter().filter(|path| !path.ends_with(&extension)).collect();
+    let mut success = true;
+    let (mut matched, mut recorded) = (0, 0);
+    for path in &samples {
+        let source = match Source::open(path, options) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("error: failed to read {path}: {err}");
+                success = false;
+                continue;
+            }
+        };
+        let mut dump = String::new();
+        for token in source.lex(&options.profile).iter().filter(|token| options.keeps(token)) {
+            dump.push_str(&token.dump());
+            dump.push('\n');
         }
+
+        let golden_path = format!("{path}.{GOLDEN_EXTENSION}");
+        let expected = fs::read_to_string(&golden_path);
+        match mode {
+            Golden::Record if expected.as_deref().ok() == Some(&dump) => matched += 1,
+            Golden::Record => match fs::write(&golden_path, &dump) {
+                Ok(()) => {
+                    println!("recorded {golden_path}");
+                    recorded += 1;
+                },
+                Err(err) => {
+                    eprintln!("error: failed to write {golden_path}: {err}");
+                    success = false;
+                }
+            },
+            Golden::Verify => match expected {
+                Ok(expected) if expected == dump => matched += 1,
+                Ok(expected) => {
+                    // Lines are tokens, so the first differing line is the
+                    // first differing token
+                    let (expected, actual) = (expected.lines().map(Some).chain([None]), dump.lines().map(Some).chain([None]));
+                    let (line, (expected, actual)) = expected.zip(actual).enumerate().find(|(_, (a, b))| a != b).unwrap_or_default();
+                    println!("{path}: token {} differs from {golden_path}", line + 1);
+                    println!("- {}", expected.unwrap_or("end of input"));
+                    println!("+ {}", actual.unwrap_or("end of input"));
+                    success = false;
+                },
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    eprintln!("error: {golden_path} is missing, record it with --record");
+                    success = false;
+                },
+                Err(err) => {
+                    eprintln!("error: failed to read {golden_path}: {err}");
+                    success = false;
+                }
+            }
+        }
+    }
+    match mode {
+        Golden::Record => println!("{recorded} recorded, {matched} unchanged"),
+        Golden::Verify => println!("{matched} of {} files match", samples.len())
     }
+    success
 }
 
 fn main() {
-    if let Some(arg) = env::args().nth(1) {
-        let file = fs::read_to_string(arg).expect("failed to read file");
-        let lexer: Lexer<'_> = Lexer::new(&file);
+    let mut args = env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "decode").is_some() {
+        let paths: Vec<String> = args.collect();
+        if let Err(err) = decode(&paths) {
+            eprintln!("error: {err}");
+            process::exit(2);
+        }
+        return;
+    }
+    if args.next_if(|arg| arg == "explain").is_some() {
+        let code = args.next().unwrap_or_default();
+        match LexErrorKind::explain(&code) {
+            Some(explanation) => println!("{}: {explanation}", code.to_ascii_uppercase()),
+            None => {
+                eprintln!("error: unknown error code `{code}`");
+                process::exit(2);
+            }
+        }
+        return;
+    }
+    if args.next_if(|arg| arg == "diff").is_some() {
+        let result = Options::parse(args).and_then(|options| diff(&options));
+        match result {
+            Ok(same) => process::exit(i32::from(!same)),
+            Err(err) => {
+                eprintln!("error: {err}");
+                process::exit(2);
+            }
+        }
+    }
+
+    let mut options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(2);
+        }
+    };
+    if options.repl {
+        repl(&options);
+        return;
+    }
+    if options.paths.is_empty() {
+        options.paths.push(STDIN.to_string());
+    }
+    if let Some(runs) = options.bench {
+        if !bench(&options, runs) {
+            process::exit(1);
+        }
+        return;
+    }
+    if let Some(mode) = options.golden {
+        if options.paths.iter().any(|path| path == STDIN) {
+            eprintln!("error: --record and --verify need files, not stdin");
+            process::exit(2);
+        }
+        if !golden(&options, mode) {
+            process::exit(1);
+        }
+        return;
+    }
+    if options.watch && options.paths.iter().any(|path| path == STDIN) {
+        eprintln!("error: --watch can't watch stdin");
+        process::exit(2);
+    }
+
+    let success = run(&options);
+    if options.watch {
+        watch(&options);
+    }
+    if !success {
+        process::exit(1);
+    }
+}
+
+/// Lexes lines of stdin as they're entered, numbering positions as if they
+/// were one source
+fn repl(options: &Options) {
+    let interactive = io::stdin().is_terminal();
+    if let Err(err) = lex_lines(options, io::stdin().lock(), io::stdout(), interactive) {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
+}
+
+/// Prints the tokens of each line as soon as it's read
+///
+/// Each line is lexed whole on its own, so tokens don't span lines, but
+/// spans and positions carry on from the line before.
+fn lex_lines(options: &Options, mut input: impl BufRead, mut output: impl Write, prompt: bool) -> io::Result<()> {
+    let (mut offset, mut row) = (0, 1);
+    let mut line = String::new();
+    loop {
+        if prompt {
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        for token in options.profile.build_with_offset(&line, offset, (row, 1)).filter(|token| options.keeps(token)) {
+            writeln!(output, "{token:?}")?;
+        }
+        output.flush()?;
+        offset += line.len();
+        row += 1;
+    }
+}
+
+/// Times lexing each file a number of times, returning whether every file
+/// could be read
+fn bench(options: &Options, runs: usize) -> bool {
+    let builder = &options.profile;
+    let mut success = true;
+    for path in &options.paths {
+        let source = match Source::open(path, options) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("error: failed to read {path}: {err}");
+                success = false;
+                continue;
+            }
+        };
+
+        let mut tokens = 0;
+        let mut times: Vec<Duration> = (0..runs)
+            .map(|_| {
+                let start = Instant::now();
+                tokens = std::hint::black_box(source.count(builder));
+                start.elapsed()
+            })
+            .collect();
+        times.sort();
+
+        let min = times[0];
+        let median = times[runs / 2];
+        let mean = times.iter().sum::<Duration>().div_f64(runs as f64);
+        let rate = source.len() as f64 / 1e6 / median.as_secs_f64();
+        let path = if path == STDIN { "<stdin>" } else { path };
+        println!("{path}: {tokens} tokens, {runs} runs: min {min:?}, median {median:?}, mean {mean:?} ({rate:.1} MB/s)");
+    }
+    success
+}
+
+/// How often watched files are checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The last modification times of files
+fn modified(paths: &[String]) -> Vec<Option<SystemTime>> {
+    paths.iter().map(|path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok()).collect()
+}
+
+/// Lexes the files again whenever one of them changes
+fn watch(options: &Options) -> ! {
+    let mut last = modified(&options.paths);
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+        let current = modified(&options.paths);
+        if current != last {
+            last = current;
+            eprintln!("--- files changed, lexing again");
+            run(options);
+        }
+    }
+}
+
+/// Lexes the files and prints the results, returning whether every file
+/// was read and lexed without errors
+fn run(options: &Options) -> bool {
+    let start = Instant::now();
+    let results = lex_files(options);
+    let elapsed = start.elapsed();
+
+    let mut total = 0;
+    let mut stats = Stats::default();
+    let mut success = true;
+    for result in results {
+        match result {
+            Ok(report) => {
+                if let Err(err) = io::stdout().write_all(&report.output) {
+                    eprintln!("error: failed to write the tokens: {err}");
+                    process::exit(1);
+                }
+                for LexError { kind, position: (row, col), .. } in &report.errors {
+                    eprintln!("{}:{row}:{col}: error[{}]: {kind}", report.path, kind.code());
+                }
+                eprintln!("{}: {} tokens in {:?}", report.path, report.tokens, report.elapsed);
+                total += report.tokens;
+                stats.merge(report.stats.unwrap_or_default());
+                success &= report.errors.is_empty();
+            },
+            Err(err) => {
+                eprintln!("error: {err}");
+                success = false;
+            }
+        }
+    }
+    if options.stats {
+        stats.print();
+    }
+    if options.paths.len() > 1 {
+        let rate = total as f64 / elapsed.as_secs_f64();
+        eprintln!("total: {total} tokens in {elapsed:?} ({rate:.0} tokens/s)");
+    }
+    success
+}
 
-        elapsed!(
-            for tok in lexer {
-                println!("{tok:?}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Input giving a line per read, checking what was printed before each
+    struct Lines {
+        lines: Vec<&'static str>,
+        output: Rc<RefCell<Vec<u8>>>,
+        expected: Vec<&'static str>
+    }
+
+    impl Read for Lines {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let printed = String::from_utf8_lossy(&self.output.borrow()).into_owned();
+            if let Some(expected) = self.expected.pop() {
+                assert!(printed.ends_with(expected), "{printed:?} doesn't end with {expected:?}");
+            }
+            let Some(line) = self.lines.pop() else {
+                return Ok(0);
             };
-            print!("Elapsed time: ");
-        );
+            buf[..line.len()].copy_from_slice(line.as_bytes());
+            Ok(line.len())
+        }
+    }
+
+    /// Output shared with the input checking it
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn prints_each_line_before_reading_the_next() {
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let input = Lines {
+            lines: vec!["c\n", "a + b\n"],
+            output: Rc::clone(&output),
+            expected: vec![
+                "Ident(\"c\"), position: (2, 1), span: Span { start: 6, end: 7 } }\n",
+                "Ident(\"b\"), position: (1, 5), span: Span { start: 4, end: 5 } }\n",
+                ""
+            ]
+        };
+        lex_lines(&Options::default(), io::BufReader::new(input), Shared(Rc::clone(&output)), false).unwrap();
     }
 }
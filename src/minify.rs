@@ -0,0 +1,106 @@
+//! Printing sources back with as little whitespace as keeps their tokens.
+use crate::{Lexer, LexerBuilder, Token, TokenKind};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// What separated two tokens in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gap {
+    /// Nothing, so they were adjacent
+    None,
+    /// Whitespace or comments on one line
+    Space,
+    /// Whitespace or comments with a line break in them
+    Line
+}
+
+impl LexerBuilder {
+    /// Lexes a source and prints it back without its comments, and with
+    /// only the whitespace needed to keep its tokens apart
+    ///
+    /// For shipping scripts in a DSL embedded in something else. Whether two
+    /// tokens need a space is decided by lexing them together, so `a - b`
+    /// becomes `a-b`, but `a - -b` keeps a space if `--` is an operator. A
+    /// line break is kept instead of a space where there was one, and in
+    /// indentation mode lines are indented by a space a block.
+    ///
+    /// The result is lexed again to check it has the same tokens. Where
+    /// tokens lex differently on their own than where they are, e.g. in a
+    /// directive or a template, every gap between tokens is kept as a single
+    /// space or line break instead, and if even that changes the tokens the
+    /// source is returned as it is.
+    pub fn minify(&self, source: &str) -> String {
+        let mut builder = self.clone().trivia(true);
+        builder.config_mut().observer = None;
+        let tokens: Vec<_> = builder.build(source).collect();
+        let builder = builder.trivia(false);
+        let significant: Vec<_> = tokens.iter().map(|token| &token.kind).filter(|kind| !kind.is_trivia()).collect();
+        let lexes_same = |text: &str| builder.build(text).map(|token| token.kind).eq(significant.iter().copied().cloned());
+
+        let minified = builder.print_minified(source, &tokens, true);
+        if lexes_same(&minified) {
+            return minified;
+        }
+        let spaced = builder.print_minified(source, &tokens, false);
+        if lexes_same(&spaced) {
+            return spaced;
+        }
+        source.into()
+    }
+
+    /// Prints tokens lexed with trivia without it, dropping the gaps between
+    /// them if `join` and they lex the same joined
+    fn print_minified(&self, source: &str, tokens: &[Token], join: bool) -> String {
+        let text = |token: &Token| &source[token.span.start..token.span.end];
+        let indentation = self.config.indentation;
+        let mut output = String::with_capacity(source.len());
+        let mut pair = String::new();
+        let mut prev: Option<&Token> = None;
+        let mut gap = Gap::None;
+        let mut depth = 0;
+
+        for token in tokens {
+            match &token.kind {
+                TokenKind::Whitespace(trivia) | TokenKind::Comment(trivia) => {
+                    if trivia.contains(['\n', '\r']) {
+                        gap = Gap::Line;
+                    } else if gap == Gap::None {
+                        gap = Gap::Space;
+                    }
+                    continue;
+                },
+                TokenKind::Indent => depth += 1,
+                TokenKind::Dedent => depth -= usize::from(depth > 0),
+                _ => {}
+            }
+            if token.span.start == token.span.end {
+                continue;
+            }
+
+            // A layout token's line break keeps the tokens around it apart
+            let line_start = matches!(prev, None | Some(Token { kind: TokenKind::Newline, .. }));
+            if line_start && indentation {
+                output.extend((0..depth).map(|_| ' '));
+            } else if let Some(prev) = prev.filter(|_| gap != Gap::None && token.kind != TokenKind::Newline && !line_start) {
+                pair.clear();
+                pair.push_str(text(prev));
+                pair.push_str(text(token));
+                if !join || !self.lexes_apart(&pair, prev, token) {
+                    output.push(if gap == Gap::Line { '\n' } else { ' ' });
+                }
+            }
+            output.push_str(text(token));
+            prev = Some(token);
+            gap = Gap::None;
+        }
+        output
+    }
+
+    /// Does the text of two tokens joined lex as the same two tokens?
+    fn lexes_apart(&self, pair: &str, first: &Token, second: &Token) -> bool {
+        let lexer = Lexer::with_config(pair, Arc::clone(&self.config));
+        let mut kinds = lexer.map(|token| token.kind).filter(|kind| !kind.is_layout());
+        kinds.next().as_ref() == Some(&first.kind) && kinds.next().as_ref() == Some(&second.kind) && kinds.next().is_none()
+    }
+}
@@ -0,0 +1,170 @@
+//! Context-sensitive lexing modes.
+use crate::{LexErrorKind, Lexer, TokenKind};
+use alloc::string::String;
+
+/// How the lexer tokenizes the source
+///
+/// Modes are kept on a stack managed by the lexer; see [`Lexer::push_mode`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    /// Tokens are lexed with the configured rules
+    Normal,
+    /// Source text is lexed as [`TokenKind::Text`] up to `end`, where the
+    /// mode pops itself
+    Raw {
+        end: String
+    },
+    /// The contents of an interpolated string
+    ///
+    /// Text is lexed as [`TokenKind::Text`]. `open` pushes an
+    /// [`Mode::Embedded`] mode closed by `close`, and `quote` ends the string
+    /// and pops the mode. Both are lexed as [`TokenKind::Punct`].
+    Interpolated {
+        quote: char,
+        open: String,
+        close: String
+    },
+    /// Host text with embedded regions, as in a template
+    ///
+    /// Text is lexed as [`TokenKind::Text`]. `open` pushes an
+    /// [`Mode::Embedded`] mode closed by `close`, and is lexed as
+    /// [`TokenKind::Punct`]. The mode lasts to the end of the source.
+    Template {
+        open: String,
+        close: String
+    },
+    /// Tokens are lexed with the configured rules up to `close`, which is
+    /// lexed as [`TokenKind::Punct`] and pops the mode
+    Embedded {
+        close: String
+    },
+    /// Tokens are lexed with the configured rules up to the end of the line,
+    /// where the mode pops itself
+    Directive
+}
+
+/// The mode of a lexer with nothing on its stack
+static NORMAL: Mode = Mode::Normal;
+
+/// Finds where the text of an interpolated string ends
+fn text_end(rest: &str, quote: char, open: &str) -> usize {
+    let mut chars = rest.char_indices();
+
+    while let Some((i, ch)) = chars.next() {
+        if ch == quote || !open.is_empty() && rest[i..].starts_with(open) {
+            return i;
+        }
+        if ch == '\\' {
+            chars.next();
+        }
+    }
+
+    rest.len()
+}
+
+impl<'a> Lexer<'a> {
+    /// The mode the lexer is in
+    #[inline]
+    pub fn mode(&self) -> &Mode {
+        self.modes.last().unwrap_or(&NORMAL)
+    }
+
+    /// Switches to a new mode until it's popped
+    pub fn push_mode(&mut self, mode: Mode) {
+        self.modes.push(mode);
+    }
+
+    /// Returns to the previous mode, returning the one left
+    pub fn pop_mode(&mut self) -> Option<Mode> {
+        self.modes.pop()
+    }
+
+    /// Does the current mode lex raw text rather than tokens?
+    #[inline]
+    pub(crate) fn in_text_mode(&self) -> bool {
+        matches!(self.mode(), Mode::Raw { .. } | Mode::Interpolated { .. } | Mode::Template { .. })
+    }
+
+    /// Pops the current mode if the lexer has reached its end
+    pub(crate) fn end_mode(&mut self) -> bool {
+        let ended = match self.mode() {
+            Mode::Raw { end } => self.rest().starts_with(end.as_str()),
+            Mode::Directive => self.at_line_end(),
+            _ => false
+        };
+
+        if ended {
+            self.pop_mode();
+        }
+        ended
+    }
+
+    /// Pops a mode left open at the end of the source, returning its error
+    pub(crate) fn unterminated_mode(&mut self) -> Option<TokenKind<'a>> {
+        if let Mode::Interpolated { .. } = self.mode() {
+            self.pop_mode();
+            Some(TokenKind::Error(LexErrorKind::UnterminatedString))
+        } else {
+            None
+        }
+    }
+
+    /// Lexes a token specific to the current mode
+    ///
+    /// Returns `None` when the token should be lexed with the configured rules.
+    pub(crate) fn trim_mode_token(&mut self) -> Option<TokenKind<'a>> {
+        let start = self.pos;
+        let rest = self.rest();
+
+        match self.mode() {
+            Mode::Raw { end } => {
+                let len = rest.find(end.as_str()).unwrap_or(rest.len());
+                self.advance_to(start + len);
+                return Some(TokenKind::Text(self.slice(start, self.pos)));
+            },
+            Mode::Interpolated { quote, open, close } => {
+                if rest.starts_with(*quote) {
+                    self.next_char();
+                    self.pop_mode();
+                } else if rest.starts_with(open.as_str()) {
+                    let close = close.clone();
+                    self.advance_to(start + open.len());
+                    self.push_mode(Mode::Embedded { close });
+                } else {
+                    let len = text_end(rest, *quote, open);
+                    self.advance_to(start + len);
+                    return Some(TokenKind::Text(self.slice(start, self.pos)));
+                }
+                return Some(TokenKind::Punct(self.slice(start, self.pos)));
+            },
+            Mode::Template { open, close } => {
+                if !rest.starts_with(open.as_str()) {
+                    let len = rest.find(open.as_str()).unwrap_or(rest.len());
+                    self.advance_to(start + len);
+                    return Some(TokenKind::Text(self.slice(start, self.pos)));
+                }
+                let close = close.clone();
+                self.advance_to(start + open.len());
+                self.push_mode(Mode::Embedded { close });
+                return Some(TokenKind::Punct(self.slice(start, self.pos)));
+            },
+            Mode::Embedded { close } if rest.starts_with(close.as_str()) => {
+                self.advance_to(start + close.len());
+                self.pop_mode();
+                return Some(TokenKind::Punct(self.slice(start, self.pos)));
+            },
+            _ => {}
+        }
+
+        let (quote, open, close) = self.config.interpolation.as_ref()?;
+        if self.prev != *quote {
+            return None;
+        }
+
+        let mode = Mode::Interpolated { quote: *quote, open: open.clone(), close: close.clone() };
+        self.next_char();
+        self.push_mode(mode);
+        Some(TokenKind::Punct(self.slice(start, self.pos)))
+    }
+}
@@ -0,0 +1,118 @@
+//! The table of operators a lexer recognises.
+use crate::Op;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt;
+
+/// Every built-in operator and its default spelling, in `Op` declaration order
+const OPERATORS: [(&str, Op); 13] = [
+    ("+", Op::Plus),
+    ("-", Op::Minus),
+    ("*", Op::Multiply),
+    ("/", Op::Divide),
+    ("%", Op::Modulo),
+    ("=", Op::Equal),
+    ("<>", Op::NotEqual),
+    (">", Op::Greater),
+    (">=", Op::GreaterOrEqual),
+    ("<", Op::Less),
+    ("<=", Op::LessOrEqual),
+    ("++", Op::Increment),
+    ("--", Op::Decrement)
+];
+
+/// The built-in operators only recognised when enabled, as they change how
+/// sequences of signs like `--x` are lexed
+const OPTIONAL: [Op; 2] = [Op::Increment, Op::Decrement];
+
+impl Op {
+    /// The operator declared at an index of `Op`
+    pub(crate) fn from_index(index: usize) -> Option<Self> {
+        OPERATORS.get(index).map(|(_, op)| op.clone())
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(OPERATORS[self.clone() as usize].0)
+    }
+}
+
+/// Operator spellings, each lexed as a built-in [`Op`] or as itself
+///
+/// Entries are kept sorted by their first character and then longest first,
+/// so the first entry that matches is the longest one.
+#[derive(Debug, Clone)]
+pub(crate) struct OperatorTable {
+    entries: Vec<(String, Option<Op>)>
+}
+
+impl Default for OperatorTable {
+    fn default() -> Self {
+        let mut table = Self { entries: Vec::new() };
+        for (symbol, op) in OPERATORS {
+            if !OPTIONAL.contains(&op) {
+                table.insert(symbol.to_string(), Some(op));
+            }
+        }
+        table
+    }
+}
+
+impl OperatorTable {
+    /// The sort key of an operator spelling
+    #[inline]
+    fn key(symbol: &str) -> (char, Reverse<usize>) {
+        (symbol.chars().next().unwrap_or('\0'), Reverse(symbol.len()))
+    }
+
+    /// Adds an operator, replacing any with the same spelling
+    pub fn insert(&mut self, symbol: String, op: Option<Op>) {
+        if symbol.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|(existing, _)| *existing != symbol);
+        let key = Self::key(&symbol);
+        let i = self.entries.partition_point(|(existing, _)| Self::key(existing) < key);
+        self.entries.insert(i, (symbol, op));
+    }
+
+    /// Removes every built-in operator, keeping custom ones
+    pub fn clear_builtin(&mut self) {
+        self.entries.retain(|(_, op)| op.is_none());
+    }
+
+    /// Adds the built-in operators with their default spellings
+    pub fn insert_builtin(&mut self, ops: impl IntoIterator<Item = Op>) {
+        for op in ops {
+            let (symbol, _) = OPERATORS[op.clone() as usize];
+            self.insert(symbol.to_string(), Some(op));
+        }
+    }
+
+    /// Removes the given built-in operators, whatever their spelling
+    pub fn remove_builtin(&mut self, ops: &[Op]) {
+        self.entries.retain(|(_, op)| !matches!(op, Some(op) if ops.contains(op)));
+    }
+
+    /// The length in bytes of the longest operator
+    pub fn longest(&self) -> usize {
+        self.entries.iter().map(|(symbol, _)| symbol.len()).max().unwrap_or(0)
+    }
+
+    /// Finds the longest operator at the start of a string
+    ///
+    /// Returns its length in bytes and the built-in operator it's lexed as.
+    pub fn longest_match(&self, rest: &str) -> Option<(usize, Option<Op>)> {
+        let first = rest.chars().next()?;
+        let start = self.entries.partition_point(|(symbol, _)| Self::key(symbol).0 < first);
+
+        self.entries[start..]
+            .iter()
+            .take_while(|(symbol, _)| symbol.starts_with(first))
+            .find(|(symbol, _)| rest.starts_with(symbol.as_str()))
+            .map(|(symbol, op)| (symbol.len(), op.clone()))
+    }
+}
@@ -0,0 +1,122 @@
+//! Tokens that own their text.
+use crate::{LexErrorKind, Op, Span, Symbol, Token, TokenKind};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// A [`TokenKind`] owning its text, so it can outlive the source
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedTokenKind {
+    Opr(Op),
+    Ident(String),
+    Symbol(Symbol),
+    Keyword(String),
+    Punct(String),
+    Num(i32),
+    Float(String),
+    Str(String),
+    Text(String),
+    Newline,
+    Indent,
+    Dedent,
+    Whitespace(String),
+    Comment(String),
+    OpeningBracket,
+    ClosingBracket,
+    Error(LexErrorKind)
+}
+
+impl OwnedTokenKind {
+    /// Borrows the kind as a [`TokenKind`]
+    pub fn as_kind(&self) -> TokenKind<'_> {
+        match self {
+            Self::Opr(op) => TokenKind::Opr(op.clone()),
+            Self::Ident(ident) => TokenKind::Ident(Cow::Borrowed(ident)),
+            Self::Symbol(symbol) => TokenKind::Symbol(*symbol),
+            Self::Keyword(keyword) => TokenKind::Keyword(keyword),
+            Self::Punct(symbol) => TokenKind::Punct(symbol),
+            Self::Num(value) => TokenKind::Num(*value),
+            Self::Float(text) => TokenKind::Float(text),
+            Self::Str(contents) => TokenKind::Str(contents),
+            Self::Text(text) => TokenKind::Text(text),
+            Self::Newline => TokenKind::Newline,
+            Self::Indent => TokenKind::Indent,
+            Self::Dedent => TokenKind::Dedent,
+            Self::Whitespace(text) => TokenKind::Whitespace(text),
+            Self::Comment(text) => TokenKind::Comment(text),
+            Self::OpeningBracket => TokenKind::OpeningBracket,
+            Self::ClosingBracket => TokenKind::ClosingBracket,
+            Self::Error(err) => TokenKind::Error(err.clone())
+        }
+    }
+}
+
+impl fmt::Display for OwnedTokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_kind().fmt(f)
+    }
+}
+
+/// A [`Token`] owning its text, e.g. for lexing lines of a REPL
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedToken {
+    /// The token's kind
+    pub kind: OwnedTokenKind,
+    /// The token's position in file
+    pub position: (usize, usize),
+    /// The bytes the token was lexed from
+    pub span: Span
+}
+
+impl OwnedToken {
+    /// Borrows the token as a [`Token`]
+    pub fn as_token(&self) -> Token<'_> {
+        Token { kind: self.kind.as_kind(), position: self.position, span: self.span }
+    }
+}
+
+impl fmt::Display for OwnedToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl TokenKind<'_> {
+    /// Copies the kind's text so it no longer borrows the source
+    pub fn into_owned(self) -> OwnedTokenKind {
+        match self {
+            Self::Opr(op) => OwnedTokenKind::Opr(op),
+            Self::Ident(ident) => OwnedTokenKind::Ident(ident.into_owned()),
+            Self::Symbol(symbol) => OwnedTokenKind::Symbol(symbol),
+            Self::Keyword(keyword) => OwnedTokenKind::Keyword(keyword.to_string()),
+            Self::Punct(symbol) => OwnedTokenKind::Punct(symbol.to_string()),
+            Self::Num(value) => OwnedTokenKind::Num(value),
+            Self::Float(text) => OwnedTokenKind::Float(text.to_string()),
+            Self::Str(contents) => OwnedTokenKind::Str(contents.to_string()),
+            Self::Text(text) => OwnedTokenKind::Text(text.to_string()),
+            Self::Newline => OwnedTokenKind::Newline,
+            Self::Indent => OwnedTokenKind::Indent,
+            Self::Dedent => OwnedTokenKind::Dedent,
+            Self::Whitespace(text) => OwnedTokenKind::Whitespace(text.to_string()),
+            Self::Comment(text) => OwnedTokenKind::Comment(text.to_string()),
+            Self::OpeningBracket => OwnedTokenKind::OpeningBracket,
+            Self::ClosingBracket => OwnedTokenKind::ClosingBracket,
+            Self::Error(err) => OwnedTokenKind::Error(err)
+        }
+    }
+}
+
+impl Token<'_> {
+    /// Copies the token's text so it no longer borrows the source
+    pub fn into_owned(self) -> OwnedToken {
+        OwnedToken { kind: self.kind.into_owned(), position: self.position, span: self.span }
+    }
+}
+
+impl<'a> From<Token<'a>> for OwnedToken {
+    fn from(token: Token<'a>) -> Self {
+        token.into_owned()
+    }
+}
@@ -0,0 +1,147 @@
+//! Lexing a large source on several threads.
+use crate::{Lexer, LexerBuilder, Token};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::thread;
+
+/// The tokens lexed from a chunk of the source, starting from the default
+/// state at its start
+struct Chunk<'a> {
+    /// The tokens starting in the chunk
+    tokens: Vec<Token<'a>>,
+    /// The lexer after the chunk's first token, to check the previous chunk
+    /// reaches the same state
+    first: Option<Lexer<'a>>,
+    /// The first token starting past the chunk, and the lexer after it
+    next: Option<(Token<'a>, Lexer<'a>)>
+}
+
+/// Lexes the tokens starting before a byte offset of the file, returning
+/// them with the first token after and the lexer after that
+fn lex_until<'a>(lexer: &mut Lexer<'a>, end: usize, tokens: &mut Vec<Token<'a>>) -> Option<(Token<'a>, Lexer<'a>)> {
+    for token in lexer.by_ref() {
+        if token.span.start >= end {
+            return Some((token, lexer.clone()));
+        }
+        tokens.push(token);
+    }
+    None
+}
+
+/// Lexes a chunk of the source, from a byte offset of the file up to another
+fn lex_chunk<'a>(mut lexer: Lexer<'a>, end: usize) -> Chunk<'a> {
+    let mut tokens = Vec::new();
+    let mut first = None;
+    let next = match lexer.next() {
+        Some(token) if token.span.start < end => {
+            tokens.push(token);
+            first = Some(lexer.clone());
+            lex_until(&mut lexer, end, &mut tokens)
+        },
+        Some(token) => Some((token, lexer.clone())),
+        None => None
+    };
+    Chunk { tokens, first, next }
+}
+
+impl<'a> Lexer<'a> {
+    /// Has this lexer reached the same point as another, lexing from there
+    /// on the same way?
+    fn synced_with(&self, other: &Lexer<'a>) -> bool {
+        self.byte_offset() == other.byte_offset()
+            && (self.row, self.col, self.bom) == (other.row, other.col, other.bom)
+            && (self.after_operand, self.depth) == (other.after_operand, other.depth)
+            && self.modes == other.modes
+            && self.layout == other.layout
+            && self.lookahead.is_empty()
+            && other.lookahead.is_empty()
+    }
+
+    /// Lexes a whole source string with the default settings on several
+    /// threads, keeping errors as [`TokenKind::Error`](crate::TokenKind::Error)
+    /// tokens
+    ///
+    /// See [`LexerBuilder::tokenize_parallel`].
+    pub fn tokenize_parallel(source: &'a str, threads: usize) -> Vec<Token<'a>> {
+        LexerBuilder::new().tokenize_parallel(source, threads)
+    }
+}
+
+impl LexerBuilder {
+    /// Lexes a whole source string with these settings on several threads,
+    /// keeping errors as [`TokenKind::Error`](crate::TokenKind::Error) tokens
+    ///
+    /// For sources of many megabytes, such as logs. The source is split into
+    /// a chunk a thread at line breaks, each lexed as if it started a file.
+    /// Where a line break turns out to be inside a token, e.g. a multiline
+    /// string or comment, or the lexer is in another state there, e.g. in
+    /// an indented block, the chunk after it is lexed again from the end of
+    /// the one before. So the tokens are always the same as lexing on one
+    /// thread, but sources whose line breaks are rarely at the top level
+    /// gain little.
+    ///
+    /// Comments and warnings aren't collected, and an observer set with
+    /// [`LexerBuilder::on_token`] may see tokens more than once and out of
+    /// order.
+    pub fn tokenize_parallel<'a>(&self, source: &'a str, threads: usize) -> Vec<Token<'a>> {
+        let threads = threads.max(1);
+
+        // Each chunk starts after the first line break past its share, and
+        // its position is found by skipping to it
+        let mut walker = self.build(source);
+        let mut starts = vec![(0, walker.pos())];
+        for i in 1..threads {
+            let target = (source.len() / threads * i).max(starts[starts.len() - 1].0);
+            let Some(newline) = memchr::memchr(b'\n', &source.as_bytes()[target..]) else {
+                break;
+            };
+            let start = target + newline + 1;
+            if start >= source.len() {
+                break;
+            }
+            walker.advance_to(start);
+            starts.push((start, walker.pos()));
+        }
+        let ends: Vec<usize> = starts.iter().skip(1).map(|&(start, _)| start).chain([usize::MAX]).collect();
+
+        let chunks: Vec<Chunk<'a>> = thread::scope(|scope| {
+            let handles: Vec<_> = starts
+                .iter()
+                .zip(&ends)
+                .skip(1)
+                .map(|(&(start, position), &end)| {
+                    let lexer = Lexer::with_config(&source[start..], Arc::clone(&self.config)).starting_at(start, position);
+                    scope.spawn(move || lex_chunk(lexer, end))
+                })
+                .collect();
+            let first = lex_chunk(self.build(source), ends[0]);
+            let rest = handles.into_iter().map(|handle| handle.join().unwrap_or_else(|err| std::panic::resume_unwind(err)));
+            [first].into_iter().chain(rest).collect()
+        });
+
+        // Each chunk is kept if the one before reached the same state at its
+        // first token, and lexed again from there otherwise
+        let total: usize = chunks.iter().map(|chunk| chunk.tokens.len()).sum();
+        let mut chunks = chunks.into_iter().zip(ends);
+        let Some((first, _)) = chunks.next() else {
+            return Vec::new();
+        };
+        let mut tokens = first.tokens;
+        tokens.reserve(total - tokens.len());
+        let mut next = first.next;
+        for (chunk, end) in chunks {
+            let Some((token, mut lexer)) = next else {
+                break;
+            };
+            let synced = chunk.tokens.first() == Some(&token) && chunk.first.as_ref().is_some_and(|first| lexer.synced_with(first));
+            if synced {
+                tokens.extend(chunk.tokens);
+                next = chunk.next;
+            } else {
+                tokens.push(token);
+                next = lex_until(&mut lexer, end, &mut tokens);
+            }
+        }
+        tokens
+    }
+}
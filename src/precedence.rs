@@ -0,0 +1,114 @@
+//! Operator precedence and associativity for expression parsers.
+use crate::Op;
+
+/// Which side operators of equal precedence group from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+    /// `a < b < c` is an error
+    None
+}
+
+/// The precedence and associativity of every built-in operator, indexed by `Op`
+const DEFAULT: [(u8, Associativity); 13] = [
+    (2, Associativity::Left),
+    (2, Associativity::Left),
+    (3, Associativity::Left),
+    (3, Associativity::Left),
+    (3, Associativity::Left),
+    (1, Associativity::None),
+    (1, Associativity::None),
+    (1, Associativity::None),
+    (1, Associativity::None),
+    (1, Associativity::None),
+    (1, Associativity::None),
+    (4, Associativity::Right),
+    (4, Associativity::Right)
+];
+
+impl Op {
+    /// How tightly the operator binds by default, higher binding tighter
+    ///
+    /// Comparisons bind loosest, then `+` and `-`, then `*`, `/` and `%`,
+    /// then `++` and `--`.
+    #[inline]
+    pub fn precedence(&self) -> u8 {
+        PrecedenceTable::default().precedence(self)
+    }
+
+    /// Which side the operator groups from by default
+    ///
+    /// Arithmetic is left-associative, comparisons don't chain and `++` and
+    /// `--` group from the right, as prefix operators.
+    #[inline]
+    pub fn associativity(&self) -> Associativity {
+        PrecedenceTable::default().associativity(self)
+    }
+}
+
+/// The precedence and associativity of each operator in a language
+///
+/// Starts out with the defaults of [`Op::precedence`] and
+/// [`Op::associativity`], for languages to override.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrecedenceTable {
+    entries: [(u8, Associativity); 13]
+}
+
+impl Default for PrecedenceTable {
+    fn default() -> Self {
+        Self { entries: DEFAULT }
+    }
+}
+
+impl PrecedenceTable {
+    /// Creates a table with the default precedences
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets an operator's precedence and associativity
+    pub fn with(mut self, op: Op, precedence: u8, associativity: Associativity) -> Self {
+        self.entries[op as usize] = (precedence, associativity);
+        self
+    }
+
+    /// How tightly an operator binds, higher binding tighter
+    #[inline]
+    pub fn precedence(&self, op: &Op) -> u8 {
+        self.entries[op.clone() as usize].0
+    }
+
+    /// Which side an operator groups from
+    #[inline]
+    pub fn associativity(&self, op: &Op) -> Associativity {
+        self.entries[op.clone() as usize].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        assert!(Op::Multiply.precedence() > Op::Plus.precedence());
+        assert!(Op::Minus.precedence() > Op::Less.precedence());
+        assert!(Op::Increment.precedence() > Op::Modulo.precedence());
+        assert_eq!(Op::Divide.precedence(), Op::Modulo.precedence());
+        assert_eq!(Op::Minus.associativity(), Associativity::Left);
+        assert_eq!(Op::Equal.associativity(), Associativity::None);
+        assert_eq!(Op::Decrement.associativity(), Associativity::Right);
+    }
+
+    #[test]
+    fn overrides_only_the_given_operator() {
+        let table = PrecedenceTable::new().with(Op::Equal, 0, Associativity::Left);
+        assert_eq!((table.precedence(&Op::Equal), table.associativity(&Op::Equal)), (0, Associativity::Left));
+        assert_eq!((table.precedence(&Op::NotEqual), table.associativity(&Op::NotEqual)), (1, Associativity::None));
+        assert_eq!(table.with(Op::Equal, 1, Associativity::None), PrecedenceTable::default());
+    }
+}
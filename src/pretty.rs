@@ -0,0 +1,141 @@
+//! Printing tokens as readable source text.
+use crate::{Delimiter, TokenKind};
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+/// Prints tokens as source text, choosing the spacing between them
+///
+/// For code generators producing tokens rather than text. Words are kept
+/// apart by spaces, `,` and `;` are followed by one, `.` and `::` join
+/// their operands, and prefix operators such as the `-` in `-x` join the
+/// operand after them. Layout tokens start new lines, indented by the
+/// blocks `Indent` and `Dedent` tokens open and close. Whitespace and
+/// errors are left out.
+#[derive(Debug, Clone)]
+pub struct PrettyPrinter {
+    /// Whether infix operators have spaces around them
+    space_around_operators: bool,
+    /// Whether parentheses and brackets have spaces inside them
+    space_inside_brackets: bool,
+    /// The text a block is indented by
+    indent: &'static str
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        Self { space_around_operators: true, space_inside_brackets: false, indent: "    " }
+    }
+}
+
+impl PrettyPrinter {
+    /// Creates a printer spacing operators as in `a + b` and brackets as in
+    /// `f(x)`, indenting blocks by four spaces
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether infix operators have spaces around them, as in `a + b`
+    /// rather than `a+b`
+    pub fn space_around_operators(mut self, enabled: bool) -> Self {
+        self.space_around_operators = enabled;
+        self
+    }
+
+    /// Sets whether parentheses and brackets have spaces inside them, as in
+    /// `f( x )` rather than `f(x)`
+    pub fn space_inside_brackets(mut self, enabled: bool) -> Self {
+        self.space_inside_brackets = enabled;
+        self
+    }
+
+    /// Sets the text a block is indented by
+    pub fn indent(mut self, indent: &'static str) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Prints tokens as source text
+    pub fn print<'t, 'a: 't>(&self, tokens: impl IntoIterator<Item = &'t TokenKind<'a>>) -> String {
+        let mut text = String::new();
+        let _ = self.write(&mut text, tokens);
+        text
+    }
+
+    /// Writes tokens as source text
+    ///
+    /// See [`PrettyPrinter::print`].
+    pub fn write<'t, 'a: 't>(&self, output: &mut impl Write, tokens: impl IntoIterator<Item = &'t TokenKind<'a>>) -> fmt::Result {
+        let mut prev: Option<&TokenKind> = None;
+        let mut prefix = false;
+        let mut depth = 0;
+        let mut line_start = true;
+
+        for kind in tokens {
+            match kind {
+                TokenKind::Newline => {
+                    output.write_char('\n')?;
+                    (prev, line_start) = (None, true);
+                    continue;
+                },
+                TokenKind::Indent => {
+                    depth += 1;
+                    continue;
+                },
+                TokenKind::Dedent => {
+                    depth -= usize::from(depth > 0);
+                    continue;
+                },
+                TokenKind::Whitespace(_) | TokenKind::Error(_) => continue,
+                _ => {}
+            }
+
+            if line_start {
+                for _ in 0..depth {
+                    output.write_str(self.indent)?;
+                }
+                line_start = false;
+            } else if let Some(prev) = prev.filter(|_| !prefix) {
+                if self.spaced(prev, kind) {
+                    output.write_char(' ')?;
+                }
+            }
+            write!(output, "{kind}")?;
+
+            prefix = is_spaced_operator(kind) && !prev.is_some_and(TokenKind::ends_operand);
+            prev = Some(kind);
+        }
+        Ok(())
+    }
+
+    /// Whether two tokens on the same line have a space between them
+    fn spaced(&self, prev: &TokenKind, next: &TokenKind) -> bool {
+        let opens = |kind| matches!(Delimiter::opened_by(kind), Some(Delimiter::Parenthesis | Delimiter::Bracket));
+        let closes = |kind| matches!(Delimiter::closed_by(kind), Some(Delimiter::Parenthesis | Delimiter::Bracket));
+
+        if opens(prev) || closes(next) {
+            self.space_inside_brackets
+        } else if matches!(next, TokenKind::Punct("," | ";")) || is_joining(prev) || is_joining(next) {
+            false
+        } else if matches!(prev, TokenKind::Punct("," | ";")) {
+            true
+        } else if opens(next) && prev.ends_operand() {
+            // A call or an index
+            false
+        } else if is_spaced_operator(prev) || is_spaced_operator(next) {
+            self.space_around_operators
+        } else {
+            true
+        }
+    }
+}
+
+/// Is this an operator that joins its operands, like `.` or `::`?
+fn is_joining(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::Punct("." | "::"))
+}
+
+/// Is this an operator spaced as set by
+/// [`PrettyPrinter::space_around_operators`]?
+fn is_spaced_operator(kind: &TokenKind) -> bool {
+    kind.is_operator() && !is_joining(kind) && !matches!(kind, TokenKind::Punct("," | ";"))
+}
@@ -0,0 +1,723 @@
+//! Preset configurations for common languages.
+//!
+//! Each profile is an ordinary [`LexerBuilder`], so its settings can be
+//! adjusted further before building lexers, and its source doubles as an
+//! example of configuring the builder. A [`Registry`] looks them up by name.
+use crate::{LexErrorKind, Lexer, LexerBuilder, Op, TokenKind};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Lexes JSON, per RFC 8259
+///
+/// Strings are `Str` tokens, `true`, `false` and `null` are keywords and the
+/// structural characters `{ } [ ] : ,` are `Punct` tokens. Numbers that are
+/// integers fitting an `i32` are `Num` tokens, others `Float` tokens. A
+/// leading `-` is part of the number, and a leading zero is a number on its
+/// own, so `012` is `0` and `12` for a parser to reject. There are no
+/// comments or operators.
+pub fn json() -> LexerBuilder {
+    LexerBuilder::new()
+        .string_delimiters(['"'])
+        .keywords(["true", "false", "null"])
+        .line_comments(Vec::<String>::new())
+        .operators([])
+        .operator("{")
+        .operator("}")
+        .operator("[")
+        .operator("]")
+        .operator(":")
+        .operator(",")
+        .number_rule(json_number)
+        .fallback(json_number)
+}
+
+/// Lexes a JSON number, whose integer part is only `0` if it starts with one
+fn json_number<'a>(lexer: &mut Lexer<'a>) -> Option<TokenKind<'a>> {
+    let bytes = lexer.remaining().as_bytes();
+    let sign = usize::from(bytes.first() == Some(&b'-'));
+    match bytes[sign..] {
+        [b'0', b'0'..=b'9', ..] => {
+            lexer.advance(sign + 1);
+            Some(TokenKind::Num(0))
+        },
+        [b'0'..=b'9', ..] => decimal_number(lexer),
+        _ => None
+    }
+}
+
+/// Lexes INI and TOML-style configuration files
+///
+/// `#` and `;` start comments and strings are quoted with `"` or `'`. Bare
+/// keys, which can contain `-`, are identifiers, and `true` and `false` are
+/// keywords. The `[` and `]` around section headers and the `=` of
+/// assignments are `Punct` tokens, as are the `.` of dotted keys and the `,`
+/// in arrays. Numbers are lexed as in [`json`]. Since entries end at the end
+/// of the line, line breaks are `Newline` tokens.
+pub fn ini() -> LexerBuilder {
+    LexerBuilder::new()
+        .string_delimiters(['"', '\''])
+        .keywords(["true", "false"])
+        .line_comments(["#", ";"])
+        .operators([])
+        .operator("[")
+        .operator("]")
+        .operator("=")
+        .operator(".")
+        .operator(",")
+        .ident_continue(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+        .newlines(true)
+        .number_rule(decimal_number)
+        .fallback(negative_number)
+}
+
+/// Lexes SQL queries
+///
+/// Keywords such as `SELECT` and `where` are matched regardless of case.
+/// `--` and `/* */` start comments, and strings are quoted with `'`, which
+/// is escaped by doubling it. Besides the built-in operators, `!=` is lexed
+/// as [`Op::NotEqual`], and `||`, `,`, `;` and `.` are `Punct` tokens.
+/// Numbers can have a fraction and exponent, as in [`json`].
+pub fn sql() -> LexerBuilder {
+    LexerBuilder::new()
+        .keywords(SQL_KEYWORDS)
+        .case_insensitive_keywords(true)
+        .line_comments(["--"])
+        .block_comments([("/*", "*/")])
+        .string_delimiters(['\''])
+        .doubled_quote_escapes(true)
+        .operator_as("!=", Op::NotEqual)
+        .operator("||")
+        .operator(",")
+        .operator(";")
+        .operator(".")
+        .number_rule(decimal_number)
+}
+
+/// The keywords common to the SQL dialects
+const SQL_KEYWORDS: [&str; 52] = [
+    "ALL", "ALTER", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CREATE", "DEFAULT", "DELETE", "DESC", "DISTINCT",
+    "DROP", "ELSE", "END", "EXISTS", "FALSE", "FROM", "FULL", "GROUP", "HAVING", "IN", "INDEX", "INNER", "INSERT",
+    "INTO", "IS", "JOIN", "KEY", "LEFT", "LIKE", "LIMIT", "NOT", "NULL", "ON", "OR", "ORDER", "OUTER", "PRIMARY",
+    "RIGHT", "SELECT", "SET", "TABLE", "THEN", "TRUE", "UNION", "UPDATE", "VALUES", "VIEW", "WHEN", "WHERE"
+];
+
+/// Lexes S-expressions, as in Lisp or Scheme
+///
+/// Any run of characters other than whitespace, parentheses, `"` and `;` is
+/// a symbol, lexed as an identifier, e.g. `list->vector` or `*x*`, unless
+/// it's a number. Parentheses are brackets, `;` starts comments and strings
+/// are quoted with `"`. Numbers can have a sign, fraction and exponent, as
+/// in [`json`].
+pub fn lisp() -> LexerBuilder {
+    LexerBuilder::new()
+        .string_delimiters(['"'])
+        .line_comments([";"])
+        .operators([])
+        .ident_start(|ch| is_symbol_char(ch) && !matches!(ch, '0'..='9' | '+' | '-'))
+        .ident_continue(is_symbol_char)
+        .number_rule(decimal_number)
+        .fallback(signed_number_or_symbol)
+}
+
+/// Whether a character can be part of a Lisp symbol
+fn is_symbol_char(ch: char) -> bool {
+    !ch.is_whitespace() && !matches!(ch, '(' | ')' | '"' | ';')
+}
+
+/// Lexes a token starting with a sign, a number if a digit follows or else
+/// a symbol
+fn signed_number_or_symbol<'a>(lexer: &mut Lexer<'a>) -> Option<TokenKind<'a>> {
+    let text = lexer.remaining();
+    let mut chars = text.chars();
+    if !matches!(chars.next(), Some('+' | '-')) {
+        return None;
+    }
+    if chars.next().is_some_and(|ch| ch.is_ascii_digit()) {
+        return decimal_number(lexer);
+    }
+    let len = text.find(|ch| !is_symbol_char(ch)).unwrap_or(text.len());
+    lexer.advance(len);
+    Some(TokenKind::Ident(text[..len].into()))
+}
+
+/// Lexes C and the languages following its syntax
+///
+/// Has C's keywords, `//` and `/* */` comments and the full C operator set.
+/// The arithmetic, comparison, `++` and `--` operators are built-in, with `==`
+/// as [`Op::Equal`] and `!=` as [`Op::NotEqual`], and the others, including
+/// `=`, are `Punct` tokens. Strings are quoted with `"`. Numbers can be
+/// hexadecimal with `0x`, octal with a leading `0`, or decimal with a
+/// fraction and exponent, and type suffixes such as `u` or `f` are part of
+/// the number. Character literals like `'a'` or `'\n'` are `Num` tokens
+/// holding their value, as C treats them as integers. A `\` at the end of a
+/// line joins it to the next.
+pub fn c_like() -> LexerBuilder {
+    let builder = LexerBuilder::new()
+        .keywords(C_KEYWORDS)
+        .block_comments([("/*", "*/")])
+        .string_delimiters(['"'])
+        .operators([
+            Op::Plus,
+            Op::Minus,
+            Op::Multiply,
+            Op::Divide,
+            Op::Modulo,
+            Op::Greater,
+            Op::GreaterOrEqual,
+            Op::Less,
+            Op::LessOrEqual,
+            Op::Increment,
+            Op::Decrement
+        ])
+        .operator_as("==", Op::Equal)
+        .operator_as("!=", Op::NotEqual)
+        .number_rule(c_number)
+        .fallback(char_literal)
+        .line_continuations(true);
+    C_PUNCTUATION.into_iter().fold(builder, LexerBuilder::operator)
+}
+
+/// The keywords of C99
+const C_KEYWORDS: [&str; 37] = [
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum", "extern",
+    "float", "for", "goto", "if", "inline", "int", "long", "register", "restrict", "return", "short", "signed",
+    "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void", "volatile", "while", "_Bool",
+    "_Complex", "_Imaginary"
+];
+
+/// The operators and punctuation of C that aren't built-in operators
+const C_PUNCTUATION: [&str; 31] = [
+    "=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=", "&&", "||", "!", "~", "&", "|", "^", "<<",
+    ">>", "->", ".", ",", ";", ":", "?", "{", "}", "[", "]", "..."
+];
+
+/// Lexes a C number, which is hexadecimal or octal if it starts with `0x` or
+/// `0` and can end in a type suffix
+fn c_number<'a>(lexer: &mut Lexer<'a>) -> Option<TokenKind<'a>> {
+    let text = lexer.remaining();
+    let (radix, prefix) = match text.as_bytes() {
+        [b'0', b'x' | b'X', ..] => (16, 2),
+        [b'0', b'0'..=b'7', ..] => (8, 1),
+        _ => (10, 0)
+    };
+    let (digits, fractional) = match radix {
+        10 => decimal_len(text)?,
+        _ => {
+            let digits = text[prefix..].find(|ch: char| !ch.is_digit(radix)).unwrap_or(text.len() - prefix);
+            (prefix + digits, false)
+        }
+    };
+    if digits == prefix {
+        return None;
+    }
+    let suffixes: &[char] = if fractional { &['f', 'F', 'l', 'L'] } else { &['u', 'U', 'l', 'L'] };
+    let suffix = text[digits..].find(|ch| !suffixes.contains(&ch)).unwrap_or(text.len() - digits);
+    let len = digits + suffix;
+    lexer.advance(len);
+    // Hexadecimal and octal numbers up to `u32::MAX` keep their bits
+    let value = match radix {
+        10 => text[..digits].parse().ok(),
+        _ => u32::from_str_radix(&text[prefix..digits], radix).ok().map(|value| value as i32)
+    };
+    Some(number_kind(&text[..len], value.filter(|_| !fractional)))
+}
+
+/// Lexes a character literal as the value of its character
+///
+/// Malformed literals are left to be reported as unexpected characters.
+fn char_literal<'a>(lexer: &mut Lexer<'a>) -> Option<TokenKind<'a>> {
+    let literal = lexer.remaining().strip_prefix('\'')?;
+    let (value, len) = match literal.strip_prefix('\\') {
+        Some(escape) => c_escape(escape).map(|(value, len)| (value, len + 1))?,
+        None => literal.chars().next().filter(|&ch| ch != '\'' && ch != '\n').map(|ch| (ch as i32, ch.len_utf8()))?
+    };
+    if !literal[len..].starts_with('\'') {
+        return None;
+    }
+    lexer.advance(len + 2);
+    Some(TokenKind::Num(value))
+}
+
+/// The value and length of an escape sequence following a backslash
+fn c_escape(escape: &str) -> Option<(i32, usize)> {
+    match escape.chars().next()? {
+        'x' => {
+            let len = escape[1..].chars().take(8).take_while(char::is_ascii_hexdigit).count();
+            Some((u32::from_str_radix(&escape[1..=len], 16).ok()? as i32, len + 1))
+        },
+        '0'..='7' => {
+            let len = escape.chars().take(3).take_while(|ch| ch.is_digit(8)).count();
+            Some((i32::from_str_radix(&escape[..len], 8).ok()?, len))
+        },
+        ch => {
+            let value = match ch {
+                'a' => 0x07,
+                'b' => 0x08,
+                'f' => 0x0c,
+                'n' => b'\n',
+                'r' => b'\r',
+                't' => b'\t',
+                'v' => 0x0b,
+                '\\' | '\'' | '"' | '?' => ch as u8,
+                _ => return None
+            };
+            Some((i32::from(value), 1))
+        }
+    }
+}
+
+/// Lexes shell commands, splitting them into words
+///
+/// Any run of characters other than whitespace, quotes and operators is a
+/// word, lexed as an identifier, e.g. `ls`, `-la`, `$HOME` or `2`. Strings
+/// in `"` can escape characters with a backslash, while strings in `'`
+/// can't, so a backslash in them is an ordinary character. A quoted string
+/// next to a word is a separate token. `#` starts a comment at the start of
+/// a word. The pipeline, list and redirection operators, such as `|`, `&&`,
+/// `;` and `>>`, are `Punct` tokens, and parentheses are brackets. Since
+/// commands end at the end of the line, line breaks are `Newline` tokens,
+/// unless escaped with a `\`.
+pub fn shell() -> LexerBuilder {
+    let builder = LexerBuilder::new()
+        .string_delimiters(['"'])
+        .line_comments(["#"])
+        .operators([])
+        .ident_start(|ch| is_word_char(ch) && ch != '#')
+        .ident_continue(is_word_char)
+        .newlines(true)
+        .line_continuations(true)
+        .fallback(single_quoted);
+    SHELL_OPERATORS.into_iter().fold(builder, LexerBuilder::operator)
+}
+
+/// The control and redirection operators of POSIX shells and Bash
+const SHELL_OPERATORS: [&str; 17] =
+    ["|", "||", "|&", "&", "&&", ";", ";;", "<", ">", ">>", "<<", "<<<", "<&", ">&", ">|", "&>", "&>>"];
+
+/// Whether a character can be part of a shell word
+fn is_word_char(ch: char) -> bool {
+    !ch.is_whitespace() && !matches!(ch, '|' | '&' | ';' | '<' | '>' | '(' | ')' | '"' | '\'')
+}
+
+/// Lexes a string in single quotes, in which nothing is escaped
+fn single_quoted<'a>(lexer: &mut Lexer<'a>) -> Option<TokenKind<'a>> {
+    let contents = lexer.remaining().strip_prefix('\'')?;
+    match contents.find('\'') {
+        Some(len) => {
+            lexer.advance(len + 2);
+            Some(TokenKind::Str(&contents[..len]))
+        },
+        None => {
+            lexer.advance(contents.len() + 1);
+            Some(TokenKind::Error(LexErrorKind::UnterminatedString))
+        }
+    }
+}
+
+/// Lexes a number with an optional sign, fraction and exponent
+fn decimal_number<'a>(lexer: &mut Lexer<'a>) -> Option<TokenKind<'a>> {
+    let text = lexer.remaining();
+    let (len, fractional) = decimal_len(text)?;
+    lexer.advance(len);
+    Some(number_kind(&text[..len], text[..len].parse().ok().filter(|_| !fractional)))
+}
+
+/// The length of the decimal number a string starts with, and whether it
+/// has a fraction or exponent
+fn decimal_len(text: &str) -> Option<(usize, bool)> {
+    let bytes = text.as_bytes();
+    let mut len = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let digits = |from: usize| bytes[from..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let integer = digits(len);
+    if integer == 0 {
+        return None;
+    }
+    len += integer;
+    let mut fractional = false;
+    if bytes.get(len) == Some(&b'.') && digits(len + 1) > 0 {
+        len += 1 + digits(len + 1);
+        fractional = true;
+    }
+    if matches!(bytes.get(len), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(len + 1), Some(b'+' | b'-')));
+        let exponent = digits(len + 1 + sign);
+        if exponent > 0 {
+            len += 1 + sign + exponent;
+            fractional = true;
+        }
+    }
+    Some((len, fractional))
+}
+
+/// A `Num` token for a number with an integer value, or else a `Float`
+/// token with its text
+fn number_kind(text: &str, value: Option<i32>) -> TokenKind<'_> {
+    match value {
+        Some(value) => TokenKind::Num(value),
+        None => TokenKind::Float(text)
+    }
+}
+
+/// Lexes a number starting with `-`, which the lexer doesn't start numbers at
+fn negative_number<'a>(lexer: &mut Lexer<'a>) -> Option<TokenKind<'a>> {
+    let mut rest = lexer.remaining().chars();
+    match (rest.next(), rest.next()) {
+        (Some('-'), Some('0'..='9')) => decimal_number(lexer),
+        _ => None
+    }
+}
+
+/// Profiles looked up by name at runtime, for tools handling many languages
+///
+/// Starts out with the profiles of this module, named `json`, `ini`, `sql`,
+/// `lisp`, `c-like` and `shell`, for tools to add their own to. Names match
+/// ignoring case, and with `_` matching `-`, so `C_Like` finds `c-like`.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    /// The profiles by their normalized names
+    profiles: BTreeMap<String, LexerBuilder>
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register("json", json());
+        registry.register("ini", ini());
+        registry.register("sql", sql());
+        registry.register("lisp", lisp());
+        registry.register("c-like", c_like());
+        registry.register("shell", shell());
+        registry
+    }
+}
+
+impl Registry {
+    /// Creates a registry of the profiles of this module
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with no profiles
+    pub fn empty() -> Self {
+        Self { profiles: BTreeMap::new() }
+    }
+
+    /// The form of a name profiles are stored under
+    fn normalize(name: &str) -> String {
+        name.chars().map(|ch| if ch == '_' { '-' } else { ch.to_ascii_lowercase() }).collect()
+    }
+
+    /// Adds a profile, returning the one previously registered under the
+    /// name
+    pub fn register(&mut self, name: &str, profile: LexerBuilder) -> Option<LexerBuilder> {
+        self.profiles.insert(Self::normalize(name), profile)
+    }
+
+    /// Removes a profile, returning it
+    pub fn remove(&mut self, name: &str) -> Option<LexerBuilder> {
+        self.profiles.remove(&Self::normalize(name))
+    }
+
+    /// Looks up a profile by name
+    pub fn get(&self, name: &str) -> Option<&LexerBuilder> {
+        self.profiles.get(&Self::normalize(name))
+    }
+
+    /// Creates a lexer with a profile looked up by name
+    pub fn build<'a>(&self, name: &str, source: &'a str) -> Option<Lexer<'a>> {
+        self.get(name).map(|profile| profile.build(source))
+    }
+
+    /// The names of the profiles, in lowercase and sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lexes a source with a profile into its kinds
+    fn kinds(profile: LexerBuilder, source: &str) -> Vec<TokenKind<'_>> {
+        profile.build(source).map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn json_values() {
+        assert_eq!(kinds(json(), "{\"a\": [true, null]}"), [
+            TokenKind::Punct("{"),
+            TokenKind::Str("a"),
+            TokenKind::Punct(":"),
+            TokenKind::Punct("["),
+            TokenKind::Keyword("true"),
+            TokenKind::Punct(","),
+            TokenKind::Keyword("null"),
+            TokenKind::Punct("]"),
+            TokenKind::Punct("}")
+        ]);
+    }
+
+    #[test]
+    fn json_numbers() {
+        assert_eq!(kinds(json(), "1e5 1E-3 1.5e+2 3000000000"), [
+            TokenKind::Float("1e5"),
+            TokenKind::Float("1E-3"),
+            TokenKind::Float("1.5e+2"),
+            TokenKind::Float("3000000000")
+        ]);
+        // An exponent or fraction without digits isn't part of the number
+        assert_eq!(kinds(json(), "2e 1."), [
+            TokenKind::Num(2),
+            TokenKind::Ident("e".into()),
+            TokenKind::Num(1),
+            TokenKind::Error(LexErrorKind::UnexpectedChar('.'))
+        ]);
+    }
+
+    #[test]
+    fn json_signs() {
+        assert_eq!(kinds(json(), "-3 -0.5 - 3 +1"), [
+            TokenKind::Num(-3),
+            TokenKind::Float("-0.5"),
+            TokenKind::Error(LexErrorKind::UnexpectedChar('-')),
+            TokenKind::Num(3),
+            TokenKind::Error(LexErrorKind::UnexpectedChar('+')),
+            TokenKind::Num(1)
+        ]);
+    }
+
+    #[test]
+    fn json_leading_zeros() {
+        assert_eq!(kinds(json(), "0 0.5 012 -012"), [
+            TokenKind::Num(0),
+            TokenKind::Float("0.5"),
+            TokenKind::Num(0),
+            TokenKind::Num(12),
+            TokenKind::Num(0),
+            TokenKind::Num(12)
+        ]);
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(kinds(json(), r#""a\"b" "open"#), [
+            TokenKind::Str(r#"a\"b"#),
+            TokenKind::Error(LexErrorKind::UnterminatedString)
+        ]);
+    }
+
+    #[test]
+    fn ini_entries() {
+        assert_eq!(kinds(ini(), "[a.b]\nkey-1 = 'x' ; c\n# d\n"), [
+            TokenKind::Punct("["),
+            TokenKind::Ident("a".into()),
+            TokenKind::Punct("."),
+            TokenKind::Ident("b".into()),
+            TokenKind::Punct("]"),
+            TokenKind::Newline,
+            TokenKind::Ident("key-1".into()),
+            TokenKind::Punct("="),
+            TokenKind::Str("x"),
+            TokenKind::Newline
+        ]);
+    }
+
+    #[test]
+    fn ini_numbers() {
+        assert_eq!(kinds(ini(), "n = [-2, 1e3, -0.5E-1]"), [
+            TokenKind::Ident("n".into()),
+            TokenKind::Punct("="),
+            TokenKind::Punct("["),
+            TokenKind::Num(-2),
+            TokenKind::Punct(","),
+            TokenKind::Float("1e3"),
+            TokenKind::Punct(","),
+            TokenKind::Float("-0.5E-1"),
+            TokenKind::Punct("]"),
+            TokenKind::Newline
+        ]);
+    }
+
+    #[test]
+    fn ini_unterminated_string() {
+        assert_eq!(kinds(ini(), "s = \"open"), [
+            TokenKind::Ident("s".into()),
+            TokenKind::Punct("="),
+            TokenKind::Error(LexErrorKind::UnterminatedString),
+            TokenKind::Newline
+        ]);
+    }
+
+    #[test]
+    fn sql_query() {
+        assert_eq!(kinds(sql(), "select * FROM t Where a != 'it''s' || b -- c"), [
+            TokenKind::Keyword("select"),
+            TokenKind::Opr(Op::Multiply),
+            TokenKind::Keyword("FROM"),
+            TokenKind::Ident("t".into()),
+            TokenKind::Keyword("Where"),
+            TokenKind::Ident("a".into()),
+            TokenKind::Opr(Op::NotEqual),
+            TokenKind::Str("it''s"),
+            TokenKind::Punct("||"),
+            TokenKind::Ident("b".into())
+        ]);
+    }
+
+    #[test]
+    fn sql_numbers() {
+        // A `-` is an operator rather than part of the number
+        assert_eq!(kinds(sql(), "1.5e3 -2 007"), [
+            TokenKind::Float("1.5e3"),
+            TokenKind::Opr(Op::Minus),
+            TokenKind::Num(2),
+            TokenKind::Num(7)
+        ]);
+    }
+
+    #[test]
+    fn sql_unterminated() {
+        assert_eq!(kinds(sql(), "'it''s"), [TokenKind::Error(LexErrorKind::UnterminatedString)]);
+        assert_eq!(kinds(sql(), "1 /* c"), [TokenKind::Num(1), TokenKind::Error(LexErrorKind::UnterminatedComment)]);
+    }
+
+    #[test]
+    fn lisp_symbols() {
+        assert_eq!(kinds(lisp(), "(define (f x) (* x 2)) ; c"), [
+            TokenKind::OpeningBracket,
+            TokenKind::Ident("define".into()),
+            TokenKind::OpeningBracket,
+            TokenKind::Ident("f".into()),
+            TokenKind::Ident("x".into()),
+            TokenKind::ClosingBracket,
+            TokenKind::OpeningBracket,
+            TokenKind::Ident("*".into()),
+            TokenKind::Ident("x".into()),
+            TokenKind::Num(2),
+            TokenKind::ClosingBracket,
+            TokenKind::ClosingBracket
+        ]);
+    }
+
+    #[test]
+    fn lisp_signs() {
+        // A sign is part of a number if a digit follows, and else starts a
+        // symbol
+        assert_eq!(kinds(lisp(), "-1.5e2 +5 - -x list->vector"), [
+            TokenKind::Float("-1.5e2"),
+            TokenKind::Num(5),
+            TokenKind::Ident("-".into()),
+            TokenKind::Ident("-x".into()),
+            TokenKind::Ident("list->vector".into())
+        ]);
+    }
+
+    #[test]
+    fn lisp_unterminated_string() {
+        assert_eq!(kinds(lisp(), "(f \"open)"), [
+            TokenKind::OpeningBracket,
+            TokenKind::Ident("f".into()),
+            TokenKind::Error(LexErrorKind::UnterminatedString)
+        ]);
+    }
+
+    #[test]
+    fn c_numbers() {
+        assert_eq!(kinds(c_like(), "0 0x1F 017 10UL 1.5f 1e3 -1"), [
+            TokenKind::Num(0),
+            TokenKind::Num(31),
+            TokenKind::Num(15),
+            TokenKind::Num(10),
+            TokenKind::Float("1.5f"),
+            TokenKind::Float("1e3"),
+            TokenKind::Opr(Op::Minus),
+            TokenKind::Num(1)
+        ]);
+        // Hexadecimal numbers up to `u32::MAX` keep their bits
+        assert_eq!(kinds(c_like(), "0xFFFFFFFF"), [TokenKind::Num(-1)]);
+    }
+
+    #[test]
+    fn c_char_literals() {
+        assert_eq!(kinds(c_like(), r"'a' '\n' '\x41' '\0' '\''"), [
+            TokenKind::Num(97),
+            TokenKind::Num(10),
+            TokenKind::Num(65),
+            TokenKind::Num(0),
+            TokenKind::Num(39)
+        ]);
+        // Malformed literals are unexpected characters
+        assert_eq!(kinds(c_like(), "'ab'"), [
+            TokenKind::Error(LexErrorKind::UnexpectedChar('\'')),
+            TokenKind::Ident("ab".into()),
+            TokenKind::Error(LexErrorKind::UnexpectedChar('\''))
+        ]);
+    }
+
+    #[test]
+    fn c_operators() {
+        assert_eq!(kinds(c_like(), "a <<= b->c == d ... e != f++"), [
+            TokenKind::Ident("a".into()),
+            TokenKind::Punct("<<="),
+            TokenKind::Ident("b".into()),
+            TokenKind::Punct("->"),
+            TokenKind::Ident("c".into()),
+            TokenKind::Opr(Op::Equal),
+            TokenKind::Ident("d".into()),
+            TokenKind::Punct("..."),
+            TokenKind::Ident("e".into()),
+            TokenKind::Opr(Op::NotEqual),
+            TokenKind::Ident("f".into()),
+            TokenKind::Opr(Op::Increment)
+        ]);
+    }
+
+    #[test]
+    fn c_unterminated() {
+        assert_eq!(kinds(c_like(), "\"open"), [TokenKind::Error(LexErrorKind::UnterminatedString)]);
+        assert_eq!(kinds(c_like(), "/* open"), [TokenKind::Error(LexErrorKind::UnterminatedComment)]);
+    }
+
+    #[test]
+    fn shell_words() {
+        assert_eq!(kinds(shell(), "ls -la $HOME | grep \"a b\" 2>> f # c"), [
+            TokenKind::Ident("ls".into()),
+            TokenKind::Ident("-la".into()),
+            TokenKind::Ident("$HOME".into()),
+            TokenKind::Punct("|"),
+            TokenKind::Ident("grep".into()),
+            TokenKind::Str("a b"),
+            TokenKind::Ident("2".into()),
+            TokenKind::Punct(">>"),
+            TokenKind::Ident("f".into()),
+            TokenKind::Newline
+        ]);
+        // `#` only starts a comment at the start of a word
+        assert_eq!(kinds(shell(), "a#b"), [TokenKind::Ident("a#b".into()), TokenKind::Newline]);
+    }
+
+    #[test]
+    fn shell_quotes() {
+        assert_eq!(kinds(shell(), r#"'c\d' "e\"f""#), [TokenKind::Str(r"c\d"), TokenKind::Str(r#"e\"f"#), TokenKind::Newline]);
+        assert_eq!(kinds(shell(), "echo 'open"), [
+            TokenKind::Ident("echo".into()),
+            TokenKind::Error(LexErrorKind::UnterminatedString),
+            TokenKind::Newline
+        ]);
+    }
+
+    #[test]
+    fn shell_lines() {
+        assert_eq!(kinds(shell(), "a;b \\\n c\nd"), [
+            TokenKind::Ident("a".into()),
+            TokenKind::Punct(";"),
+            TokenKind::Ident("b".into()),
+            TokenKind::Ident("c".into()),
+            TokenKind::Newline,
+            TokenKind::Ident("d".into()),
+            TokenKind::Newline
+        ]);
+    }
+}
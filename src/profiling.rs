@@ -0,0 +1,135 @@
+//! Timing the lexing of each class of token.
+use crate::{Lexer, TokenKind};
+use core::fmt;
+use std::time::{Duration, Instant};
+
+/// A broad class of token, as lexing time is broken down by in a
+/// [`LexProfile`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenClass {
+    /// Identifiers, symbols and keywords
+    Ident,
+    /// Integers and floats
+    Number,
+    /// String literals and template text
+    String,
+    /// Operators, punctuation and brackets
+    Operator,
+    /// Newlines, indents and dedents
+    Layout,
+    /// Comments, whether or not they're produced as tokens
+    Comment,
+    /// Runs of whitespace skipped between tokens
+    Whitespace,
+    /// Lexical errors
+    Error
+}
+
+impl TokenClass {
+    /// Every class
+    pub const ALL: [Self; 8] =
+        [Self::Ident, Self::Number, Self::String, Self::Operator, Self::Layout, Self::Comment, Self::Whitespace, Self::Error];
+
+    /// The class of a kind of token
+    pub fn of(kind: &TokenKind) -> Self {
+        match kind {
+            TokenKind::Ident(_) | TokenKind::Symbol(_) | TokenKind::Keyword(_) => Self::Ident,
+            TokenKind::Num(_) | TokenKind::Float(_) => Self::Number,
+            TokenKind::Str(_) | TokenKind::Text(_) => Self::String,
+            TokenKind::Opr(_) | TokenKind::Punct(_) | TokenKind::OpeningBracket | TokenKind::ClosingBracket => Self::Operator,
+            TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent => Self::Layout,
+            TokenKind::Comment(_) => Self::Comment,
+            TokenKind::Whitespace(_) => Self::Whitespace,
+            TokenKind::Error(_) => Self::Error
+        }
+    }
+
+    /// The class's name, e.g. `"ident"`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ident => "ident",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Operator => "operator",
+            Self::Layout => "layout",
+            Self::Comment => "comment",
+            Self::Whitespace => "whitespace",
+            Self::Error => "error"
+        }
+    }
+}
+
+impl fmt::Display for TokenClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// The time a lexer spent on each class of token and how many it lexed,
+/// returned by [`Lexer::profile`]
+///
+/// Displays as a table of the classes, slowest first. Timing every token
+/// slows lexing, so the absolute times are inflated, but the shares show
+/// which rules of a language are worth optimizing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LexProfile {
+    /// The number of tokens of each class, or of runs of whitespace
+    counts: [usize; TokenClass::ALL.len()],
+    /// The time spent lexing each class
+    times: [Duration; TokenClass::ALL.len()]
+}
+
+impl LexProfile {
+    /// The number of tokens lexed of a class, or of runs of whitespace
+    /// skipped
+    #[inline]
+    pub fn count(&self, class: TokenClass) -> usize {
+        self.counts[class as usize]
+    }
+
+    /// The time spent lexing tokens of a class
+    #[inline]
+    pub fn time(&self, class: TokenClass) -> Duration {
+        self.times[class as usize]
+    }
+
+    /// The time spent lexing tokens of every class
+    pub fn total_time(&self) -> Duration {
+        self.times.iter().sum()
+    }
+
+    /// Adds the time spent on a token of a class since an instant
+    pub(crate) fn record(&mut self, class: TokenClass, started: Instant) {
+        self.counts[class as usize] += 1;
+        self.times[class as usize] += started.elapsed();
+    }
+}
+
+impl fmt::Display for LexProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.total_time().as_secs_f64();
+        let mut classes = TokenClass::ALL;
+        classes.sort_by_key(|&class| core::cmp::Reverse(self.time(class)));
+
+        writeln!(f, "{:<12}{:>10}{:>14}{:>8}{:>12}", "class", "count", "time", "share", "per token")?;
+        for class in classes.into_iter().filter(|&class| self.count(class) > 0) {
+            let (count, time) = (self.count(class), self.time(class));
+            let share = if total > 0.0 { time.as_secs_f64() / total * 100.0 } else { 0.0 };
+            let per_token = time / u32::try_from(count).unwrap_or(u32::MAX);
+            writeln!(f, "{class:<12}{count:>10}{time:>14.2?}{share:>7.1}%{per_token:>12.0?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Lexer<'_> {
+    /// The time spent on each class of token so far, and how many were
+    /// lexed
+    ///
+    /// Tokens already peeked count as lexed, and tokens lexed again after
+    /// rewinding to a checkpoint count again.
+    #[inline]
+    pub fn profile(&self) -> &LexProfile {
+        &self.profile
+    }
+}
@@ -0,0 +1,100 @@
+//! Lexing into a project's own token type.
+use crate::{LexErrorKind, Lexer, Op, Span, TokenKind};
+use alloc::borrow::Cow;
+
+/// Classifies lexed tokens into a project's own token type
+///
+/// Every hook defaults to [`TokenRule::other`], so only the classes a project
+/// cares about need to be overridden.
+pub trait TokenRule<'a> {
+    /// The token type produced
+    type Token;
+
+    /// Classifies any token without a more specific hook
+    fn other(&mut self, kind: TokenKind<'a>) -> Self::Token;
+
+    /// Classifies an identifier
+    fn ident(&mut self, ident: Cow<'a, str>) -> Self::Token {
+        self.other(TokenKind::Ident(ident))
+    }
+
+    /// Classifies a keyword
+    fn keyword(&mut self, keyword: &'a str) -> Self::Token {
+        self.other(TokenKind::Keyword(keyword))
+    }
+
+    /// Classifies an operator
+    fn operator(&mut self, op: Op) -> Self::Token {
+        self.other(TokenKind::Opr(op))
+    }
+
+    /// Classifies an operator registered at runtime
+    fn punct(&mut self, symbol: &'a str) -> Self::Token {
+        self.other(TokenKind::Punct(symbol))
+    }
+
+    /// Classifies a number literal
+    fn number(&mut self, value: i32) -> Self::Token {
+        self.other(TokenKind::Num(value))
+    }
+
+    /// Classifies a string literal
+    fn string(&mut self, contents: &'a str) -> Self::Token {
+        self.other(TokenKind::Str(contents))
+    }
+
+    /// Classifies a lexical error
+    fn error(&mut self, error: LexErrorKind) -> Self::Token {
+        self.other(TokenKind::Error(error))
+    }
+}
+
+/// A lexer producing tokens classified by a [`TokenRule`]
+///
+/// Yields each token along with the bytes it was lexed from.
+#[derive(Debug, Clone)]
+pub struct RuleLexer<'a, R> {
+    lexer: Lexer<'a>,
+    rule: R
+}
+
+impl<'a, R> RuleLexer<'a, R> {
+    /// The underlying lexer
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'a> {
+        &self.lexer
+    }
+
+    /// The rule tokens are classified with
+    #[inline]
+    pub fn rule(&self) -> &R {
+        &self.rule
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Classifies every token with a [`TokenRule`]
+    pub fn with_rule<R: TokenRule<'a>>(self, rule: R) -> RuleLexer<'a, R> {
+        RuleLexer { lexer: self, rule }
+    }
+}
+
+impl<'a, R: TokenRule<'a>> Iterator for RuleLexer<'a, R> {
+    type Item = (R::Token, Span);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.lexer.next()?;
+        let kind = match token.kind {
+            TokenKind::Ident(ident) => self.rule.ident(ident),
+            TokenKind::Keyword(keyword) => self.rule.keyword(keyword),
+            TokenKind::Opr(op) => self.rule.operator(op),
+            TokenKind::Punct(symbol) => self.rule.punct(symbol),
+            TokenKind::Num(value) => self.rule.number(value),
+            TokenKind::Str(contents) => self.rule.string(contents),
+            TokenKind::Error(error) => self.rule.error(error),
+            kind => self.rule.other(kind)
+        };
+
+        Some((kind, token.span))
+    }
+}
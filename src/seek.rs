@@ -0,0 +1,64 @@
+//! Moving a lexer to any point of its source.
+use crate::layout::Layout;
+use crate::{Lexer, LineIndex, BOM};
+
+impl<'a> Lexer<'a> {
+    /// Moves the lexer to a byte offset of the file, e.g. to lex only the
+    /// part of a source visible in an editor
+    ///
+    /// The lexer continues from there as if it started there, with its
+    /// position found from the lines before and no modes, indentation or
+    /// open delimiters, so the offset should be the start of a token at the
+    /// top level, such as the start of a line outside any string or
+    /// comment. Peeked tokens are dropped. Returns `false`, leaving the lexer
+    /// as it was, if the offset is outside the source or doesn't fall on a
+    /// character boundary.
+    ///
+    /// The source's lines are found on every call, so to seek many times use
+    /// [`Lexer::seek_indexed`].
+    pub fn seek(&mut self, offset: usize) -> bool {
+        let index = LineIndex::new(self.source).with_lone_cr_newlines(self.config.lone_cr_newlines);
+        self.seek_indexed(offset, &index)
+    }
+
+    /// Moves the lexer to a byte offset of the file, finding its position
+    /// with a line index of the source
+    ///
+    /// See [`Lexer::seek`]. Also returns `false` if the index isn't of this
+    /// lexer's source, or breaks lines differently from the lexer, as set by
+    /// [`LexerBuilder::lone_cr_newlines`](crate::LexerBuilder::lone_cr_newlines).
+    pub fn seek_indexed(&mut self, offset: usize, index: &LineIndex<'a>) -> bool {
+        let Some(pos) = offset.checked_sub(self.offset).filter(|&pos| self.source.is_char_boundary(pos)) else {
+            return false;
+        };
+        if !index.indexes(self.source, self.config.lone_cr_newlines) {
+            return false;
+        }
+
+        // The start of the source is the start of its first line, past a
+        // byte-order mark the lexer skips, and only columns within the line
+        // are walked
+        let bom = if self.config.skip_bom && self.source.starts_with(BOM) {
+            BOM.len_utf8()
+        } else {
+            0
+        };
+        let line = index.line_of(pos);
+        if line == 0 {
+            self.move_to(bom);
+            (self.row, self.col) = self.origin;
+        } else {
+            self.move_to(index.line_start(line));
+            (self.row, self.col) = (self.origin.0 + line, 1);
+        }
+        self.advance_to(pos);
+        self.bom = if pos == 0 { bom } else { 0 };
+
+        self.modes = Self::initial_modes(&self.config);
+        self.layout = Layout::default();
+        self.after_operand = false;
+        self.depth = 0;
+        self.lookahead.clear();
+        true
+    }
+}
@@ -0,0 +1,178 @@
+//! Exporting tokens as LSP semantic tokens.
+use crate::{LineIndex, PositionEncoding, Token, TokenKind};
+use alloc::vec::Vec;
+
+/// Which kinds of token a rule of a [`SemanticTokenMap`] applies to
+pub type KindFilter = fn(&TokenKind) -> bool;
+
+/// How tokens are highlighted by a language server, and the legend it
+/// advertises for them
+///
+/// Starts out mapping keywords, identifiers, operators, literals, mode text
+/// and comments to the standard LSP token types, for languages to override
+/// with [`SemanticTokenMap::with`]. Tokens no rule applies to aren't
+/// highlighted.
+#[derive(Debug, Clone)]
+pub struct SemanticTokenMap {
+    /// The token types of the legend, in the order they're indexed by
+    types: Vec<&'static str>,
+    /// The token modifiers of the legend, in the order of their bits
+    modifiers: Vec<&'static str>,
+    /// The kinds each rule applies to, with its type index and modifier bits
+    rules: Vec<(KindFilter, u32, u32)>
+}
+
+impl Default for SemanticTokenMap {
+    fn default() -> Self {
+        Self::empty()
+            .with(|kind| kind.is_keyword(), "keyword", &[])
+            .with(|kind| kind.is_ident(), "variable", &[])
+            .with(|kind| kind.is_operator(), "operator", &[])
+            .with(|kind| matches!(kind, TokenKind::Num(_) | TokenKind::Float(_)), "number", &[])
+            .with(|kind| matches!(kind, TokenKind::Str(_) | TokenKind::Text(_)), "string", &[])
+            .with(|kind| matches!(kind, TokenKind::Comment(_)), "comment", &[])
+    }
+}
+
+impl SemanticTokenMap {
+    /// Creates a map with the default rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a map highlighting no tokens
+    pub fn empty() -> Self {
+        Self { types: Vec::new(), modifiers: Vec::new(), rules: Vec::new() }
+    }
+
+    /// Highlights the tokens `applies` to as a token type with modifiers,
+    /// taking priority over the rules before it
+    ///
+    /// Types and modifiers are added to the legend the first time they're
+    /// used.
+    pub fn with(mut self, applies: KindFilter, token_type: &'static str, modifiers: &[&'static str]) -> Self {
+        let token_type = legend_index(&mut self.types, token_type);
+        let modifiers = modifiers.iter().fold(0, |bits, modifier| bits | 1 << legend_index(&mut self.modifiers, modifier));
+        self.rules.push((applies, token_type, modifiers));
+        self
+    }
+
+    /// The token types of the legend, as advertised in the server's
+    /// capabilities
+    #[inline]
+    pub fn token_types(&self) -> &[&'static str] {
+        &self.types
+    }
+
+    /// The token modifiers of the legend, as advertised in the server's
+    /// capabilities
+    #[inline]
+    pub fn token_modifiers(&self) -> &[&'static str] {
+        &self.modifiers
+    }
+
+    /// The type index and modifier bits a kind of token is highlighted with
+    pub fn classify(&self, kind: &TokenKind) -> Option<(u32, u32)> {
+        self.rules.iter().rev().find(|(applies, ..)| applies(kind)).map(|&(_, token_type, modifiers)| (token_type, modifiers))
+    }
+
+    /// Encodes tokens lexed from `source` as the `data` of an LSP
+    /// `SemanticTokens` response
+    ///
+    /// Each highlighted token becomes five integers: its line relative to the
+    /// previous token's, its start character (relative to the previous
+    /// token's if on the same line), its length, its type index and its
+    /// modifier bits. Lines and characters are 0-based, with characters
+    /// counted in the encoding negotiated with the client, usually
+    /// [`PositionEncoding::Utf16`]. Tokens spanning several lines, like block
+    /// comments, are split into one token per line.
+    ///
+    /// The tokens must be in source order, as a lexer produces them.
+    pub fn encode<'t, 'a: 't>(&self, source: &str, encoding: PositionEncoding, tokens: impl IntoIterator<Item = &'t Token<'a>>) -> Vec<u32> {
+        let index = LineIndex::new(source).with_base(0, 0).with_encoding(encoding).with_lone_cr_newlines(true);
+        let mut data = Vec::new();
+        let mut prev = (0, 0);
+
+        for token in tokens {
+            let Some((token_type, modifiers)) = self.classify(&token.kind) else {
+                continue;
+            };
+            let (Some(start), Some(end)) = (index.position(token.span.start), index.position(token.span.end)) else {
+                continue;
+            };
+
+            for line in start.0..=end.0 {
+                let from = if line == start.0 { start.1 } else { 0 };
+                let to = match line == end.0 {
+                    true => end.1,
+                    false => index.position(index.line_range(line).1).map_or(from, |(_, col)| col)
+                };
+                if to <= from {
+                    continue;
+                }
+
+                let delta_start = if line == prev.0 { from - prev.1 } else { from };
+                data.extend([(line - prev.0) as u32, delta_start as u32, (to - from) as u32, token_type, modifiers]);
+                prev = (line, from);
+            }
+        }
+        data
+    }
+}
+
+/// The index of a name in a legend, adding it if missing
+fn legend_index(legend: &mut Vec<&'static str>, name: &'static str) -> u32 {
+    match legend.iter().position(|&existing| existing == name) {
+        Some(index) => index as u32,
+        None => {
+            legend.push(name);
+            legend.len() as u32 - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LexerBuilder;
+
+    #[test]
+    fn default_legend() {
+        let map = SemanticTokenMap::new();
+        assert_eq!(map.token_types(), ["keyword", "variable", "operator", "number", "string", "comment"]);
+        assert!(map.token_modifiers().is_empty());
+        assert_eq!(map.classify(&TokenKind::Float("1.5")), Some((3, 0)));
+        assert_eq!(map.classify(&TokenKind::Newline), None);
+    }
+
+    #[test]
+    fn later_rules_take_priority() {
+        let map = SemanticTokenMap::new()
+            .with(|kind| matches!(kind, TokenKind::Keyword("self")), "variable", &["readonly", "defaultLibrary"])
+            .with(|kind| matches!(kind, TokenKind::Punct(_)), "operator", &["defaultLibrary"]);
+        assert_eq!(map.token_types().len(), 6);
+        assert_eq!(map.token_modifiers(), ["readonly", "defaultLibrary"]);
+        assert_eq!(map.classify(&TokenKind::Keyword("self")), Some((1, 0b11)));
+        assert_eq!(map.classify(&TokenKind::Keyword("if")), Some((0, 0)));
+        assert_eq!(map.classify(&TokenKind::Punct("::")), Some((2, 0b10)));
+    }
+
+    #[test]
+    fn encodes_relative_positions() {
+        let source = "let x = 1 /* a\nbc */ \u{1F600}y";
+        let builder = LexerBuilder::new().keywords(["let"]).block_comments([("/*", "*/")]).trivia(true);
+        let tokens: Vec<_> = builder.build(source).collect();
+        let data = SemanticTokenMap::new().encode(source, PositionEncoding::Utf16, &tokens);
+        assert_eq!(data, [
+            0, 0, 3, 0, 0,
+            0, 4, 1, 1, 0,
+            0, 2, 1, 2, 0,
+            0, 2, 1, 3, 0,
+            // The block comment is split at the line break
+            0, 2, 4, 5, 0,
+            1, 0, 5, 5, 0,
+            // The emoji isn't highlighted, but takes up two UTF-16 units
+            0, 8, 1, 1, 0
+        ]);
+    }
+}
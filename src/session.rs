@@ -0,0 +1,198 @@
+//! Lexing sources along with the sources they include.
+use crate::{LexerBuilder, OwnedToken, Span, TokenKind};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Why a source couldn't be included
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IncludeErrorKind {
+    /// A directive not followed by a string naming the source to include
+    MissingName,
+    /// A source the loader couldn't load
+    NotFound(String),
+    /// A source including itself, directly or through others
+    Cycle(String),
+    /// Sources included inside more than the configured number of others
+    TooDeep
+}
+
+impl fmt::Display for IncludeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingName => f.write_str("include directive without a source name"),
+            Self::NotFound(name) => write!(f, "cannot find `{name}`"),
+            Self::Cycle(name) => write!(f, "`{name}` includes itself"),
+            Self::TooDeep => f.write_str("includes nest too deeply")
+        }
+    }
+}
+
+/// An include directive that couldn't be followed, along with where it is
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IncludeError {
+    /// What went wrong
+    pub kind: IncludeErrorKind,
+    /// The directive's position in its source
+    pub position: (usize, usize),
+    /// The bytes of the directive
+    pub span: Span,
+    /// The name of the source the directive is in
+    pub source_name: Arc<str>
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (row, col) = self.position;
+        write!(f, "{} at {}:{row}:{col}", self.kind, self.source_name)
+    }
+}
+
+impl core::error::Error for IncludeError {}
+
+/// A token lexed by an [`IncludeSession`], along with the source it's from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionToken {
+    /// The index of the source the token was lexed from, as given to
+    /// [`IncludeSession::source_name`]
+    pub source: usize,
+    /// The token, positioned in its own source
+    pub token: OwnedToken
+}
+
+/// Lexes sources with include directives, splicing in the tokens of the
+/// sources they include, as a preprocessor does
+///
+/// A directive is a token lexed from the configured text, e.g. a keyword
+/// like `include` or `import`, followed by a string literal naming the
+/// source to include. The directive and the name are replaced by the
+/// included source's tokens, which are positioned in that source, so
+/// errors can be reported against the right file. Sources are loaded once
+/// by name with a callback, e.g. reading a file relative to an include
+/// path, and a source included twice is spliced in twice.
+pub struct IncludeSession<F> {
+    /// The settings every source is lexed with
+    builder: LexerBuilder,
+    /// The text of a directive token
+    directive: String,
+    /// Loads a source by name
+    load: F,
+    /// The most sources a source can be included inside
+    max_depth: usize,
+    /// The names and text of the sources lexed so far
+    sources: Vec<(Arc<str>, Arc<str>)>
+}
+
+impl<F> fmt::Debug for IncludeSession<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<_> = self.sources.iter().map(|(name, _)| name).collect();
+        f.debug_struct("IncludeSession")
+            .field("builder", &self.builder)
+            .field("directive", &self.directive)
+            .field("max_depth", &self.max_depth)
+            .field("sources", &names)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: FnMut(&str) -> Option<String>> IncludeSession<F> {
+    /// The depth includes are limited to by default
+    const DEFAULT_MAX_DEPTH: usize = 64;
+
+    /// Creates a session lexing sources with a builder's settings, following
+    /// directives lexed from `directive` by loading sources with `load`
+    pub fn new(builder: LexerBuilder, directive: impl Into<String>, load: F) -> Self {
+        Self { builder, directive: directive.into(), load, max_depth: Self::DEFAULT_MAX_DEPTH, sources: Vec::new() }
+    }
+
+    /// Limits how many sources a source can be included inside
+    ///
+    /// Defaults to 64.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Lexes a named source, splicing in the tokens of the sources it
+    /// includes
+    ///
+    /// Returns an error at the first directive that can't be followed.
+    pub fn lex(&mut self, name: &str, source: impl Into<String>) -> Result<Vec<SessionToken>, IncludeError> {
+        let index = self.add(name, source.into());
+        let mut tokens = Vec::new();
+        self.splice(index, &mut Vec::new(), &mut tokens)?;
+        Ok(tokens)
+    }
+
+    /// The number of sources lexed so far, including those included
+    #[inline]
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// The name of a source, by its index
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no source with that index.
+    #[inline]
+    pub fn source_name(&self, source: usize) -> &str {
+        &self.sources[source].0
+    }
+
+    /// The text of a source, by its index
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no source with that index.
+    #[inline]
+    pub fn source(&self, source: usize) -> &str {
+        &self.sources[source].1
+    }
+
+    /// Adds a source, returning its index
+    fn add(&mut self, name: &str, text: String) -> usize {
+        self.sources.push((name.into(), text.into()));
+        self.sources.len() - 1
+    }
+
+    /// Lexes a source into `output`, following its directives
+    ///
+    /// `including` holds the sources the source is included inside.
+    fn splice(&mut self, index: usize, including: &mut Vec<usize>, output: &mut Vec<SessionToken>) -> Result<(), IncludeError> {
+        let (name, text) = self.sources[index].clone();
+        let mut lexer = self.builder.build_named(&text, Arc::clone(&name));
+
+        while let Some(token) = lexer.next() {
+            if text[token.span.start..token.span.end] != *self.directive {
+                output.push(SessionToken { source: index, token: token.into() });
+                continue;
+            }
+
+            let error = |kind| IncludeError { kind, position: token.position, span: token.span, source_name: Arc::clone(&name) };
+            let Some(TokenKind::Str(included)) = lexer.by_ref().map(|token| token.kind).find(|kind| !kind.is_trivia()) else {
+                return Err(error(IncludeErrorKind::MissingName));
+            };
+            if including.len() >= self.max_depth {
+                return Err(error(IncludeErrorKind::TooDeep));
+            }
+            let found = self.sources.iter().position(|(name, _)| **name == *included);
+            if found.is_some_and(|found| found == index || including.contains(&found)) {
+                return Err(error(IncludeErrorKind::Cycle(included.to_string())));
+            }
+            let included = match found {
+                Some(found) => found,
+                None => match (self.load)(included) {
+                    Some(text) => self.add(included, text),
+                    None => return Err(error(IncludeErrorKind::NotFound(included.to_string())))
+                }
+            };
+
+            including.push(index);
+            self.splice(included, including, output)?;
+            including.pop();
+        }
+        Ok(())
+    }
+}
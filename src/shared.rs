@@ -0,0 +1,95 @@
+//! Lexers owning their source, free of borrowed lifetimes.
+use crate::builder::Config;
+use crate::{Lexer, LexerBuilder, OwnedTokenKind, Span};
+use alloc::sync::Arc;
+
+/// A lexer owning its source through an [`Arc<str>`]
+///
+/// Its tokens share the source rather than borrowing it, so they can be
+/// kept or sent to other threads independently of the lexer.
+#[derive(Debug, Clone)]
+pub struct SharedLexer {
+    /// The lexer, borrowing `source` for as long as it's kept here
+    lexer: Lexer<'static>,
+    /// The source string being read, declared after the lexer so it outlives
+    /// it when dropped
+    source: Arc<str>
+}
+
+impl SharedLexer {
+    /// Creates a new lexer owning its source
+    pub fn new(source: impl Into<Arc<str>>) -> Self {
+        Self::with_config(source.into(), Arc::default())
+    }
+
+    /// Creates a new lexer owning its source using the given settings
+    fn with_config(source: Arc<str>, config: Arc<Config>) -> Self {
+        // SAFETY: the text of an `Arc<str>` doesn't move while the `Arc` is
+        // alive, and it's kept alongside the lexer and dropped after it. The
+        // lexer is only lent out for as long as `self` is borrowed, and tokens
+        // are made owned before they're returned.
+        let text: &'static str = unsafe { &*core::ptr::from_ref::<str>(&source) };
+        Self { lexer: Lexer::with_config(text, config), source }
+    }
+
+    /// The source string being read
+    #[inline]
+    pub fn source(&self) -> &Arc<str> {
+        &self.source
+    }
+
+    /// The lexer reading the source, for its comments, warnings and other
+    /// figures collected so far
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'_> {
+        &self.lexer
+    }
+}
+
+impl LexerBuilder {
+    /// Creates a lexer owning its source
+    pub fn build_shared(&self, source: impl Into<Arc<str>>) -> SharedLexer {
+        SharedLexer::with_config(source.into(), Arc::clone(&self.config))
+    }
+}
+
+impl Iterator for SharedLexer {
+    type Item = SharedToken;
+
+    fn next(&mut self) -> Option<SharedToken> {
+        let token = self.lexer.next()?;
+        Some(SharedToken {
+            kind: token.kind.into_owned(),
+            position: token.position,
+            span: token.span,
+            source: Arc::clone(&self.source)
+        })
+    }
+}
+
+/// A token sharing the source it was lexed from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SharedToken {
+    /// The token's kind
+    pub kind: OwnedTokenKind,
+    /// The token's position in file
+    pub position: (usize, usize),
+    /// The bytes the token was lexed from
+    pub span: Span,
+    /// The source string the token was lexed from
+    source: Arc<str>
+}
+
+impl SharedToken {
+    /// The source string the token was lexed from
+    #[inline]
+    pub fn source(&self) -> &Arc<str> {
+        &self.source
+    }
+
+    /// The exact source text the token was lexed from
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.source[self.span.start..self.span.end]
+    }
+}
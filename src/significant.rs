@@ -0,0 +1,36 @@
+//! Skipping trivia tokens.
+use crate::{Lexer, Token};
+
+/// A lexer without its whitespace and comment tokens, returned by
+/// [`Lexer::significant`]
+#[derive(Debug, Clone)]
+pub struct Significant<'a> {
+    lexer: Lexer<'a>
+}
+
+impl<'a> Significant<'a> {
+    /// The underlying lexer
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'a> {
+        &self.lexer
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Skips whitespace and comment tokens
+    ///
+    /// Lets a parser share a lexer configuration that keeps trivia for a
+    /// formatter.
+    #[inline]
+    pub fn significant(self) -> Significant<'a> {
+        Significant { lexer: self }
+    }
+}
+
+impl<'a> Iterator for Significant<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        self.lexer.by_ref().find(|token| !token.kind.is_trivia())
+    }
+}
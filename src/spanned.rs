@@ -0,0 +1,39 @@
+//! Pairing tokens with their source text.
+use crate::{Lexer, Token};
+
+/// A lexer producing each token with the text it was lexed from, returned
+/// by [`Lexer::spanned_text`]
+#[derive(Debug, Clone)]
+pub struct SpannedText<'a> {
+    lexer: Lexer<'a>
+}
+
+impl<'a> SpannedText<'a> {
+    /// The underlying lexer
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'a> {
+        &self.lexer
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Pairs each token with the exact source text it was lexed from
+    ///
+    /// For highlighters and formatters that need every token's spelling,
+    /// e.g. of a string literal's quotes and escapes, without slicing the
+    /// source themselves.
+    #[inline]
+    pub fn spanned_text(self) -> SpannedText<'a> {
+        SpannedText { lexer: self }
+    }
+}
+
+impl<'a> Iterator for SpannedText<'a> {
+    type Item = (Token<'a>, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.lexer.next()?;
+        let text = self.lexer.text(token.span);
+        Some((token, text))
+    }
+}
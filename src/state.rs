@@ -0,0 +1,111 @@
+//! Persisting a lexer's progress through a source string.
+use crate::layout::Layout;
+use crate::{Lexer, LexerBuilder, Mode};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// A lexer's progress through its source, to resume lexing it later
+///
+/// Unlike a [`Checkpoint`](crate::Checkpoint) a state doesn't borrow the
+/// source, so it can be kept across sessions (and serialized with the
+/// `serde` feature). It keeps where the source starts in its file and the
+/// source's name, so spans, positions and errors are the same once resumed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LexerState {
+    /// The byte offset of the current character
+    pub(crate) pos: usize,
+    /// The length of a skipped byte-order mark not yet produced as trivia
+    pub(crate) bom: usize,
+    /// The byte offset of the source within its file
+    pub(crate) offset: usize,
+    /// The name of the source, e.g. its path
+    name: Option<Arc<str>>,
+    /// The 1-based row the lexer is on
+    row: usize,
+    /// The 1-based column the lexer is on
+    col: usize,
+    /// The 1-based row and column the source starts at
+    origin: (usize, usize),
+    /// The stack of modes the lexer is in
+    modes: Vec<Mode>,
+    /// The lexer's progress through newlines and indentation
+    layout: Layout,
+    /// Whether the last token lexed can end an operand
+    after_operand: bool,
+    /// The number of delimiters open
+    depth: usize
+}
+
+impl LexerState {
+    /// The byte offset lexing resumes at
+    #[inline]
+    pub fn byte_offset(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Saves the lexer's progress
+    ///
+    /// Tokens already peeked count as lexed.
+    pub fn state(&self) -> LexerState {
+        LexerState {
+            pos: self.pos,
+            bom: self.bom,
+            offset: self.offset,
+            name: self.name.clone(),
+            row: self.row,
+            col: self.col,
+            origin: self.origin,
+            modes: self.modes.clone(),
+            layout: self.layout.clone(),
+            after_operand: self.after_operand,
+            depth: self.depth
+        }
+    }
+
+    /// Resumes lexing a source string with the default settings
+    ///
+    /// Returns `None` if the state doesn't fall on a character boundary of
+    /// the source.
+    pub fn from_state(source: &'a str, state: LexerState) -> Option<Self> {
+        let mut lexer = Self::new(source);
+        lexer.resume(state).then_some(lexer)
+    }
+
+    /// Moves the lexer to a saved state, returning whether it fell on a
+    /// character boundary of the source
+    pub(crate) fn resume(&mut self, state: LexerState) -> bool {
+        if !self.source.is_char_boundary(state.pos) {
+            return false;
+        }
+        self.move_to(state.pos);
+        self.bom = state.bom;
+        self.offset = state.offset;
+        #[cfg(feature = "tracing")]
+        if let Some(name) = &state.name {
+            self.span.record("name", &**name);
+        }
+        self.name = state.name;
+        self.row = state.row;
+        self.col = state.col;
+        self.origin = state.origin;
+        self.modes = state.modes;
+        self.layout = state.layout;
+        self.after_operand = state.after_operand;
+        self.depth = state.depth;
+        self.lookahead.clear();
+        true
+    }
+}
+
+impl LexerBuilder {
+    /// Resumes lexing a source string with these settings
+    ///
+    /// See [`Lexer::from_state`].
+    pub fn build_from_state<'a>(&self, source: &'a str, state: LexerState) -> Option<Lexer<'a>> {
+        let mut lexer = self.build(source);
+        lexer.resume(state).then_some(lexer)
+    }
+}
@@ -0,0 +1,186 @@
+//! Figures about a source collected while lexing it.
+use crate::{Delimiter, Lexer, TokenKind};
+
+/// The number of kinds of token, numbered by [`TokenKind::id`]
+const KIND_COUNT: usize = 17;
+
+/// The name of each kind of token, in the order of their ids
+const KIND_NAMES: [&str; KIND_COUNT] = [
+    "Opr", "Ident", "Symbol", "Keyword", "Punct", "Num", "Str", "Text", "Newline", "Indent", "Dedent", "Whitespace",
+    "Comment", "OpeningBracket", "ClosingBracket", "Error", "Float"
+];
+
+/// Figures about the source lexed so far, returned by [`Lexer::stats`]
+///
+/// Every line is either code, comments or blank. A line break at the end of
+/// the source doesn't start another line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LexStats {
+    /// The length of the source lexed, in bytes
+    pub bytes: usize,
+    /// The number of lines lexed
+    pub lines: usize,
+    /// The number of lines with a token other than a comment on them
+    pub code_lines: usize,
+    /// The number of lines with only comments on them
+    pub comment_lines: usize,
+    /// The number of lines of nothing but whitespace
+    pub blank_lines: usize,
+    /// The most delimiters open at once
+    pub max_depth: usize,
+    /// The number of tokens of each kind, by id
+    kinds: [usize; KIND_COUNT]
+}
+
+impl LexStats {
+    /// The number of tokens lexed of the same kind as a token, whatever it
+    /// holds
+    #[inline]
+    pub fn count(&self, kind: &TokenKind) -> usize {
+        self.kinds[usize::from(kind.id())]
+    }
+
+    /// The number of tokens lexed
+    pub fn tokens(&self) -> usize {
+        self.kinds.iter().sum()
+    }
+
+    /// The name of each kind of token lexed and how many were, in the order
+    /// of [`TokenKind::id`]
+    pub fn kinds(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        KIND_NAMES.into_iter().zip(self.kinds).filter(|&(_, count)| count > 0)
+    }
+}
+
+/// The figures a lexer has collected so far
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Tally {
+    /// The figures about the lines before the last line with a token
+    stats: LexStats,
+    /// The last line with a token, counting from 1 at the start of the
+    /// source, and whether it has code
+    pending: Option<(usize, bool)>,
+    /// The number of delimiters open
+    depth: usize
+}
+
+impl Tally {
+    /// Counts a line as code or comments
+    fn finish_line(&mut self, code: bool, lines: usize) {
+        if code {
+            self.stats.code_lines += lines;
+        } else {
+            self.stats.comment_lines += lines;
+        }
+    }
+
+    /// Counts a token spanning lines from `first` to `last`
+    fn count(&mut self, kind: &TokenKind, first: usize, last: usize) {
+        self.stats.kinds[usize::from(kind.id())] += 1;
+        if Delimiter::opened_by(kind).is_some() {
+            self.depth += 1;
+            self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        } else if Delimiter::closed_by(kind).is_some() {
+            self.depth = self.depth.saturating_sub(1);
+        }
+
+        let code = match kind {
+            TokenKind::Whitespace(_) | TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent => return,
+            TokenKind::Comment(_) => false,
+            _ => true
+        };
+        let first_code = match self.pending {
+            Some((line, pending_code)) if line == first => code || pending_code,
+            Some((_, pending_code)) => {
+                self.finish_line(pending_code, 1);
+                code
+            },
+            None => code
+        };
+        if last > first {
+            self.finish_line(first_code, 1);
+            self.finish_line(code, last - first - 1);
+            self.pending = Some((last, code));
+        } else {
+            self.pending = Some((first, first_code));
+        }
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Figures about the source lexed so far, if the lexer was configured to
+    /// collect them
+    ///
+    /// Tokens already peeked count as lexed, and comments are counted even
+    /// if they aren't produced as tokens.
+    pub fn stats(&self) -> LexStats {
+        let mut tally = self.tally.clone();
+        if let Some((_, code)) = tally.pending {
+            tally.finish_line(code, 1);
+        }
+        let mut stats = tally.stats;
+
+        // A line counts once something is lexed on it
+        let row = self.row - self.origin.0 + 1;
+        let line_start = if row == 1 { self.origin.1 } else { 1 };
+        stats.bytes = self.pos;
+        stats.lines = row - usize::from(self.col == line_start);
+        stats.blank_lines = stats.lines.saturating_sub(stats.code_lines + stats.comment_lines);
+        stats
+    }
+
+    /// Counts a token lexed from a position up to the current character
+    pub(crate) fn tally(&mut self, kind: &TokenKind, position: (usize, usize)) {
+        // Lines count from 1 at the start of the source, and a token ending
+        // in a line break doesn't reach the line after it
+        let start = position.0 + 1 - self.config.base.0;
+        let end = if self.col == 1 && self.row > start { self.row - 1 } else { self.row };
+        let line = |row: usize| row + 1 - self.origin.0;
+        let (first, last) = (line(start), line(end));
+        self.tally.count(kind, first, last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LexerBuilder;
+
+    /// Lexes a whole source, collecting figures about it
+    fn stats(builder: LexerBuilder, source: &str) -> LexStats {
+        let mut lexer = builder.collect_stats(true).build(source);
+        lexer.by_ref().for_each(drop);
+        lexer.stats()
+    }
+
+    #[test]
+    fn counts_lines_by_what_is_on_them() {
+        let builder = LexerBuilder::new().line_comments(["//"]);
+        let source = "// header\nx = 1\n\n   \ny // trailing\n";
+        let stats = stats(builder, source);
+        assert_eq!(stats.bytes, source.len());
+        assert_eq!((stats.lines, stats.code_lines, stats.comment_lines, stats.blank_lines), (5, 2, 1, 2));
+    }
+
+    #[test]
+    fn counts_lines_spanned_by_a_token() {
+        let builder = LexerBuilder::new().block_comments([("/*", "*/")]);
+        let stats = stats(builder, "/* one\ntwo */ x\n/* three */");
+        assert_eq!((stats.lines, stats.code_lines, stats.comment_lines, stats.blank_lines), (3, 1, 2, 0));
+    }
+
+    #[test]
+    fn counts_tokens_by_kind() {
+        let stats = stats(LexerBuilder::new(), "f((x), 2)");
+        assert_eq!(stats.count(&TokenKind::Ident("y".into())), 2);
+        assert_eq!(stats.count(&TokenKind::Num(0)), 1);
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.tokens(), stats.kinds().map(|(_, count)| count).sum::<usize>());
+        assert_eq!(stats.kinds().next(), Some(("Ident", 2)));
+    }
+
+    #[test]
+    fn counts_nothing_in_an_empty_source() {
+        assert_eq!(stats(LexerBuilder::new(), ""), LexStats::default());
+    }
+}
@@ -0,0 +1,179 @@
+//! Buffered token streams.
+use crate::{match_delimiters, DelimiterMap, Lexer, Token};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// The tokens lexed from a source string
+#[derive(Debug, Clone)]
+pub struct TokenStream<'a> {
+    /// The source string the tokens were lexed from
+    pub(crate) source: &'a str,
+    /// The byte offset of the source within its file
+    pub(crate) offset: usize,
+    /// The name of the source, e.g. its path
+    pub(crate) name: Option<Arc<str>>,
+    /// The lexed tokens
+    pub(crate) tokens: Vec<Token<'a>>
+}
+
+impl<'a> TokenStream<'a> {
+    /// Lexes every token from a lexer
+    pub fn new(lexer: Lexer<'a>) -> Self {
+        let source = lexer.source;
+        let offset = lexer.offset;
+        let name = lexer.name.clone();
+        Self { source, offset, name, tokens: lexer.collect() }
+    }
+
+    /// The source string the tokens were lexed from
+    #[inline]
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The name the source was given, if any
+    #[inline]
+    pub fn source_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The lexed tokens
+    #[inline]
+    pub fn tokens(&self) -> &[Token<'a>] {
+        &self.tokens
+    }
+
+    /// Keeps only the tokens a predicate accepts
+    pub fn retain(&mut self, keep: impl FnMut(&Token<'a>) -> bool) {
+        self.tokens.retain(keep);
+    }
+
+    /// The source text a token was lexed from
+    #[inline]
+    pub fn text(&self, token: &Token<'a>) -> &'a str {
+        &self.source[token.span.start - self.offset..token.span.end - self.offset]
+    }
+
+    /// Concatenates the source text of every token
+    ///
+    /// When lexed with trivia kept this reproduces the source exactly, which
+    /// is what formatters build on. Otherwise whitespace and comments are
+    /// missing.
+    pub fn to_source(&self) -> String {
+        self.tokens.iter().map(|token| self.text(token)).collect()
+    }
+
+    /// Are the streams the same tokens, ignoring where they are in their
+    /// sources and any whitespace and comments between them?
+    ///
+    /// What formatter and refactoring tests want to assert: `f(a, b)` and
+    /// `f( a,b )` are structurally equal, `f(a, c)` isn't.
+    pub fn structurally_eq(&self, other: &TokenStream<'a>) -> bool {
+        self.significant().map(|token| &token.kind).eq(other.significant().map(|token| &token.kind))
+    }
+
+    /// Are the streams the same kinds of tokens, ignoring what they hold as
+    /// well as where they are and any whitespace and comments?
+    ///
+    /// So `f(a, b)` and `g(x, 1)` differ, as `1` isn't an identifier, but
+    /// `f(a, b)` and `g(x, y)` don't.
+    pub fn kinds_eq(&self, other: &TokenStream<'a>) -> bool {
+        self.significant().map(|token| token.kind.id()).eq(other.significant().map(|token| token.kind.id()))
+    }
+
+    /// Pairs up the stream's delimiters, by the indexes of their tokens
+    ///
+    /// See [`match_delimiters`].
+    pub fn delimiter_map(&self) -> DelimiterMap {
+        match_delimiters(&self.tokens)
+    }
+
+    /// The tokens other than whitespace and comments
+    fn significant(&self) -> impl Iterator<Item = &Token<'a>> {
+        self.tokens.iter().filter(|token| !token.kind.is_trivia())
+    }
+
+    /// A cursor at the first token
+    #[inline]
+    pub fn cursor(&self) -> Cursor<'_, 'a> {
+        Cursor { stream: self, index: 0 }
+    }
+}
+
+impl<'a> From<Lexer<'a>> for TokenStream<'a> {
+    fn from(lexer: Lexer<'a>) -> Self {
+        Self::new(lexer)
+    }
+}
+
+/// A position in a [`TokenStream`] that parsers move through
+///
+/// Cursors are cheap to copy, so saving one and moving back to it is how a
+/// parser backtracks.
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'s, 'a> {
+    stream: &'s TokenStream<'a>,
+    /// The index of the next token
+    index: usize
+}
+
+impl<'s, 'a> Cursor<'s, 'a> {
+    /// The stream the cursor moves through
+    #[inline]
+    pub fn stream(&self) -> &'s TokenStream<'a> {
+        self.stream
+    }
+
+    /// The index of the next token
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Is the cursor past the last token?
+    #[inline]
+    pub fn is_over(&self) -> bool {
+        self.index >= self.stream.tokens.len()
+    }
+
+    /// Peeks the next token without moving
+    #[inline]
+    pub fn peek(&self) -> Option<&'s Token<'a>> {
+        self.peek_nth(0)
+    }
+
+    /// Peeks the token `n` places ahead without moving, 0 being the next
+    /// token
+    #[inline]
+    pub fn peek_nth(&self, n: usize) -> Option<&'s Token<'a>> {
+        self.stream.tokens.get(self.index + n)
+    }
+
+    /// Moves to a token index, clamped to the end of the stream
+    #[inline]
+    pub fn seek(&mut self, index: usize) {
+        self.index = index.min(self.stream.tokens.len());
+    }
+
+    /// The tokens in a range of indices, e.g. from a saved
+    /// [`Cursor::index`] up to the current one
+    ///
+    /// Returns `None` if the range is out of bounds.
+    #[inline]
+    pub fn slice(&self, range: Range<usize>) -> Option<&'s [Token<'a>]> {
+        self.stream.tokens.get(range)
+    }
+}
+
+impl<'s, 'a> Iterator for Cursor<'s, 'a> {
+    type Item = &'s Token<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'s Token<'a>> {
+        let token = self.peek()?;
+        self.index += 1;
+        Some(token)
+    }
+}
@@ -0,0 +1,339 @@
+//! Lexing input that arrives in chunks.
+use crate::builder::{Config, Observer};
+use crate::{Lexer, LexerBuilder, LexerState, OwnedToken};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+#[cfg(feature = "async")]
+use core::pin::Pin;
+#[cfg(feature = "async")]
+use core::task::{Context, Poll};
+#[cfg(feature = "async")]
+use futures_core::Stream;
+#[cfg(feature = "async")]
+use futures_io::AsyncRead;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+/// A lexer fed its source a chunk at a time
+///
+/// A token is only produced once the input after it shows it's complete,
+/// so only the text of a partial token is buffered between chunks, along
+/// with the line it's on when indentation is significant. Tokens own their
+/// text, as the chunks they were lexed from are discarded.
+///
+/// Each chunk is lexed along with the buffered text before it, so a token
+/// spanning many chunks, like a long string or block comment, is lexed
+/// again with each of them; feed larger chunks where that matters. The
+/// comments, warnings and stats a [`Lexer`] collects on the side aren't
+/// collected, as the buffer is lexed afresh with each chunk.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingLexer {
+    /// The settings the source is lexed with, but for the observer
+    config: Arc<Config>,
+    /// The callback invoked with every token produced, which the lexers
+    /// relexing the buffer don't invoke
+    observer: Option<Observer>,
+    /// The source not yet lexed into complete tokens
+    buffer: String,
+    /// The byte offset of the buffer within the whole source
+    offset: usize,
+    /// The lexer's progress at the start of the buffer
+    state: Option<LexerState>,
+    /// Complete tokens not yet returned
+    tokens: VecDeque<OwnedToken>,
+    /// Whether the end of the source has been reached
+    finished: bool
+}
+
+impl StreamingLexer {
+    /// Creates a new streaming lexer with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next chunk of the source
+    pub fn feed(&mut self, chunk: &str) {
+        self.buffer.push_str(chunk);
+        self.lex_buffer();
+    }
+
+    /// Marks the end of the source, completing any partial token
+    pub fn finish(&mut self) {
+        self.finished = true;
+        self.lex_buffer();
+    }
+
+    /// Lexes the buffered source into as many complete tokens as possible
+    fn lex_buffer(&mut self) {
+        if self.buffer.is_empty() && !self.finished {
+            return;
+        }
+
+        let mut lexer = Lexer::with_config(&self.buffer, Arc::clone(&self.config));
+        lexer.offset = self.offset;
+        if let Some(state) = self.state.take() {
+            lexer.resume(state);
+        }
+
+        // A token near the end of the buffer could continue in the next
+        // chunk, as `=` becomes `===` once the rest of the operator arrives,
+        // and so could whitespace and comments skipped up to the end. Lexing
+        // resumes after the last token ending far enough from the end that
+        // the next chunk can't change where it ends. Tokens taking up no
+        // text, like indents, depend on the token after them (an indent
+        // before a comment isn't one), so they wait for it.
+        let end = self.offset + self.buffer.len();
+        let lookahead = self.config.lookahead();
+        let mut state = lexer.state();
+        let mut waiting = Vec::new();
+        while let Some(token) = lexer.next().filter(|token| self.finished || token.span.end + lookahead <= end) {
+            if token.span.start == token.span.end && !self.finished {
+                waiting.push(token.into_owned());
+                continue;
+            }
+            for token in waiting.drain(..).chain([token.into_owned()]) {
+                if let Some(observer) = &self.observer {
+                    (observer.0)(&token.as_token());
+                }
+                self.tokens.push_back(token);
+            }
+            state = lexer.state();
+        }
+
+        // A skipped byte-order mark not yet produced as trivia stays buffered,
+        // as does the line lexing resumes on when indentation is measured from
+        // its text
+        let mut consumed = state.pos - state.bom;
+        if self.config.indentation {
+            consumed = self.buffer[..consumed].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+        }
+        self.buffer.drain(..consumed);
+        self.offset += consumed;
+        state.pos -= consumed;
+        state.offset += consumed;
+        self.state = Some(state);
+    }
+
+    /// Lexes a source split into fragments, such as the chunks of a rope
+    pub fn fragments<'f, I: IntoIterator<Item = &'f str>>(self, fragments: I) -> FragmentLexer<I::IntoIter> {
+        FragmentLexer { fragments: fragments.into_iter(), lexer: self }
+    }
+
+    /// Wraps a reader, lexing it as it is read
+    #[cfg(feature = "std")]
+    pub fn reader<R: Read>(self, reader: R) -> ReaderLexer<R> {
+        ReaderLexer { reader, lexer: self, bytes: Vec::new() }
+    }
+}
+
+impl LexerBuilder {
+    /// Creates a lexer fed its source a chunk at a time with these settings
+    pub fn build_streaming(&self) -> StreamingLexer {
+        // Tokens near the end of a chunk are lexed again with the next, so
+        // only the streaming lexer observes the ones it produces
+        let mut config = Arc::clone(&self.config);
+        let observer = config.observer.clone();
+        if observer.is_some() {
+            Arc::make_mut(&mut config).observer = None;
+        }
+        StreamingLexer { config, observer, ..StreamingLexer::default() }
+    }
+
+    /// Creates a lexer over a source split into fragments with these settings
+    ///
+    /// See [`FragmentLexer`].
+    pub fn build_fragments<'f, I: IntoIterator<Item = &'f str>>(&self, fragments: I) -> FragmentLexer<I::IntoIter> {
+        self.build_streaming().fragments(fragments)
+    }
+}
+
+/// Returns the complete tokens lexed so far
+///
+/// Feeding more of the source can produce more tokens after this returns
+/// `None`.
+impl Iterator for StreamingLexer {
+    type Item = OwnedToken;
+
+    #[inline]
+    fn next(&mut self) -> Option<OwnedToken> {
+        self.tokens.pop_front()
+    }
+}
+
+/// A [`StreamingLexer`] lexing a source split into fragments, such as the
+/// chunks of a rope
+///
+/// Spans are byte offsets into the whole source, as if the fragments were
+/// joined, so editors don't have to flatten their buffer to lex it. Tokens
+/// can span fragments. Fragments are fed as tokens are taken, so a rope
+/// iterator can be passed directly.
+#[derive(Debug, Clone)]
+pub struct FragmentLexer<I> {
+    fragments: I,
+    lexer: StreamingLexer
+}
+
+impl<'f, I: Iterator<Item = &'f str>> Iterator for FragmentLexer<I> {
+    type Item = OwnedToken;
+
+    fn next(&mut self) -> Option<OwnedToken> {
+        loop {
+            if let Some(token) = self.lexer.next() {
+                return Some(token);
+            }
+            if self.lexer.finished {
+                return None;
+            }
+
+            match self.fragments.next() {
+                Some(fragment) => self.lexer.feed(fragment),
+                None => self.lexer.finish()
+            }
+        }
+    }
+}
+
+impl<'f, I: Iterator<Item = &'f str>> FusedIterator for FragmentLexer<I> {}
+
+#[cfg(feature = "std")]
+impl StreamingLexer {
+    /// The size of each chunk read from a reader
+    const CHUNK: usize = 8 * 1024;
+
+    /// Reserves space at the end of `bytes` for the next chunk, returning
+    /// where it starts
+    fn reserve_chunk(bytes: &mut Vec<u8>) -> usize {
+        let start = bytes.len();
+        bytes.resize(start + Self::CHUNK, 0);
+        start
+    }
+
+    /// Feeds the whole characters among the bytes read so far, keeping any
+    /// partial character at the end
+    ///
+    /// `read` is how many bytes were read into the chunk starting at
+    /// `start`, 0 marking the end of the source.
+    fn feed_bytes(&mut self, bytes: &mut Vec<u8>, start: usize, read: usize) -> io::Result<()> {
+        bytes.truncate(start + read);
+        if read == 0 {
+            if !bytes.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "stream ended in the middle of a character"));
+            }
+            self.finish();
+            return Ok(());
+        }
+
+        let valid = match core::str::from_utf8(bytes) {
+            Ok(chunk) => chunk.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err))
+        };
+        self.feed(core::str::from_utf8(&bytes[..valid]).unwrap_or_default());
+        bytes.drain(..valid);
+        Ok(())
+    }
+
+    /// Records a read error, ending the source unless the read can be retried
+    fn read_error(&mut self, err: io::Error) -> Option<io::Error> {
+        if err.kind() == io::ErrorKind::Interrupted {
+            return None;
+        }
+        self.finished = true;
+        Some(err)
+    }
+}
+
+/// A [`StreamingLexer`] reading its source from an [`io::Read`]
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ReaderLexer<R> {
+    reader: R,
+    lexer: StreamingLexer,
+    /// Bytes read that don't make up a whole character yet
+    bytes: Vec<u8>
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Iterator for ReaderLexer<R> {
+    type Item = io::Result<OwnedToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.lexer.next() {
+                return Some(Ok(token));
+            }
+            if self.lexer.finished {
+                return None;
+            }
+
+            let start = StreamingLexer::reserve_chunk(&mut self.bytes);
+            let result = match self.reader.read(&mut self.bytes[start..]) {
+                Ok(read) => self.lexer.feed_bytes(&mut self.bytes, start, read),
+                Err(err) => {
+                    self.bytes.truncate(start);
+                    Err(err)
+                }
+            };
+            if let Some(err) = result.err().and_then(|err| self.lexer.read_error(err)) {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// A [`StreamingLexer`] reading its source from a [`futures_io::AsyncRead`]
+///
+/// Tokens are produced as a [`futures_core::Stream`] as input arrives. Tokio
+/// readers can be adapted with `tokio_util::compat`.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncReaderLexer<R> {
+    reader: R,
+    lexer: StreamingLexer,
+    /// Bytes read that don't make up a whole character yet
+    bytes: Vec<u8>
+}
+
+#[cfg(feature = "async")]
+impl StreamingLexer {
+    /// Wraps an asynchronous reader, lexing it as it is read
+    pub fn async_reader<R: AsyncRead + Unpin>(self, reader: R) -> AsyncReaderLexer<R> {
+        AsyncReaderLexer { reader, lexer: self, bytes: Vec::new() }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> Stream for AsyncReaderLexer<R> {
+    type Item = io::Result<OwnedToken>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(token) = this.lexer.next() {
+                return Poll::Ready(Some(Ok(token)));
+            }
+            if this.lexer.finished {
+                return Poll::Ready(None);
+            }
+
+            let start = StreamingLexer::reserve_chunk(&mut this.bytes);
+            let result = match Pin::new(&mut this.reader).poll_read(cx, &mut this.bytes[start..]) {
+                Poll::Ready(Ok(read)) => this.lexer.feed_bytes(&mut this.bytes, start, read),
+                Poll::Ready(Err(err)) => {
+                    this.bytes.truncate(start);
+                    Err(err)
+                },
+                Poll::Pending => {
+                    this.bytes.truncate(start);
+                    return Poll::Pending;
+                }
+            };
+            if let Some(err) = result.err().and_then(|err| this.lexer.read_error(err)) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Lexing the text of a token with other settings.
+use crate::{Lexer, LexerBuilder, Span, Token};
+use alloc::sync::Arc;
+
+impl<'a> Lexer<'a> {
+    /// Lexes part of a token's text with other settings, e.g. the
+    /// expression in an interpolated string or the body of a raw string
+    ///
+    /// `inner` is the part of the token to lex, as bytes of the file like the
+    /// token's span. Spans and positions are reported relative to the file,
+    /// found from the token's own position, and errors are given the source
+    /// name. Returns `None` if `inner` isn't within the token or doesn't fall
+    /// on character boundaries.
+    pub fn sublex(&self, token: &Token<'a>, inner: Span, builder: &LexerBuilder) -> Option<Lexer<'a>> {
+        if inner.start < token.span.start || inner.end > token.span.end {
+            return None;
+        }
+        let text = self.text_of(inner)?;
+        let before = self.text_of(Span { start: token.span.start, end: inner.start })?;
+
+        // Walks the token's text before `inner` to find the position it
+        // starts at
+        let mut walker = Lexer::with_config(before, Arc::clone(&self.config)).starting_at(token.span.start, token.position);
+        walker.advance_to(before.len());
+        let lexer = builder.build_with_offset(text, inner.start, walker.pos());
+        Some(match &self.name {
+            Some(name) => lexer.named(Arc::clone(name)),
+            None => lexer
+        })
+    }
+}
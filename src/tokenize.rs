@@ -0,0 +1,108 @@
+//! Lexing a whole source string at once.
+use crate::{LexError, LexWarning, Lexer, LexerBuilder, Token, TokenKind};
+use alloc::vec::Vec;
+
+impl<'a> Lexer<'a> {
+    /// Lexes a whole source string with the default settings
+    ///
+    /// Returns every error if any token couldn't be lexed.
+    pub fn tokenize(source: &'a str) -> Result<Vec<Token<'a>>, Vec<LexError>> {
+        Self::new(source).collect_tokens()
+    }
+
+    /// Lexes a whole source string with the default settings, keeping errors
+    /// as [`TokenKind::Error`] tokens
+    pub fn tokenize_all(source: &'a str) -> Vec<Token<'a>> {
+        Self::new(source).collect()
+    }
+
+    /// Lexes a whole source string with the default settings, keeping errors
+    /// as [`TokenKind::Error`] tokens, along with the warnings found
+    pub fn tokenize_all_with_warnings(source: &'a str) -> (Vec<Token<'a>>, Vec<LexWarning>) {
+        LexerBuilder::new().tokenize_all_with_warnings(source)
+    }
+
+    /// Lexes every remaining token
+    ///
+    /// Returns every error if any token couldn't be lexed.
+    pub fn collect_tokens(self) -> Result<Vec<Token<'a>>, Vec<LexError>> {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        let source_name = self.name.clone();
+
+        for token in self {
+            match token.kind {
+                TokenKind::Error(kind) => {
+                    let source_name = source_name.clone();
+                    errors.push(LexError { kind, position: token.position, span: token.span, source_name });
+                },
+                _ => tokens.push(token)
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Lexes every remaining token onto the end of a vector, keeping errors
+    /// as [`TokenKind::Error`] tokens
+    ///
+    /// Room for the tokens is reserved up front, estimated from the length
+    /// of the source, so large files are collected with few reallocations.
+    pub fn tokenize_into(self, tokens: &mut Vec<Token<'a>>) -> TokenizeStats {
+        let bytes = self.remaining().len();
+        let estimate = bytes / AVERAGE_TOKEN_LEN + 1;
+        tokens.reserve(estimate);
+
+        let mut stats = TokenizeStats { bytes, estimate, ..TokenizeStats::default() };
+        for token in self {
+            let capacity = tokens.capacity();
+            stats.errors += usize::from(token.kind.is_error());
+            tokens.push(token);
+            stats.tokens += 1;
+            stats.reallocations += usize::from(tokens.capacity() != capacity);
+        }
+        stats
+    }
+}
+
+/// The average length in bytes of a token and the whitespace after it, in
+/// typical source code
+const AVERAGE_TOKEN_LEN: usize = 6;
+
+/// Figures about a run of [`Lexer::tokenize_into`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenizeStats {
+    /// The number of tokens lexed, errors included
+    pub tokens: usize,
+    /// The number of error tokens lexed
+    pub errors: usize,
+    /// The length of the source lexed, in bytes
+    pub bytes: usize,
+    /// The number of tokens room was reserved for
+    pub estimate: usize,
+    /// How many times the vector grew past the room reserved
+    pub reallocations: usize
+}
+
+impl LexerBuilder {
+    /// Lexes a whole source string with these settings
+    ///
+    /// Returns every error if any token couldn't be lexed.
+    pub fn tokenize<'a>(&self, source: &'a str) -> Result<Vec<Token<'a>>, Vec<LexError>> {
+        self.build(source).collect_tokens()
+    }
+
+    /// Lexes a whole source string with these settings, keeping errors as
+    /// [`TokenKind::Error`] tokens, along with the warnings found
+    ///
+    /// Warnings are checked for even if the settings don't enable them.
+    pub fn tokenize_all_with_warnings<'a>(&self, source: &'a str) -> (Vec<Token<'a>>, Vec<LexWarning>) {
+        let mut lexer = self.clone().warnings(true).build(source);
+        let tokens = lexer.by_ref().collect();
+        (tokens, lexer.warnings)
+    }
+}
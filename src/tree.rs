@@ -0,0 +1,311 @@
+//! Grouping tokens into trees by their delimiters.
+use crate::{DelimiterError, Span, Token, TokenKind};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+/// A pair of delimiters tokens can be grouped by
+///
+/// Parentheses are lexed as [`TokenKind::OpeningBracket`] and
+/// [`TokenKind::ClosingBracket`]; braces and square brackets are recognised
+/// as [`TokenKind::Punct`] once registered with
+/// [`LexerBuilder::operator`](crate::LexerBuilder::operator).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+    /// `( ... )`
+    Parenthesis,
+    /// `{ ... }`
+    Brace,
+    /// `[ ... ]`
+    Bracket
+}
+
+impl Delimiter {
+    /// The delimiter a token opens, if any
+    pub fn opened_by(kind: &TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::OpeningBracket => Some(Self::Parenthesis),
+            TokenKind::Punct("{") => Some(Self::Brace),
+            TokenKind::Punct("[") => Some(Self::Bracket),
+            _ => None
+        }
+    }
+
+    /// The delimiter a token closes, if any
+    pub fn closed_by(kind: &TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::ClosingBracket => Some(Self::Parenthesis),
+            TokenKind::Punct("}") => Some(Self::Brace),
+            TokenKind::Punct("]") => Some(Self::Bracket),
+            _ => None
+        }
+    }
+}
+
+/// A token, or a delimited group of token trees
+#[derive(Debug, Clone)]
+pub enum TokenTree<'a> {
+    Token(Token<'a>),
+    Group(Group<'a>)
+}
+
+impl TokenTree<'_> {
+    /// The bytes the tree was lexed from
+    #[inline]
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Token(token) => token.span,
+            Self::Group(group) => group.span()
+        }
+    }
+}
+
+/// Token trees surrounded by a pair of delimiters
+#[derive(Debug, Clone)]
+pub struct Group<'a> {
+    /// The kind of delimiters
+    pub delimiter: Delimiter,
+    /// The opening delimiter's span
+    pub open: Span,
+    /// The closing delimiter's span
+    pub close: Span,
+    /// The trees between the delimiters
+    pub trees: Vec<TokenTree<'a>>
+}
+
+impl Group<'_> {
+    /// The bytes the group was lexed from, delimiters included
+    #[inline]
+    pub fn span(&self) -> Span {
+        Span { start: self.open.start, end: self.close.end }
+    }
+}
+
+/// Nests tokens into trees of balanced delimited groups
+///
+/// Returns an error at the first delimiter that isn't balanced.
+pub fn token_trees<'a>(tokens: impl IntoIterator<Item = Token<'a>>) -> Result<Vec<TokenTree<'a>>, DelimiterError> {
+    let mut open: Vec<(Delimiter, Span, Vec<TokenTree<'a>>)> = Vec::new();
+    let mut trees = Vec::new();
+
+    for token in tokens {
+        if let Some(delimiter) = Delimiter::opened_by(&token.kind) {
+            open.push((delimiter, token.span, core::mem::take(&mut trees)));
+        } else if let Some(found) = Delimiter::closed_by(&token.kind) {
+            let Some((delimiter, span, outer)) = open.pop() else {
+                return Err(DelimiterError::Unopened { delimiter: found, span: token.span });
+            };
+            if delimiter != found {
+                return Err(DelimiterError::Mismatched { open: span, close: token.span, expected: delimiter, found });
+            }
+            let group = Group { delimiter, open: span, close: token.span, trees: core::mem::replace(&mut trees, outer) };
+            trees.push(TokenTree::Group(group));
+        } else {
+            trees.push(TokenTree::Token(token));
+        }
+    }
+
+    match open.pop() {
+        Some((delimiter, span, _)) => Err(DelimiterError::Unclosed { delimiter, span }),
+        None => Ok(trees)
+    }
+}
+
+/// Checks that every delimiter is balanced, without building trees
+///
+/// Accepts borrowed or owned tokens, so a lexer can be checked directly.
+/// Returns an error at the first delimiter that isn't balanced.
+pub fn validate_delimiters<'a, T: Borrow<Token<'a>>>(tokens: impl IntoIterator<Item = T>) -> Result<(), DelimiterError> {
+    let mut open: Vec<(Delimiter, Span)> = Vec::new();
+
+    for token in tokens {
+        let token = token.borrow();
+        if let Some(delimiter) = Delimiter::opened_by(&token.kind) {
+            open.push((delimiter, token.span));
+        } else if let Some(found) = Delimiter::closed_by(&token.kind) {
+            match open.pop() {
+                Some((delimiter, _)) if delimiter == found => {},
+                Some((expected, span)) => {
+                    return Err(DelimiterError::Mismatched { open: span, close: token.span, expected, found });
+                },
+                None => return Err(DelimiterError::Unopened { delimiter: found, span: token.span })
+            }
+        }
+    }
+
+    match open.pop() {
+        Some((delimiter, span)) => Err(DelimiterError::Unclosed { delimiter, span }),
+        None => Ok(())
+    }
+}
+
+/// The delimiter matching each delimiter token, by index, built by
+/// [`match_delimiters`]
+///
+/// For editors highlighting the bracket matching the one at the cursor,
+/// and parsers skipping over groups, in constant time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DelimiterMap {
+    /// The index of the delimiter matching each token, if it's a matched
+    /// delimiter
+    partners: Vec<Option<usize>>,
+    /// The span of each delimiter token and of the one matching it, in
+    /// order
+    spans: Vec<(Span, Option<Span>)>,
+    /// The indexes of the delimiters with no match, in order
+    unmatched: Vec<usize>
+}
+
+impl DelimiterMap {
+    /// The index of the delimiter matching the token at an index, if it's
+    /// a delimiter with a match
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<usize> {
+        self.partners.get(index).copied().flatten()
+    }
+
+    /// The span of the delimiter matching the one lexed from a byte offset,
+    /// if there is one
+    pub fn at_offset(&self, offset: usize) -> Option<Span> {
+        let i = self.spans.partition_point(|(span, _)| span.end <= offset);
+        self.spans.get(i).filter(|(span, _)| span.start <= offset).and_then(|&(_, partner)| partner)
+    }
+
+    /// The indexes of the delimiters with no match, in order
+    #[inline]
+    pub fn unmatched(&self) -> &[usize] {
+        &self.unmatched
+    }
+}
+
+/// Pairs up every delimiter with the one matching it
+///
+/// Accepts borrowed or owned tokens, like [`validate_delimiters`], but
+/// carries on past unbalanced delimiters as editors must: a closing
+/// delimiter matching one opened further out closes it, leaving those
+/// opened since unmatched, and one matching nothing open is unmatched.
+pub fn match_delimiters<'a, T: Borrow<Token<'a>>>(tokens: impl IntoIterator<Item = T>) -> DelimiterMap {
+    let mut map = DelimiterMap::default();
+    // The delimiters open, their indexes and the indexes of their spans
+    let mut open: Vec<(Delimiter, usize, usize)> = Vec::new();
+
+    for (i, token) in tokens.into_iter().enumerate() {
+        let token = token.borrow();
+        map.partners.push(None);
+        if let Some(delimiter) = Delimiter::opened_by(&token.kind) {
+            open.push((delimiter, i, map.spans.len()));
+            map.spans.push((token.span, None));
+        } else if let Some(found) = Delimiter::closed_by(&token.kind) {
+            let partner = match open.iter().rposition(|&(delimiter, ..)| delimiter == found) {
+                Some(depth) => {
+                    let (_, opener, opener_span) = open[depth];
+                    map.unmatched.extend(open.drain(depth..).skip(1).map(|(_, index, _)| index));
+                    map.partners[opener] = Some(i);
+                    map.partners[i] = Some(opener);
+                    map.spans[opener_span].1 = Some(token.span);
+                    Some(map.spans[opener_span].0)
+                },
+                None => {
+                    map.unmatched.push(i);
+                    None
+                }
+            };
+            map.spans.push((token.span, partner));
+        }
+    }
+
+    map.unmatched.extend(open.into_iter().map(|(_, index, _)| index));
+    map.unmatched.sort_unstable();
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LexerBuilder;
+
+    /// Lexes a source with braces and square brackets as delimiters
+    fn lex(source: &str) -> Vec<Token<'_>> {
+        LexerBuilder::new().operator("{").operator("}").operator("[").operator("]").build(source).collect()
+    }
+
+    /// The span of bytes from `start` to `end`
+    fn span(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    #[test]
+    fn nests_groups() {
+        let trees = token_trees(lex("f(a, [b]) {}")).unwrap();
+        assert_eq!(trees.len(), 3);
+        assert!(matches!(&trees[0], TokenTree::Token(token) if token.kind == TokenKind::Ident("f".into())));
+
+        let TokenTree::Group(call) = &trees[1] else {
+            panic!("expected a group, found {:?}", trees[1]);
+        };
+        assert_eq!((call.delimiter, call.open, call.close, call.span()), (Delimiter::Parenthesis, span(1, 2), span(8, 9), span(1, 9)));
+        assert_eq!(call.trees.len(), 3);
+        let TokenTree::Group(index) = &call.trees[2] else {
+            panic!("expected a group, found {:?}", call.trees[2]);
+        };
+        assert_eq!((index.delimiter, index.trees.len()), (Delimiter::Bracket, 1));
+
+        let TokenTree::Group(block) = &trees[2] else {
+            panic!("expected a group, found {:?}", trees[2]);
+        };
+        assert_eq!((block.delimiter, block.trees.len()), (Delimiter::Brace, 0));
+        assert_eq!(trees[2].span(), span(10, 12));
+    }
+
+    #[test]
+    fn grouping_reports_unbalanced_delimiters() {
+        assert_eq!(token_trees(lex("(a")).unwrap_err(), DelimiterError::Unclosed { delimiter: Delimiter::Parenthesis, span: span(0, 1) });
+        assert_eq!(token_trees(lex("a]")).unwrap_err(), DelimiterError::Unopened { delimiter: Delimiter::Bracket, span: span(1, 2) });
+        assert_eq!(token_trees(lex("{(})")).unwrap_err(), DelimiterError::Mismatched {
+            open: span(1, 2),
+            close: span(2, 3),
+            expected: Delimiter::Parenthesis,
+            found: Delimiter::Brace
+        });
+    }
+
+    #[test]
+    fn validates_delimiters() {
+        assert_eq!(validate_delimiters(lex("f(a, [b]) {}")), Ok(()));
+        assert_eq!(validate_delimiters(lex("([)]")), Err(DelimiterError::Mismatched {
+            open: span(1, 2),
+            close: span(2, 3),
+            expected: Delimiter::Bracket,
+            found: Delimiter::Parenthesis
+        }));
+        // Borrowed tokens are accepted too
+        let tokens = lex("{ [");
+        assert_eq!(validate_delimiters(tokens.iter()), Err(DelimiterError::Unclosed { delimiter: Delimiter::Bracket, span: span(2, 3) }));
+        let tokens = lex("} {");
+        assert_eq!(validate_delimiters(tokens.iter()), Err(DelimiterError::Unopened { delimiter: Delimiter::Brace, span: span(0, 1) }));
+    }
+
+    #[test]
+    fn matches_delimiters() {
+        let map = match_delimiters(lex("f(a, [b]) {}"));
+        assert_eq!((map.get(1), map.get(7)), (Some(7), Some(1)));
+        assert_eq!((map.get(4), map.get(6)), (Some(6), Some(4)));
+        assert_eq!((map.get(8), map.get(9)), (Some(9), Some(8)));
+        assert_eq!((map.get(0), map.get(3), map.get(10)), (None, None, None));
+        assert_eq!(map.at_offset(1), Some(span(8, 9)));
+        assert_eq!(map.at_offset(11), Some(span(10, 11)));
+        assert_eq!(map.at_offset(2), None);
+        assert!(map.unmatched().is_empty());
+    }
+
+    #[test]
+    fn matching_carries_on_past_unbalanced_delimiters() {
+        // `)` closes the `(` opened before the `[`, leaving it unmatched, and
+        // `]` then matches nothing
+        let map = match_delimiters(lex("f(a[b)c] ("));
+        assert_eq!((map.get(1), map.get(5)), (Some(5), Some(1)));
+        assert_eq!((map.get(3), map.get(7)), (None, None));
+        assert_eq!(map.unmatched(), [3, 7, 8]);
+        assert_eq!(map.at_offset(5), Some(span(1, 2)));
+        assert_eq!(map.at_offset(3), None);
+    }
+}
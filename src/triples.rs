@@ -0,0 +1,49 @@
+//! Spanned triples for parser generators.
+use crate::{LexError, Lexer, TokenKind};
+
+/// A token's kind between the byte offsets it starts and ends at
+pub type Triple<'a> = (usize, TokenKind<'a>, usize);
+
+/// A lexer producing the spanned triples LALRPOP expects of an external
+/// lexer, returned by [`Lexer::triples`]
+///
+/// Whitespace and comment tokens are skipped and error tokens become errors.
+/// A grammar using it declares `type Location = usize;` and
+/// `type Error = LexError;` in its `extern` block, with its terminals mapped
+/// to `TokenKind` patterns.
+#[derive(Debug, Clone)]
+pub struct Triples<'a> {
+    lexer: Lexer<'a>
+}
+
+impl<'a> Triples<'a> {
+    /// The underlying lexer
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'a> {
+        &self.lexer
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Produces each token as a `(start, kind, end)` triple, as LALRPOP
+    /// parsers take their input
+    #[inline]
+    pub fn triples(self) -> Triples<'a> {
+        Triples { lexer: self }
+    }
+}
+
+impl<'a> Iterator for Triples<'a> {
+    type Item = Result<Triple<'a>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.lexer.by_ref().find(|token| !token.kind.is_trivia())?;
+        Some(match token.kind {
+            TokenKind::Error(kind) => {
+                let source_name = self.lexer.name.clone();
+                Err(LexError { kind, position: token.position, span: token.span, source_name })
+            },
+            kind => Ok((token.span.start, kind, token.span.end))
+        })
+    }
+}
@@ -0,0 +1,132 @@
+//! Attaching trivia to the tokens around it.
+use crate::{Lexer, Span, Token, TokenKind};
+use alloc::vec::Vec;
+
+/// A token with the whitespace and comments around it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TriviaToken<'a> {
+    /// The trivia from the end of the previous token's line up to the token
+    pub leading: Vec<Token<'a>>,
+    /// The token itself, never trivia
+    pub token: Token<'a>,
+    /// The trivia after the token up to the end of its line
+    pub trailing: Vec<Token<'a>>
+}
+
+impl TriviaToken<'_> {
+    /// The bytes the token was lexed from, along with its trivia
+    pub fn full_span(&self) -> Span {
+        Span {
+            start: self.leading.first().map_or(self.token.span.start, |trivia| trivia.span.start),
+            end: self.trailing.last().map_or(self.token.span.end, |trivia| trivia.span.end)
+        }
+    }
+}
+
+/// A lexer producing each token with its leading and trailing trivia,
+/// returned by [`Lexer::attach_trivia`]
+#[derive(Debug, Clone)]
+pub struct AttachedTrivia<'a> {
+    lexer: Lexer<'a>,
+    /// The leading trivia of the next token
+    leading: Vec<Token<'a>>,
+    /// The token after the trivia read ahead
+    next: Option<Token<'a>>
+}
+
+impl<'a> AttachedTrivia<'a> {
+    /// The underlying lexer
+    #[inline]
+    pub fn lexer(&self) -> &Lexer<'a> {
+        &self.lexer
+    }
+
+    /// Splits whitespace after the first line break in it, if any
+    ///
+    /// Returns the whitespace up to the end of the line, and the rest if it
+    /// isn't empty.
+    fn split_line(&self, trivia: &Token<'a>) -> Option<(Token<'a>, Option<Token<'a>>)> {
+        let TokenKind::Whitespace(text) = trivia.kind else {
+            return None;
+        };
+        let bytes = text.as_bytes();
+        let end = memchr::memchr2_iter(b'\n', b'\r', bytes).find_map(|i| match bytes[i] {
+            b'\n' => Some(i + 1),
+            _ if bytes.get(i + 1) == Some(&b'\n') => Some(i + 2),
+            _ => self.lexer.config.lone_cr_newlines.then_some(i + 1)
+        })?;
+
+        let split = trivia.span.start + end;
+        let line = Token {
+            kind: TokenKind::Whitespace(&text[..end]),
+            position: trivia.position,
+            span: Span { start: trivia.span.start, end: split }
+        };
+        let rest = (end < text.len()).then(|| Token {
+            kind: TokenKind::Whitespace(&text[end..]),
+            position: (trivia.position.0 + 1, self.lexer.config.base.1),
+            span: Span { start: split, end: trivia.span.end }
+        });
+        Some((line, rest))
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Attaches whitespace and comments to the tokens around them, as in
+    /// Roslyn
+    ///
+    /// A token's trailing trivia runs up to the end of its line, and the
+    /// rest is the leading trivia of the next token, so a comment on a line
+    /// of its own stays with the code after it. Trivia after the last token
+    /// trails it, and a source of nothing but trivia produces nothing. The
+    /// lexer has to be configured to keep trivia, with
+    /// [`LexerBuilder::trivia`](crate::LexerBuilder::trivia).
+    #[inline]
+    pub fn attach_trivia(self) -> AttachedTrivia<'a> {
+        AttachedTrivia { lexer: self, leading: Vec::new(), next: None }
+    }
+}
+
+impl<'a> Iterator for AttachedTrivia<'a> {
+    type Item = TriviaToken<'a>;
+
+    fn next(&mut self) -> Option<TriviaToken<'a>> {
+        let mut leading = core::mem::take(&mut self.leading);
+        let token = loop {
+            let token = self.next.take().or_else(|| self.lexer.next())?;
+            if !token.kind.is_trivia() {
+                break token;
+            }
+            leading.push(token);
+        };
+
+        // The trivia after the end of the line is read ahead too, to trail
+        // the token if there's no token after it
+        let mut trailing = Vec::new();
+        let mut line_ended = false;
+        while let Some(trivia) = self.lexer.next() {
+            if !trivia.kind.is_trivia() {
+                self.next = Some(trivia);
+                break;
+            }
+            if line_ended {
+                self.leading.push(trivia);
+                continue;
+            }
+
+            match self.split_line(&trivia) {
+                Some((line, rest)) => {
+                    trailing.push(line);
+                    self.leading.extend(rest);
+                    line_ended = true;
+                },
+                None => trailing.push(trivia)
+            }
+        }
+        if self.next.is_none() {
+            trailing.append(&mut self.leading);
+        }
+
+        Some(TriviaToken { leading, token, trailing })
+    }
+}
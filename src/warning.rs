@@ -0,0 +1,230 @@
+//! Lexical warnings, about source text that lexes but is likely a mistake.
+use crate::{Lexer, Span, TokenKind};
+use core::fmt;
+
+/// The different kinds of lexical warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LexWarningKind {
+    /// A `_` doubled or at either end of a number, or right after one that
+    /// doesn't take separators, so `1_000` lexes as `1` and `_000`
+    SuspiciousDigitSeparator,
+    /// Indentation with both tabs and spaces, in indentation mode, or that
+    /// is only consistent with its block's for some widths of tab
+    MixedIndentation,
+    /// An identifier with characters from more than one script, e.g. Latin
+    /// and Cyrillic, found with the `unicode` feature
+    MixedScriptIdent,
+    /// An identifier with a non-ASCII character that looks like ASCII text,
+    /// found with the `unicode` feature
+    ConfusableIdent(char),
+    /// An identifier following none of the configured naming conventions
+    NamingConvention
+}
+
+impl fmt::Display for LexWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SuspiciousDigitSeparator => f.write_str("suspicious digit separator"),
+            Self::MixedIndentation => f.write_str("indentation mixes tabs and spaces"),
+            Self::MixedScriptIdent => f.write_str("identifier mixes scripts"),
+            Self::ConfusableIdent(ch) => write!(f, "identifier has `{ch}`, which looks like ASCII"),
+            Self::NamingConvention => f.write_str("identifier doesn't follow the naming convention")
+        }
+    }
+}
+
+/// A convention for writing identifiers of several words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdentCase {
+    /// `snake_case`
+    Snake,
+    /// `camelCase`
+    Camel,
+    /// `PascalCase`
+    Pascal,
+    /// `SCREAMING_CASE`
+    ScreamingSnake
+}
+
+impl IdentCase {
+    /// The conventions in the order [`IdentCase::of`] tries them
+    const ALL: [Self; 4] = [Self::Snake, Self::Camel, Self::Pascal, Self::ScreamingSnake];
+
+    /// Does an identifier follow the convention?
+    ///
+    /// Underscores at either end, as in `_unused` or `__init__`, are ignored,
+    /// and digits are neither case, so a single lowercase word like `count`
+    /// is both `snake_case` and `camelCase`.
+    pub fn matches(self, ident: &str) -> bool {
+        let name = ident.trim_matches('_');
+        let Some(first) = name.chars().next() else {
+            return true;
+        };
+        match self {
+            Self::Snake => !name.chars().any(char::is_uppercase),
+            Self::Camel => !first.is_uppercase() && !name.contains('_'),
+            Self::Pascal => !first.is_lowercase() && !name.contains('_'),
+            Self::ScreamingSnake => !name.chars().any(char::is_lowercase)
+        }
+    }
+
+    /// The convention an identifier follows, `snake_case` first where it
+    /// follows several, or `None` if it follows none, like `mixed_Case`
+    pub fn of(ident: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|case| case.matches(ident))
+    }
+}
+
+/// A lexical warning along with where it occurred
+///
+/// Warnings don't stop the source from lexing, so tools can report or
+/// ignore them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LexWarning {
+    /// What looks wrong
+    pub kind: LexWarningKind,
+    /// The warning's position in file
+    pub position: (usize, usize),
+    /// The bytes the warning is about
+    pub span: Span
+}
+
+impl fmt::Display for LexWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (row, col) = self.position;
+        write!(f, "{} at {row}:{col}", self.kind)
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Records a warning about the bytes from a byte offset in the source up
+    /// to the current character
+    fn warn(&mut self, kind: LexWarningKind, position: (usize, usize), start: usize) {
+        let span = Span { start: self.offset + start, end: self.offset + self.pos };
+        self.warnings.push(LexWarning { kind, position, span });
+    }
+
+    /// Checks a token lexed from a byte offset for anything suspicious, and
+    /// identifiers for the configured naming conventions
+    pub(crate) fn check_token(&mut self, kind: &TokenKind<'a>, position: (usize, usize), start: usize) {
+        if self.config.warnings {
+            match kind {
+                TokenKind::Num(_) | TokenKind::Float(_) => {
+                    let text = self.slice(start, self.pos);
+                    if text.starts_with('_') || text.ends_with('_') || text.contains("__") || self.current() == Some('_') {
+                        self.warn(LexWarningKind::SuspiciousDigitSeparator, position, start);
+                    }
+                },
+                #[cfg(feature = "unicode")]
+                TokenKind::Ident(ident) => {
+                    use unicode_security::MixedScript;
+
+                    if !ident.is_single_script() {
+                        self.warn(LexWarningKind::MixedScriptIdent, position, start);
+                    }
+                    if let Some(ch) = ident.chars().find(|&ch| crate::lint::is_confusable(ch)) {
+                        self.warn(LexWarningKind::ConfusableIdent(ch), position, start);
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        let naming = &self.config.naming_conventions;
+        if !naming.is_empty() && matches!(kind, TokenKind::Ident(_) | TokenKind::Symbol(_)) {
+            let text = self.slice(start, self.pos);
+            if !naming.iter().any(|case| case.matches(text)) {
+                self.warn(LexWarningKind::NamingConvention, position, start);
+            }
+        }
+    }
+
+    /// Checks the indentation of the line the lexer is at the start of,
+    /// given whether it's inconsistent with its block's
+    pub(crate) fn check_indentation(&mut self, inconsistent: bool) {
+        let line_start = self.source[..self.pos].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+        let line = self.slice(line_start, self.pos);
+        let indentation = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        if inconsistent || indentation.contains(' ') && indentation.contains('\t') {
+            let span = Span { start: self.offset + line_start, end: self.offset + line_start + indentation.len() };
+            let position = (self.pos().0, self.config.base.1);
+            self.warnings.push(LexWarning { kind: LexWarningKind::MixedIndentation, position, span });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LexerBuilder;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    /// The kinds of warning found in a source
+    fn warnings(builder: LexerBuilder, source: &str) -> Vec<LexWarningKind> {
+        let (_, warnings) = builder.tokenize_all_with_warnings(source);
+        warnings.into_iter().map(|warning| warning.kind).collect()
+    }
+
+    #[test]
+    fn warns_about_digit_separators() {
+        // Numbers don't take separators by default, so `1_000` is `1` and `_000`
+        for source in ["1_000", "1000_"] {
+            assert_eq!(warnings(LexerBuilder::new(), source), [LexWarningKind::SuspiciousDigitSeparator], "{source}");
+        }
+        assert_eq!(warnings(LexerBuilder::new(), "1000 + x_1"), []);
+    }
+
+    #[test]
+    fn warns_about_mixed_indentation() {
+        let builder = LexerBuilder::new().indentation(true);
+        assert_eq!(warnings(builder.clone(), "x\n \ty\n"), [LexWarningKind::MixedIndentation]);
+        assert_eq!(warnings(builder, "x\n    y\n"), []);
+    }
+
+    #[test]
+    fn records_where_warnings_are() {
+        let (_, warnings) = LexerBuilder::new().tokenize_all_with_warnings("x = 1_000");
+        assert_eq!(warnings, [LexWarning {
+            kind: LexWarningKind::SuspiciousDigitSeparator,
+            position: (1, 5),
+            span: Span { start: 4, end: 5 }
+        }]);
+        assert_eq!(warnings[0].to_string(), "suspicious digit separator at 1:5");
+    }
+
+    #[test]
+    fn doesnt_warn_unless_enabled() {
+        let mut lexer = LexerBuilder::new().build("1__000");
+        lexer.by_ref().for_each(drop);
+        assert_eq!(lexer.warnings(), []);
+    }
+
+    #[test]
+    fn classifies_identifiers() {
+        assert_eq!(IdentCase::of("line_count"), Some(IdentCase::Snake));
+        assert_eq!(IdentCase::of("count"), Some(IdentCase::Snake));
+        assert_eq!(IdentCase::of("lineCount"), Some(IdentCase::Camel));
+        assert_eq!(IdentCase::of("LineCount"), Some(IdentCase::Pascal));
+        assert_eq!(IdentCase::of("MAX_LINES"), Some(IdentCase::ScreamingSnake));
+        assert_eq!(IdentCase::of("mixed_Case"), None);
+        assert!(IdentCase::Camel.matches("count"));
+        assert!(IdentCase::Snake.matches("__init__"));
+        assert!(!IdentCase::Pascal.matches("_lineCount"));
+    }
+
+    #[test]
+    fn warns_about_naming_conventions() {
+        let builder = LexerBuilder::new().naming_conventions([IdentCase::Snake, IdentCase::Pascal]);
+        let mut lexer = builder.build("let lineCount = Point.x_1 + MAX_LEN");
+        lexer.by_ref().for_each(drop);
+        let spans: Vec<_> = lexer.warnings().iter().map(|warning| (warning.kind, warning.span)).collect();
+        assert_eq!(spans, [
+            (LexWarningKind::NamingConvention, Span { start: 4, end: 13 }),
+            (LexWarningKind::NamingConvention, Span { start: 28, end: 35 })
+        ]);
+    }
+}
@@ -0,0 +1,45 @@
+//! Bindings for JavaScript through `wasm-bindgen`.
+use crate::{Lexer, TokenKind};
+use alloc::string::ToString;
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+/// Lexes a source string with the default settings into an array of token
+/// objects
+///
+/// Each object has the token's `kind` name, its source `text`, the `start`
+/// and `end` of its span in UTF-16 code units (so `source.slice(start, end)`
+/// is its text in JavaScript) and its 1-based `line` and `column`. Error
+/// tokens also have a `message`.
+#[wasm_bindgen]
+pub fn tokenize(source: &str) -> JsValue {
+    let tokens = Array::new();
+    // Spans are converted from bytes to UTF-16 as the tokens come in order
+    let (mut byte, mut utf16) = (0, 0);
+    let mut to_utf16 = |offset: usize| {
+        utf16 += source[byte..offset].encode_utf16().count();
+        byte = offset;
+        utf16
+    };
+
+    for token in Lexer::new(source) {
+        let object = Object::new();
+        set(&object, "kind", token.kind.name().into());
+        set(&object, "text", token.text(source).into());
+        set(&object, "start", to_utf16(token.span.start).into());
+        set(&object, "end", to_utf16(token.span.end).into());
+        set(&object, "line", token.position.0.into());
+        set(&object, "column", token.position.1.into());
+        if let TokenKind::Error(kind) = &token.kind {
+            set(&object, "message", kind.to_string().into());
+        }
+        tokens.push(&object);
+    }
+    tokens.into()
+}
+
+/// Sets a property of a token object
+fn set(object: &Object, key: &str, value: JsValue) {
+    // Defining a property of a plain object can't fail
+    let _ = Reflect::set(object, &JsValue::from_str(key), &value);
+}
@@ -0,0 +1,109 @@
+//! Lexing doesn't allocate once a lexer is built.
+use reusable_lexer::{Lexer, LexerBuilder, Mode, Token};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations made on each thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const SOURCE: &str = "\u{FEFF}let x = 1 + foo(\"bar\", 42) // comment\n\
+    /* block\n comment */ if x >= 10 { y = x * 2 } else { y = -x % 3 }\n\
+    café => ≠ @\n\"unterminated";
+
+/// Lexes every token, returning how many allocations were made
+fn count_allocations(mut lexer: Lexer) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let mut count = 0;
+    for token in lexer.by_ref() {
+        std::hint::black_box(&token);
+        count += 1;
+    }
+    let allocations = ALLOCATIONS.with(Cell::get) - before;
+    assert!(count > 0);
+    allocations
+}
+
+fn builder() -> LexerBuilder {
+    LexerBuilder::new()
+        .string_delimiters(['"'])
+        .block_comments([("/*", "*/")])
+        .keywords(["let", "if", "else"])
+        .operator("=>")
+}
+
+#[test]
+fn default_settings() {
+    assert_eq!(count_allocations(Lexer::new(SOURCE)), 0);
+}
+
+#[test]
+fn configured() {
+    assert_eq!(count_allocations(builder().build(SOURCE)), 0);
+}
+
+#[test]
+fn trivia() {
+    assert_eq!(count_allocations(builder().trivia(true).build(SOURCE)), 0);
+}
+
+#[test]
+fn newlines() {
+    assert_eq!(count_allocations(builder().newlines(true).build(SOURCE)), 0);
+}
+
+#[test]
+fn unicode_encodings() {
+    use reusable_lexer::PositionEncoding;
+
+    for encoding in [PositionEncoding::Utf8, PositionEncoding::Utf16, PositionEncoding::Codepoints] {
+        assert_eq!(count_allocations(builder().encoding(encoding).tab_width(4).build(SOURCE)), 0);
+    }
+}
+
+#[test]
+fn peeking_within_capacity() {
+    let mut lexer = builder().build(SOURCE);
+    lexer.peek_nth(3);
+    let _: Vec<Token> = lexer.by_ref().take(4).collect();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    while lexer.peek_token().is_some() {
+        lexer.next();
+    }
+    assert_eq!(ALLOCATIONS.with(Cell::get) - before, 0);
+}
+
+#[test]
+fn modes_within_capacity() {
+    let mut lexer = builder().build("abc) def");
+    lexer.push_mode(Mode::Raw { end: ")".into() });
+    lexer.pop_mode();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    lexer.push_mode(Mode::Directive);
+    lexer.by_ref().for_each(drop);
+    assert_eq!(ALLOCATIONS.with(Cell::get) - before, 0);
+}
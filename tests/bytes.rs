@@ -0,0 +1,27 @@
+//! Lexing bytes that aren't valid UTF-8 lexes the valid text between
+//! invalid sequences as if it were part of the whole source.
+use reusable_lexer::{LexErrorKind, LexerBuilder, Span, TokenKind};
+
+/// Lexes bytes into their kinds and spans
+fn lex(bytes: &[u8]) -> Vec<(TokenKind<'_>, Span)> {
+    LexerBuilder::new().build_from_bytes_lossy(bytes).map(|token| (token.kind, token.span)).collect()
+}
+
+#[test]
+fn leading_byte_order_mark_is_skipped() {
+    assert_eq!(lex(b"\xEF\xBB\xBFx\xFFy"), [
+        (TokenKind::Ident("x".into()), Span { start: 3, end: 4 }),
+        (TokenKind::Error(LexErrorKind::InvalidUtf8), Span { start: 4, end: 5 }),
+        (TokenKind::Ident("y".into()), Span { start: 5, end: 6 })
+    ]);
+}
+
+#[test]
+fn byte_order_mark_after_an_invalid_sequence_is_lexed() {
+    assert_eq!(lex(b"x\xFF\xEF\xBB\xBFy"), [
+        (TokenKind::Ident("x".into()), Span { start: 0, end: 1 }),
+        (TokenKind::Error(LexErrorKind::InvalidUtf8), Span { start: 1, end: 2 }),
+        (TokenKind::Error(LexErrorKind::UnexpectedChar('\u{feff}')), Span { start: 2, end: 5 }),
+        (TokenKind::Ident("y".into()), Span { start: 5, end: 6 })
+    ]);
+}
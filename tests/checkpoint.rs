@@ -0,0 +1,53 @@
+//! Rewinding to a checkpoint lexes the same tokens again, once.
+use reusable_lexer::{LexerBuilder, Token};
+
+const SOURCE: &str = "a // one\n  b /* two */ c\nd\n";
+
+fn builder() -> LexerBuilder {
+    LexerBuilder::new().line_comments(["//"]).block_comments([("/*", "*/")]).collect_comments(true).indentation(true)
+}
+
+fn texts<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens.iter().map(|token| token.text(SOURCE)).collect()
+}
+
+#[test]
+fn rewinding_relexes_the_same_tokens() {
+    let mut lexer = builder().build(SOURCE);
+    lexer.next();
+    let checkpoint = lexer.checkpoint();
+    let first: Vec<_> = lexer.by_ref().collect();
+    assert_eq!(texts(lexer.comments()), ["// one", "/* two */"]);
+
+    lexer.rewind(checkpoint);
+    assert_eq!(lexer.comments().len(), 0);
+    let second: Vec<_> = lexer.by_ref().collect();
+    assert_eq!(first, second);
+    assert_eq!(texts(lexer.comments()), ["// one", "/* two */"]);
+}
+
+#[test]
+fn rewinding_keeps_peeked_tokens() {
+    let mut lexer = builder().build(SOURCE);
+    lexer.peek_nth(3);
+    let checkpoint = lexer.checkpoint();
+    let first: Vec<_> = lexer.by_ref().collect();
+    lexer.rewind(checkpoint);
+    assert_eq!(lexer.by_ref().collect::<Vec<_>>(), first);
+}
+
+#[test]
+fn speculation_rewinds_unless_committed() {
+    let mut lexer = builder().build(SOURCE);
+    {
+        let mut speculation = lexer.speculate();
+        speculation.by_ref().take(4).for_each(drop);
+    }
+    assert_eq!(lexer.next().map(|token| token.text(SOURCE)), Some("a"));
+    assert_eq!(lexer.comments().len(), 0);
+
+    let mut speculation = lexer.speculate();
+    speculation.by_ref().take(2).for_each(drop);
+    speculation.commit();
+    assert_eq!(texts(lexer.comments()), ["// one"]);
+}
@@ -0,0 +1,132 @@
+//! Every way of lexing a source gives the same tokens as lexing it whole.
+use proptest::prelude::*;
+use reusable_lexer::{Edit, LexerBuilder, OwnedToken, Token, TokenStream};
+use std::sync::{Arc, Mutex};
+
+/// Sources made of the characters the lexer treats specially
+fn source() -> impl Strategy<Value = String> {
+    "(let|if|x|42|-1|1\\.5|\"s\"|\"|'|`|\\$\\{|\\}|<%|%>|/\\*|\\*/|//|#|\\(|\\)|\\[|\\]|=|===|<=>|<|>|-|\n|\r\n|  |\t|é|\u{FEFF}){0,40}"
+}
+
+/// Settings covering trivia, comments, strings, operators, layout and modes
+fn builders() -> Vec<LexerBuilder> {
+    let configured = LexerBuilder::new()
+        .keywords(["let", "if"])
+        .line_comments(["//", "#"])
+        .block_comments([("/*", "*/")])
+        .string_delimiters(['"', '\'']);
+    vec![
+        LexerBuilder::new(),
+        configured.clone(),
+        configured.clone().trivia(true).collect_comments(true),
+        configured.clone().indentation(true).newlines(true),
+        configured.clone().indentation(true).trivia(true).tab_errors(true),
+        configured.clone().interpolated_strings('`', "${", "}"),
+        configured.clone().embedded_regions("<%", "%>"),
+        configured.clone().operator("===").operator("<=>").operator("<<<<"),
+        configured.negative_literals(true)
+    ]
+}
+
+/// The last character boundary of a source at or before a byte offset
+fn floor_boundary(source: &str, offset: usize) -> usize {
+    (0..=offset.min(source.len())).rev().find(|&i| source.is_char_boundary(i)).unwrap_or(0)
+}
+
+/// Lexes a source whole, the tokens every other way is checked against
+fn lex<'a>(builder: &LexerBuilder, source: &'a str) -> Vec<Token<'a>> {
+    builder.build(source).collect()
+}
+
+/// Splits a source into chunks at character boundaries near the given
+/// offsets
+fn split<'a>(source: &'a str, cuts: &[usize]) -> Vec<&'a str> {
+    let mut cuts: Vec<_> = cuts.iter().map(|cut| floor_boundary(source, cut % (source.len() + 1))).collect();
+    cuts.sort_unstable();
+    let bounds: Vec<_> = [0].into_iter().chain(cuts).chain([source.len()]).collect();
+    bounds.windows(2).map(|pair| &source[pair[0]..pair[1]]).collect()
+}
+
+/// Applies edits one after another, returning each and the source after it
+fn apply(source: &str, edits: &[(usize, usize, String)]) -> Vec<(Edit, String)> {
+    let mut source = source.to_string();
+    let mut applied = Vec::new();
+    for (start, len, text) in edits {
+        let start = floor_boundary(&source, start % (source.len() + 1));
+        let end = floor_boundary(&source, start + len % (source.len() - start + 1)).max(start);
+        source.replace_range(start..end, text);
+        applied.push((Edit::new(start..end, text), source.clone()));
+    }
+    applied
+}
+
+proptest! {
+    #[test]
+    fn relexing_edits_matches_lexing_whole(
+        source in source(),
+        edits in prop::collection::vec((any::<usize>(), any::<usize>(), source()), 1..4)
+    ) {
+        let applied = apply(&source, &edits);
+        for builder in builders() {
+            let mut stream = TokenStream::new(builder.build(&source));
+            for (edit, edited) in &applied {
+                let (relexed, _) = builder.relex(&stream, edited, edit);
+                prop_assert_eq!(relexed.tokens(), &lex(&builder, edited)[..], "{:?} after {:?} with {:?}", edited, edit, builder);
+                stream = relexed;
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_chunks_matches_lexing_whole(source in source(), cuts in prop::collection::vec(any::<usize>(), 0..6)) {
+        let chunks = split(&source, &cuts);
+        for builder in builders() {
+            let whole: Vec<OwnedToken> = lex(&builder, &source).into_iter().map(Token::into_owned).collect();
+            let mut streaming = builder.build_streaming();
+            let mut streamed = Vec::new();
+            for chunk in &chunks {
+                streaming.feed(chunk);
+                streamed.extend(streaming.by_ref());
+            }
+            streaming.finish();
+            streamed.extend(streaming);
+            prop_assert_eq!(&streamed, &whole, "{:?} with {:?}", chunks, builder);
+
+            let fragments: Vec<_> = builder.build_fragments(chunks.iter().copied()).collect();
+            prop_assert_eq!(&fragments, &whole, "{:?} with {:?}", chunks, builder);
+        }
+    }
+
+    #[test]
+    fn streaming_observes_each_token_once(source in source(), cuts in prop::collection::vec(any::<usize>(), 0..6)) {
+        let chunks = split(&source, &cuts);
+        for builder in builders() {
+            let observed = Arc::new(Mutex::new(Vec::new()));
+            let observer = Arc::clone(&observed);
+            let builder = builder.on_token(move |token| observer.lock().unwrap().push(token.clone().into_owned()));
+            let streamed: Vec<_> = builder.build_fragments(chunks.iter().copied()).collect();
+            prop_assert_eq!(&*observed.lock().unwrap(), &streamed, "{:?} with {:?}", chunks, builder);
+        }
+    }
+
+    #[test]
+    fn cached_streams_load_the_same(source in source()) {
+        for builder in builders().into_iter().chain([LexerBuilder::new().fold_ident_case(true)]) {
+            let stream = TokenStream::new(builder.build_named(&source, "cached.src"));
+            let loaded = TokenStream::from_cache(&source, &stream.to_cache());
+            prop_assert!(loaded.is_ok(), "{:?}", loaded);
+            let loaded = loaded.unwrap();
+            prop_assert_eq!(loaded.tokens(), stream.tokens());
+            prop_assert_eq!(loaded.source_name(), Some("cached.src"));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn lexing_in_parallel_matches_lexing_whole(lines in prop::collection::vec(source(), 0..12), threads in 1..6usize) {
+        let source = lines.join("\n");
+        for builder in builders() {
+            prop_assert_eq!(builder.tokenize_parallel(&source, threads), lex(&builder, &source), "{:?}", builder);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! The dump format writes tokens the same whatever their error messages say.
+use reusable_lexer::{LexErrorKind, LexerBuilder, Span, Token, TokenKind, TokenStream};
+
+#[test]
+fn dumps_each_token_on_a_line() {
+    let builder = LexerBuilder::new().keywords(["let"]).string_delimiters(['"']).indentation(true);
+    let dump = TokenStream::new(builder.build("let x = \"a\\\"\"\n  7 $\n")).dump();
+    assert_eq!(dump, concat!(
+        "1:1 0..3 Keyword \"let\"\n",
+        "1:5 4..5 Ident \"x\"\n",
+        "1:7 6..7 Opr =\n",
+        "1:9 8..13 Str \"a\\\\\\\"\"\n",
+        "1:14 13..14 Newline\n",
+        "2:3 16..16 Indent\n",
+        "2:3 16..17 Num 7\n",
+        "2:5 18..19 Error L0001 \"$\"\n",
+        "2:6 19..20 Newline\n",
+        "3:1 20..20 Dedent\n"
+    ));
+}
+
+#[test]
+fn dumps_errors_as_their_codes() {
+    let token = |kind| Token { kind: TokenKind::Error(kind), position: (1, 1), span: Span { start: 0, end: 1 } };
+    assert_eq!(token(LexErrorKind::UnterminatedString).dump(), "1:1 0..1 Error L0002");
+    assert_eq!(token(LexErrorKind::NonAsciiDigit('٣')).dump(), "1:1 0..1 Error L0010 \"٣\"");
+    let expected = LexErrorKind::Expected { expected: ")".into(), found: Some("]".into()) };
+    assert_eq!(token(expected).dump(), "1:1 0..1 Error L0006 \")\" \"]\"");
+    let expected = LexErrorKind::Expected { expected: ")".into(), found: None };
+    assert_eq!(token(expected).dump(), "1:1 0..1 Error L0006 \")\"");
+}
@@ -0,0 +1,31 @@
+//! Tokens ending exactly at the end of the source are lexed whole, and
+//! lexing then stops.
+use reusable_lexer::{LexerBuilder, Span, TokenKind};
+
+/// Lexes a source, checking its last token and that none follow
+fn assert_last(builder: &LexerBuilder, source: &str, kind: TokenKind, span: Span) {
+    let mut lexer = builder.build(source);
+    let last = lexer.by_ref().last().expect("no tokens");
+    assert_eq!((last.kind, last.span), (kind, span));
+    assert_eq!(lexer.next(), None);
+    assert_eq!(lexer.next(), None);
+}
+
+#[test]
+fn identifier_at_eof() {
+    assert_last(&LexerBuilder::new(), "let value", TokenKind::Ident("value".into()), Span { start: 4, end: 9 });
+}
+
+#[test]
+fn number_at_eof() {
+    assert_last(&LexerBuilder::new(), "x = 1234", TokenKind::Num(1234), Span { start: 4, end: 8 });
+}
+
+#[test]
+fn comment_at_eof() {
+    let builder = LexerBuilder::new().line_comments(["//"]).trivia(true);
+    assert_last(&builder, "x // the end", TokenKind::Comment("// the end"), Span { start: 2, end: 12 });
+    // Skipped rather than produced, the comment still ends lexing
+    let builder = LexerBuilder::new().line_comments(["//"]);
+    assert_last(&builder, "x // the end", TokenKind::Ident("x".into()), Span { start: 0, end: 1 });
+}
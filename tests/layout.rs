@@ -0,0 +1,69 @@
+//! Indentation is measured the same wherever the source starts, and lines
+//! inside delimiters are ignored.
+use reusable_lexer::{LexerBuilder, TokenKind};
+
+/// Lexes a source into its kinds, as the whole source and as one starting
+/// mid-line
+fn kinds(builder: &LexerBuilder, source: &'static str) -> [Vec<TokenKind<'static>>; 2] {
+    let whole = builder.build(source).map(|token| token.kind).collect();
+    [whole, builder.build_with_offset(source, 10, (3, 7)).map(|token| token.kind).collect()]
+}
+
+#[test]
+fn indentation_ignores_the_origin_column() {
+    let builder = LexerBuilder::new().indentation(true);
+    let [whole, offset] = kinds(&builder, "x\ny\n  z\n");
+    assert_eq!(whole[..3], [TokenKind::Ident("x".into()), TokenKind::Newline, TokenKind::Ident("y".into())]);
+    assert_eq!(whole, offset);
+}
+
+#[test]
+fn indentation_skips_a_byte_order_mark() {
+    let builder = LexerBuilder::new().indentation(true);
+    let [whole, offset] = kinds(&builder, "\u{FEFF}  x\n  y\n");
+    assert_eq!(whole[..2], [TokenKind::Indent, TokenKind::Ident("x".into())]);
+    assert_eq!(whole, offset);
+}
+
+#[test]
+fn indentation_counts_tab_stops() {
+    let builder = LexerBuilder::new().indentation(true).tab_width(8);
+    let [whole, offset] = kinds(&builder, "\tx\n        y\n");
+    assert!(!whole.iter().any(TokenKind::is_error), "{whole:?}");
+    assert_eq!(whole.iter().filter(|kind| **kind == TokenKind::Indent).count(), 1);
+    assert_eq!(whole, offset);
+}
+
+#[test]
+fn indentation_survives_chunk_splits() {
+    let builder = LexerBuilder::new().line_comments(["//"]).indentation(true);
+    for (builder, chunks) in [
+        // A `/` could start a token or a comment-only line
+        (builder.clone(), ["x\n\t/", "/ comment\ny\n"]),
+        // Whitespace produced as trivia before a token split across chunks
+        (builder.trivia(true), ["x\n  y", "z\n"])
+    ] {
+        let source = chunks.concat();
+        let whole: Vec<_> = builder.build(&source).map(|token| token.kind.into_owned()).collect();
+        let streamed: Vec<_> = builder.build_fragments(chunks).map(|token| token.kind).collect();
+        assert_eq!(streamed, whole);
+    }
+}
+
+#[test]
+fn indentation_ignores_lines_inside_registered_delimiters() {
+    let builder = LexerBuilder::new().indentation(true).operator("[").operator("]");
+    let [whole, offset] = kinds(&builder, "x = [\n    1,\n    2\n]\ny\n");
+    let layout: Vec<_> = whole.iter().filter(|kind| kind.is_layout()).collect();
+    assert_eq!(layout, [&TokenKind::Newline, &TokenKind::Newline], "{whole:?}");
+    assert_eq!(whole, offset);
+}
+
+#[test]
+fn newlines_end_no_statement_inside_registered_delimiters() {
+    let builder = LexerBuilder::new().newlines(true).operator("{").operator("}");
+    let [whole, offset] = kinds(&builder, "f({\n  x\n})\ny\n");
+    assert_eq!(whole.iter().filter(|kind| **kind == TokenKind::Newline).count(), 2, "{whole:?}");
+    assert_eq!(whole[whole.len() - 3..], [TokenKind::Newline, TokenKind::Ident("y".into()), TokenKind::Newline]);
+    assert_eq!(whole, offset);
+}
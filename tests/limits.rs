@@ -0,0 +1,62 @@
+//! Configured limits turn whatever goes past them into errors, leaving the
+//! rest of the source lexed as usual.
+use reusable_lexer::{LexErrorKind, LexerBuilder, Op, Span, TokenKind};
+
+/// Lexes a source into its kinds and spans
+fn lex(builder: &LexerBuilder, source: &'static str) -> Vec<(TokenKind<'static>, Span)> {
+    builder.build(source).map(|token| (token.kind, token.span)).collect()
+}
+
+#[test]
+fn lexes_a_long_source_as_one_error() {
+    let builder = LexerBuilder::new().max_source_len(8);
+    assert_eq!(lex(&builder, "x = 12345"), [(TokenKind::Error(LexErrorKind::SourceTooLong), Span { start: 0, end: 9 })]);
+    assert_eq!(lex(&builder, "x = 1234").len(), 3);
+    assert_eq!(lex(&builder, ""), []);
+}
+
+#[test]
+fn lexes_long_tokens_as_errors() {
+    let builder = LexerBuilder::new().max_token_len(4);
+    assert_eq!(lex(&builder, "abcd + abcde"), [
+        (TokenKind::Ident("abcd".into()), Span { start: 0, end: 4 }),
+        (TokenKind::Opr(Op::Plus), Span { start: 5, end: 6 }),
+        (TokenKind::Error(LexErrorKind::TokenTooLong), Span { start: 7, end: 12 })
+    ]);
+}
+
+#[test]
+fn lexes_deeply_nested_delimiters_as_errors() {
+    let builder = LexerBuilder::new().max_nesting(2);
+    let kinds: Vec<_> = lex(&builder, "(((x))) (x)").into_iter().map(|(kind, _)| kind).collect();
+    assert_eq!(kinds[2], TokenKind::Error(LexErrorKind::TooDeeplyNested));
+    assert_eq!(kinds.iter().filter(|kind| kind.is_error()).count(), 1, "{kinds:?}");
+}
+
+#[test]
+fn lexes_the_rest_of_a_cancelled_source_as_one_error() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let flag = cancelled.clone();
+    let builder = LexerBuilder::new().cancel_when(move || flag.load(Ordering::Relaxed));
+    let source = "x ".repeat(1000);
+    let mut lexer = builder.build(&source);
+    assert_eq!(lexer.next().unwrap().kind, TokenKind::Ident("x".into()));
+    cancelled.store(true, Ordering::Relaxed);
+
+    let rest: Vec<_> = lexer.collect();
+    let last = rest.last().unwrap();
+    assert_eq!(last.kind, TokenKind::Error(LexErrorKind::Cancelled));
+    assert_eq!(last.span.end, source.len());
+    assert!(rest[..rest.len() - 1].iter().all(|token| token.kind == TokenKind::Ident("x".into())));
+    assert!(rest.len() < 300, "{}", rest.len());
+}
+
+#[test]
+fn lexes_everything_unless_cancelled() {
+    let builder = LexerBuilder::new().cancel_when(|| false);
+    let source = "x ".repeat(1000);
+    assert_eq!(builder.build(&source).count(), 1000);
+}
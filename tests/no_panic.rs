@@ -0,0 +1,83 @@
+//! Lexing never panics, whatever the input and settings.
+use proptest::prelude::*;
+use reusable_lexer::{match_delimiters, profiles, token_trees, Edit, IdentCase, LexerBuilder, PositionEncoding, TokenStream};
+
+/// Sources of arbitrary text, and of the characters the lexer treats
+/// specially in arbitrary combinations
+fn source() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<String>(),
+        "\u{FEFF}?(let|if|IF|x|42|1_0|0x|1e|\\.5|\"|'|`|\\$\\{|\\}|<%|%>|/\\*|\\*/|\\(\\*|\\*\\)|//|#|--|\\\\|>>|\\+\\+|-|\\(|\\)|\\[|\\]|;|\n|\r|\r\n|  |\t|é|e\u{301}|a\u{430}|\u{663}|\u{FEFF}|\0){0,48}"
+    ]
+}
+
+/// Settings covering every option, limits and the predefined profiles
+fn builders() -> Vec<LexerBuilder> {
+    let configured = LexerBuilder::new()
+        .keywords(["let", "if", "IF"])
+        .line_comments(["//", "#", "--"])
+        .block_comments([("/*", "*/"), ("(*", "*)")])
+        .string_delimiters(['"', '\''])
+        .interpolated_strings('`', "${", "}")
+        .operator("=>")
+        .operator(">>=");
+    let mut builders = vec![
+        LexerBuilder::new(),
+        configured.clone().trivia(true).collect_comments(true).warnings(true),
+        configured.clone().indentation(true).newlines(true).tab_width(4).warnings(true),
+        configured.clone().lone_cr_newlines(true).line_continuations(true),
+        configured.clone().whitespace(|ch| ch == ' ' || ch == '\t').newlines(true).indentation(true).tab_errors(true),
+        configured.clone().embedded_regions("<%", "%>").doubled_quote_escapes(true).negative_literals(true),
+        configured.clone().increment_operators(true).case_insensitive_keywords(true).fold_ident_case(true),
+        configured.clone().max_source_len(16).max_token_len(4).max_nesting(2).convert_non_ascii_digits(true),
+        configured.clone().position_base(0, 0).encoding(PositionEncoding::Utf16).naming_conventions([IdentCase::Snake]),
+        configured.encoding(PositionEncoding::Utf8).skip_bom(false).trivia(true).indentation(true)
+    ];
+    let registry = profiles::Registry::new();
+    builders.extend(registry.names().filter_map(|name| registry.get(name)).cloned());
+    builders
+}
+
+/// The last character boundary of a source at or before a byte offset
+fn floor_boundary(source: &str, offset: usize) -> usize {
+    (0..=offset.min(source.len())).rev().find(|&i| source.is_char_boundary(i)).unwrap_or(0)
+}
+
+proptest! {
+    #[test]
+    fn lexing_never_panics(source in source(), bytes in any::<Vec<u8>>(), at in any::<usize>()) {
+        let split = floor_boundary(&source, at % (source.len() + 1));
+        for builder in builders() {
+            let tokens: Vec<_> = builder.build(&source).collect();
+            match_delimiters(&tokens).at_offset(split);
+            let _ = token_trees(tokens.clone());
+            builder.build(&source).attach_trivia().for_each(drop);
+            builder.build_with_offset(&source, 7, (3, 4)).triples().for_each(drop);
+            builder.build_from_bytes_lossy(&bytes).for_each(drop);
+            builder.build_fragments([&source[..split], &source[split..]]).for_each(drop);
+            #[cfg(feature = "std")]
+            builder.tokenize_parallel(&source, 3);
+            builder.minify(&source);
+
+            let mut lexer = builder.build(&source);
+            lexer.seek(at % (source.len() + 2));
+            while lexer.peek_token().is_some() {
+                lexer.split_right_shift();
+                let _ = lexer.expect(&reusable_lexer::TokenKind::Punct(";"));
+                lexer.next();
+            }
+
+            let stream = TokenStream::new(builder.build(&source));
+            let _ = TokenStream::from_cache(&source, &bytes);
+            let mut cache = stream.to_cache();
+            if let Some(byte) = cache.get_mut(at % (bytes.len() + 1)) {
+                *byte ^= bytes.first().copied().unwrap_or(1) | 1;
+            }
+            let _ = TokenStream::from_cache(&source, &cache);
+
+            let mut edited = source.clone();
+            edited.replace_range(split.., "x\"/*");
+            builder.relex(&stream, &edited, &Edit::new(split..source.len(), "x\"/*"));
+        }
+    }
+}
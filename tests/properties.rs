@@ -0,0 +1,114 @@
+//! Invariants every token stream upholds, checked on generated sources.
+use proptest::prelude::*;
+use reusable_lexer::{LexerBuilder, Token, TokenKind, TokenStream};
+
+/// Sources mixing the characters the lexer treats specially with arbitrary
+/// ones
+fn source() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-z0-9_ \t\r\n\"'/*#=<>!+%(){}-]{0,64}",
+        "(let|if|x|42|\"s\"|/\\*|\\*/|//|\n|  |\t|=>|é|\u{FEFF}|\u{300}){0,32}",
+        any::<String>()
+    ]
+}
+
+/// Settings exercising trivia, comments, strings, keywords and layout
+fn builders() -> Vec<LexerBuilder> {
+    let configured = LexerBuilder::new()
+        .keywords(["let", "if"])
+        .line_comments(["//"])
+        .block_comments([("/*", "*/")])
+        .string_delimiters(['"'])
+        .operator("=>");
+    vec![
+        LexerBuilder::new(),
+        configured.clone(),
+        configured.clone().trivia(true),
+        configured.indentation(true).newlines(true)
+    ]
+}
+
+/// Lexes a source, giving up if there are implausibly many tokens for its
+/// length instead of looping forever
+fn lex<'a>(builder: &LexerBuilder, source: &'a str) -> Vec<Token<'a>> {
+    let limit = 2 * source.len() + 2;
+    let tokens: Vec<_> = builder.build(source).take(limit + 1).collect();
+    assert!(tokens.len() <= limit, "lexing didn't terminate");
+    tokens
+}
+
+/// Compares two token streams by kind only, as relexing moves tokens
+fn kinds<'a>(tokens: &'a [Token]) -> Vec<&'a TokenKind<'a>> {
+    tokens.iter().map(|token| &token.kind).collect()
+}
+
+proptest! {
+    #[test]
+    fn lexing_terminates(source in source()) {
+        for builder in builders() {
+            lex(&builder, &source);
+        }
+    }
+
+    #[test]
+    fn positions_never_decrease(source in source()) {
+        for builder in builders() {
+            let tokens = lex(&builder, &source);
+            for pair in tokens.windows(2) {
+                prop_assert!(pair[0].position <= pair[1].position, "{:?} then {:?}", pair[0], pair[1]);
+                prop_assert!(pair[0].span.end <= pair[1].span.start, "{:?} then {:?}", pair[0], pair[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn spans_slice_to_text(source in source()) {
+        for builder in builders() {
+            for token in lex(&builder, &source) {
+                let text = source.get(token.span.start..token.span.end);
+                prop_assert!(text.is_some(), "{:?} isn't within the source", token);
+                // Layout tokens and errors at the end of unterminated tokens
+                // are the only ones taking up no text
+                let empty = text.is_some_and(str::is_empty);
+                prop_assert!(!empty || token.kind.is_layout() || token.kind.is_error(), "{:?} is empty", token);
+            }
+        }
+    }
+
+    #[test]
+    fn trivia_reproduces_the_source(source in source()) {
+        let builder = &builders()[2];
+        let stream = TokenStream::new(builder.build(&source));
+        prop_assert_eq!(stream.to_source(), source);
+    }
+
+    #[test]
+    fn relexing_spans_reproduces_tokens(source in source()) {
+        // Without trivia, whitespace before each token keeps them apart and
+        // keeps a byte-order mark token from moving to the start
+        for builder in &builders()[..2] {
+            let tokens = lex(builder, &source);
+            let spaced: String = tokens.iter().flat_map(|token| [" ", token.text(&source)]).collect();
+            let relexed = lex(builder, &spaced);
+            prop_assert_eq!(kinds(&tokens), kinds(&relexed), "relexing {:?}", spaced);
+        }
+    }
+
+    #[test]
+    fn size_hints_bound_the_tokens_left(source in source()) {
+        let interpolated = LexerBuilder::new().interpolated_strings('"', "{", "}");
+        for builder in builders().iter().chain([&interpolated]) {
+            let mut lexer = builder.build(&source);
+            let mut hints = Vec::new();
+            loop {
+                hints.push(lexer.size_hint());
+                if lexer.next().is_none() {
+                    break;
+                }
+            }
+            for (left, (lower, upper)) in hints.into_iter().rev().enumerate() {
+                prop_assert!(lower <= left && !upper.is_some_and(|upper| upper < left), "{:?} for {}", (lower, upper), left);
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! A token stream lexed with trivia prints back as its source exactly.
+use proptest::prelude::*;
+use reusable_lexer::{LexerBuilder, TokenStream};
+
+/// Settings keeping trivia, with comments, strings, layout and modes
+fn builders() -> Vec<LexerBuilder> {
+    let configured = LexerBuilder::new()
+        .keywords(["let", "if"])
+        .line_comments(["//", "#"])
+        .block_comments([("/*", "*/")])
+        .string_delimiters(['"', '\''])
+        .trivia(true);
+    vec![
+        LexerBuilder::new().trivia(true),
+        configured.clone(),
+        configured.clone().indentation(true).newlines(true).tab_width(4),
+        configured.clone().lone_cr_newlines(true).line_continuations(true),
+        configured.clone().interpolated_strings('`', "${", "}"),
+        configured.clone().embedded_regions("<%", "%>"),
+        configured.skip_bom(false)
+    ]
+}
+
+/// Checks every builder prints a source back as it is
+fn assert_round_trips(source: &str) {
+    for builder in builders() {
+        let stream = TokenStream::new(builder.build(source));
+        assert_eq!(stream.to_source(), source, "{builder:?}");
+    }
+}
+
+#[test]
+fn crlf_round_trips() {
+    assert_round_trips("let x = 1\r\nif x {\r\n  y\r\n}\r\n");
+}
+
+#[test]
+fn byte_order_mark_round_trips() {
+    assert_round_trips("\u{FEFF}let x = 1\n");
+    assert_round_trips("\u{FEFF}");
+}
+
+#[test]
+fn tabs_round_trip() {
+    assert_round_trips("if x\n\tlet y = 2\n\t\tz\t// tabbed\n");
+}
+
+#[test]
+fn comments_round_trip() {
+    assert_round_trips("// first\nx /* inline */ y # last");
+    assert_round_trips("/* unterminated\ncomment");
+}
+
+#[test]
+fn unterminated_string_round_trips() {
+    assert_round_trips("let s = \"never closed\nx");
+    assert_round_trips("'");
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_sources_round_trip(source in any::<String>()) {
+        for builder in builders() {
+            let stream = TokenStream::new(builder.build(&source));
+            prop_assert_eq!(stream.to_source(), source.as_str(), "{:?}", builder);
+        }
+    }
+
+    #[test]
+    fn special_characters_round_trip(source in "(let|x|1|\"|'|`|\\$\\{|\\}|<%|%>|/\\*|\\*/|//|#|\\\\|\n|\r|\r\n|  |\t|\u{FEFF}){0,32}") {
+        for builder in builders() {
+            let stream = TokenStream::new(builder.build(&source));
+            prop_assert_eq!(stream.to_source(), source.as_str(), "{:?}", builder);
+        }
+    }
+}
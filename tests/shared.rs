@@ -0,0 +1,44 @@
+//! A lexer owning its source lexes as one borrowing it does.
+use reusable_lexer::{LexerBuilder, SharedLexer};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const SOURCE: &str = "let x = 1 // one\nif x { y = \"s\" } /* two */\n";
+
+#[test]
+fn shared_lexer_lexes_the_same_tokens() {
+    let builder = LexerBuilder::new().keywords(["let", "if"]).line_comments(["//"]).block_comments([("/*", "*/")]);
+    let shared: Vec<_> = builder.build_shared(SOURCE).map(|token| (token.kind, token.position, token.span)).collect();
+    let borrowed = builder.build(SOURCE).map(|token| (token.kind.into_owned(), token.position, token.span));
+    let borrowed: Vec<_> = borrowed.collect();
+    assert_eq!(shared, borrowed);
+}
+
+#[test]
+fn shared_lexer_keeps_what_it_collects() {
+    let builder = LexerBuilder::new().line_comments(["//"]).block_comments([("/*", "*/")]).collect_comments(true);
+    let mut lexer = builder.build_shared(SOURCE);
+    lexer.by_ref().for_each(drop);
+    let comments: Vec<_> = lexer.lexer().comments().iter().map(|comment| comment.text(SOURCE)).collect();
+    assert_eq!(comments, ["// one", "/* two */"]);
+}
+
+#[test]
+fn shared_lexer_asks_to_cancel_periodically() {
+    let asked = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&asked);
+    let builder = LexerBuilder::new().cancel_when(move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+        false
+    });
+    let source = "x ".repeat(1000);
+    assert_eq!(builder.build_shared(source).count(), 1000);
+    assert!(asked.load(Ordering::Relaxed) < 10, "asked {} times", asked.load(Ordering::Relaxed));
+}
+
+#[test]
+fn shared_lexer_moves_across_threads() {
+    let lexer: SharedLexer = LexerBuilder::new().build_shared(SOURCE);
+    let tokens = std::thread::spawn(move || lexer.collect::<Vec<_>>()).join().unwrap();
+    assert_eq!(tokens[0].text(), "let");
+}
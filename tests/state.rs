@@ -0,0 +1,31 @@
+//! Resuming from a saved state lexes the same tokens as not stopping.
+use reusable_lexer::{Lexer, LexerBuilder};
+
+const SOURCE: &str = "let x = 1\n  if x { y = \"s\" } // done\n";
+
+/// Lexes a few tokens, then resumes a fresh lexer from the state and
+/// checks the rest match
+fn resumes_the_same(builder: &LexerBuilder, mut lexer: Lexer) {
+    lexer.by_ref().take(4).for_each(drop);
+    let state = lexer.state();
+    let mut resumed = builder.build_from_state(SOURCE, state).unwrap();
+    assert_eq!(resumed.source_name(), lexer.source_name());
+    assert_eq!(resumed.pos(), lexer.pos());
+    assert!(resumed.by_ref().eq(lexer.by_ref()));
+}
+
+#[test]
+fn state_keeps_the_offset_and_origin() {
+    let builder = LexerBuilder::new().keywords(["let", "if"]).line_comments(["//"]).indentation(true);
+    resumes_the_same(&builder, builder.build_with_offset(SOURCE, 40, (3, 7)));
+}
+
+#[test]
+fn state_keeps_the_name() {
+    let builder = LexerBuilder::new().string_delimiters(['"']).line_comments(["//"]);
+    let mut lexer = builder.build_named(SOURCE, "main.src");
+    lexer.by_ref().take(2).for_each(drop);
+    let resumed = Lexer::from_state(SOURCE, lexer.state()).unwrap();
+    assert_eq!(resumed.source_name(), Some("main.src"));
+    resumes_the_same(&builder, builder.build_named(SOURCE, "main.src"));
+}